@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+
+use chrono::{DateTime, Datelike, Days, IsoWeek, NaiveDate, NaiveTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, transmit_read_binary_apdu, MAX_READ_BINARY_LENGTH};
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF};
+use crate::error::CardReaderError;
+use crate::parse::{check_ring_pointer, parse_bcd_number, parse_time_real, take_array};
+use crate::transport::CardTransport;
+
+/// Length of the activity daily records buffer (`activityStructureLength`)
+pub const ACTIVITY_STRUCTURE_LENGTH: usize = 13776;
+/// Length of the header of a single `CardActivityDailyRecord`
+pub const ACTIVITY_DAILY_RECORD_HEADER_LENGTH: usize = 12;
+/// Number of minutes in a day
+pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Activity of the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Activity {
+    Break,
+    Available,
+    Work,
+    Driving,
+}
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Activity::Break => "Break/rest",
+            Activity::Available => "Available",
+            Activity::Work => "Work",
+            Activity::Driving => "Driving",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Card slot of the VU an activity was recorded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Slot {
+    Driver,
+    CoDriver,
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Slot::Driver => "driver",
+            Slot::CoDriver => "co-driver",
+        })
+    }
+}
+
+/// A single `ActivityChangeInfo` record, i.e. a change of activity at a given minute of the day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ActivityChange {
+    /// Slot the card was inserted in
+    pub slot: Slot,
+    /// Whether the vehicle was crewed (`true`) or driven single (`false`)
+    pub crew: bool,
+    /// Whether the card was inserted in the slot
+    pub card_inserted: bool,
+    pub activity: Activity,
+    /// Minutes since 00:00 UTC of the day the activity began
+    pub minute_of_day: u16,
+}
+
+impl fmt::Display for ActivityChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02} {:<12}", self.minute_of_day / 60, self.minute_of_day % 60, self.activity.to_string())?;
+        write!(f, " {}", self.slot)?;
+        write!(f, ", {}", if self.crew { "crew" } else { "single" })?;
+        if !self.card_inserted {
+            write!(f, ", card not inserted")?;
+        }
+        Ok(())
+    }
+}
+
+/// Activity of a single calendar day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActivityDay {
+    pub date: NaiveDate,
+    /// Number of days the card has been inserted in a VU
+    pub presence_counter: u16,
+    /// Distance travelled during the day in kilometres
+    pub day_distance: u16,
+    pub changes: Vec<ActivityChange>,
+}
+
+impl fmt::Display for ActivityDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  {} km, presence counter {}", self.date, self.day_distance, self.presence_counter)?;
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes an `ActivityChangeInfo` value
+///
+/// The value is laid out as `scpaattttttttttt` where `s` is the slot, `c` the driving status, `p` the card status,
+/// `aa` the activity and `t` the minutes since 00:00. That is, the slot is bit 15, the driving status bit 14, the card
+/// status bit 13, the activity bits 12-11 and the minutes bits 10-0.
+///
+/// # Arguments
+/// - `raw` - The 2-byte `ActivityChangeInfo` value
+///
+/// # Returns
+/// The decoded activity change
+pub fn decode_activity_change(raw: u16) -> ActivityChange {
+    let activity = match (raw >> 11) & 0b11 {
+        0 => Activity::Break,
+        1 => Activity::Available,
+        2 => Activity::Work,
+        _ => Activity::Driving,
+    };
+
+    ActivityChange {
+        slot: if raw & 0x8000 == 0 { Slot::Driver } else { Slot::CoDriver },
+        crew: raw & 0x4000 != 0,
+        card_inserted: raw & 0x2000 == 0,
+        activity,
+        minute_of_day: raw & 0x07FF,
+    }
+}
+
+/// Copies bytes out of a cyclic buffer, wrapping around to the start of the buffer at its end
+///
+/// # Arguments
+/// - `buffer` - The cyclic buffer
+/// - `start` - The offset to start copying from
+/// - `length` - The number of bytes to copy
+fn cyclic_bytes(buffer: &[u8], start: usize, length: usize) -> Vec<u8> {
+    (0..length).map(|i| buffer[(start + i) % buffer.len()]).collect()
+}
+
+/// Reads bytes out of the cyclic buffer of the currently selected driver activity EF, wrapping around to the start of
+/// the buffer at its end
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `buffer_len` - The length of the cyclic buffer, i.e. the EF without its pointers
+/// - `start` - The offset in the buffer to start reading from
+/// - `length` - The number of bytes to read
+fn read_cyclic(card: &impl CardTransport, buffer_len: usize, start: usize, length: usize) -> Result<Vec<u8>, CardReaderError> {
+    let mut bytes = Vec::with_capacity(length);
+    while bytes.len() < length {
+        let position = (start + bytes.len()) % buffer_len;
+        let chunk_len = (length - bytes.len()).min(buffer_len - position).min(MAX_READ_BINARY_LENGTH as usize);
+        let chunk = transmit_read_binary_apdu(card, (4 + position) as u16, chunk_len as u8)?;
+        if chunk.is_empty() {
+            return Err(CardReaderError::Truncated);
+        }
+        bytes.extend_from_slice(&chunk[..chunk.len().min(chunk_len)]);
+    }
+    Ok(bytes)
+}
+
+/// Parses a single `CardActivityDailyRecord`
+///
+/// # Arguments
+/// - `record` - The record including its header
+///
+/// # Returns
+/// The parsed activity day, or `None` if the record has no date
+fn parse_activity_day(record: &[u8]) -> Result<Option<ActivityDay>, CardReaderError> {
+    let (_previous_record_length, remaining) = take_array::<2>(record)?;
+    let (_record_length, remaining) = take_array::<2>(remaining)?;
+    let (date, remaining) = take_array(remaining)?;
+    let (presence_counter, remaining) = take_array::<2>(remaining)?;
+    let (day_distance, remaining) = take_array(remaining)?;
+
+    let Some(date) = parse_time_real(&date) else {
+        return Ok(None);
+    };
+
+    let changes = remaining
+        .chunks_exact(2)
+        .map(|change| decode_activity_change(u16::from_be_bytes([change[0], change[1]])))
+        .collect();
+
+    Ok(Some(ActivityDay {
+        date: date.date_naive(),
+        presence_counter: parse_bcd_number(&presence_counter)? as u16,
+        day_distance: u16::from_be_bytes(day_distance),
+        changes,
+    }))
+}
+
+/// Parses the contents of the driver activity EF
+///
+/// The daily records are stored in a cyclic buffer which is walked from the oldest record to the newest one,
+/// wrapping around the end of the buffer when the oldest record is located after the newest one. Gen1 and Gen2 EFs
+/// share the record layout and only differ in the length of the buffer, which is why the same parser is used for both.
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+///
+/// # Returns
+/// The activity days in chronological order
+pub fn parse_driver_activity(data: &[u8]) -> Result<Vec<ActivityDay>, CardReaderError> {
+    let (oldest, remaining) = take_array(data)?;
+    let (newest, buffer) = take_array(remaining)?;
+    let oldest = u16::from_be_bytes(oldest) as usize;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let mut days = Vec::new();
+    if buffer.len() < ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+        return Ok(days);
+    }
+    check_ring_pointer(oldest, buffer.len())?;
+    check_ring_pointer(newest, buffer.len())?;
+
+    let mut position = oldest;
+    // Every record is at least as long as its header, which bounds the walk even if the pointers are inconsistent
+    for _ in 0..buffer.len() / ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+        let header = cyclic_bytes(buffer, position, ACTIVITY_DAILY_RECORD_HEADER_LENGTH);
+        let record_length = u16::from_be_bytes([header[2], header[3]]) as usize;
+        if record_length < ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+            break;
+        }
+
+        let record = cyclic_bytes(buffer, position, record_length);
+        if let Some(day) = parse_activity_day(&record)? {
+            days.push(day);
+        }
+
+        if position == newest {
+            break;
+        }
+        position = (position + record_length) % buffer.len();
+    }
+
+    Ok(days)
+}
+
+/// Flattens activity days into their activity changes
+///
+/// # Arguments
+/// - `days` - The activity days in chronological order
+///
+/// # Returns
+/// An iterator over the time each activity began, the slot it was recorded in and the activity, in chronological order
+pub fn activity_changes(days: &[ActivityDay]) -> impl Iterator<Item = (DateTime<Utc>, Slot, Activity)> + '_ {
+    days.iter().flat_map(|day| {
+        let midnight = day.date.and_time(NaiveTime::MIN).and_utc();
+        day.changes
+            .iter()
+            .map(move |change| (midnight + TimeDelta::minutes(i64::from(change.minute_of_day)), change.slot, change.activity))
+    })
+}
+
+/// Computes the total driving time of a day
+///
+/// Each activity lasts until the next change, and the last activity of the day lasts until midnight.
+///
+/// # Arguments
+/// - `day` - The activity day
+///
+/// # Returns
+/// The number of minutes spent driving during the day
+pub fn daily_driving_minutes(day: &ActivityDay) -> u32 {
+    driving_minutes(day, None)
+}
+
+/// Computes the driving time of a day recorded with the card in the given slot
+///
+/// # Arguments
+/// - `day` - The activity day
+/// - `slot` - The slot to count the driving time of
+///
+/// # Returns
+/// The number of minutes spent driving with the card in the slot during the day
+pub fn daily_driving_minutes_in_slot(day: &ActivityDay, slot: Slot) -> u32 {
+    driving_minutes(day, Some(slot))
+}
+
+/// Computes the total driving time of each ISO week
+///
+/// # Arguments
+/// - `days` - The activity days
+///
+/// # Returns
+/// The number of minutes spent driving during each ISO week with activity days, in chronological order
+pub fn weekly_driving_minutes(days: &[ActivityDay]) -> Vec<(IsoWeek, u32)> {
+    weekly_minutes(days, None)
+}
+
+/// Computes the driving time of each ISO week recorded with the card in the given slot
+///
+/// # Arguments
+/// - `days` - The activity days
+/// - `slot` - The slot to count the driving time of
+///
+/// # Returns
+/// The number of minutes spent driving with the card in the slot during each ISO week with activity days, in
+/// chronological order
+pub fn weekly_driving_minutes_in_slot(days: &[ActivityDay], slot: Slot) -> Vec<(IsoWeek, u32)> {
+    weekly_minutes(days, Some(slot))
+}
+
+/// Sums the driving intervals of a day, counting only the intervals begun in the slot if one is given
+fn driving_minutes(day: &ActivityDay, slot: Option<Slot>) -> u32 {
+    let ends = day.changes.iter().skip(1).map(|change| change.minute_of_day).chain([MINUTES_PER_DAY]);
+    day.changes
+        .iter()
+        .zip(ends)
+        .filter(|(change, _)| change.activity == Activity::Driving && slot.is_none_or(|slot| change.slot == slot))
+        .map(|(change, end)| u32::from(end.min(MINUTES_PER_DAY).saturating_sub(change.minute_of_day)))
+        .sum()
+}
+
+/// Sums the driving time of the days of each ISO week, counting only the given slot if one is given
+fn weekly_minutes(days: &[ActivityDay], slot: Option<Slot>) -> Vec<(IsoWeek, u32)> {
+    let mut weeks = BTreeMap::new();
+    for day in days {
+        *weeks.entry(day.date.iso_week()).or_insert(0) += driving_minutes(day, slot);
+    }
+    weeks.into_iter().collect()
+}
+
+/// Writes the activity days as CSV, with one row per activity change
+///
+/// The rows have the columns `date`, `time`, `slot` and `activity`. The time is the start of the activity as `HH:MM`
+/// UTC; a change recorded at or past midnight is written on the following date, so the rows stay in chronological
+/// order across day boundaries.
+///
+/// # Arguments
+/// - `days` - The activity days to write
+/// - `w` - The writer to write the CSV to
+pub fn write_activity_csv(days: &[ActivityDay], w: impl Write) -> Result<(), CardReaderError> {
+    let mut writer = csv::Writer::from_writer(w);
+    writer.write_record(["date", "time", "slot", "activity"])?;
+
+    for day in days {
+        for change in &day.changes {
+            let days_past = u64::from(change.minute_of_day / MINUTES_PER_DAY);
+            let Some(date) = day.date.checked_add_days(Days::new(days_past)) else {
+                continue;
+            };
+            let minute_of_day = change.minute_of_day % MINUTES_PER_DAY;
+            let time = format!("{:02}:{:02}", minute_of_day / 60, minute_of_day % 60);
+            writer.write_record([date.to_string(), time, change.slot.to_string(), change.activity.to_string()])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the driver activity from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the driver activity from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The activity days stored on the card in chronological order
+pub fn read_driver_activity(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<ActivityDay>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF)?;
+    let data = read_ef_fully(card, record_ef_length(4, app_id.activity_structure_length as usize, 1)?, None)?;
+    parse_driver_activity(&data)
+}
+
+/// Reads the driver activity of a range of days from the tachograph application of the card
+///
+/// Instead of reading the whole EF, the cyclic buffer is walked back from the newest daily record by reading only the
+/// record headers, and only the records within the range are read in full. The walk stops at the first record older
+/// than `from`, so reading the last few weeks takes a fraction of the APDUs of [`read_driver_activity`].
+///
+/// # Arguments
+/// - `card` - The smart card to read the driver activity from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `from` - The first day to read
+/// - `to` - The last day to read
+///
+/// # Returns
+/// The activity days from `from` to `to`, both inclusive, in chronological order
+pub fn read_driver_activity_range(
+    card: &impl CardTransport,
+    generation: Option<Generation>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ActivityDay>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF)?;
+    let buffer_len = app_id.activity_structure_length as usize;
+
+    let pointers = transmit_read_binary_apdu(card, 0, 4)?;
+    let (oldest, remaining) = take_array(&pointers)?;
+    let (newest, _) = take_array(remaining)?;
+    let oldest = u16::from_be_bytes(oldest) as usize;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let mut days = Vec::new();
+    if buffer_len < ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+        return Ok(days);
+    }
+    check_ring_pointer(oldest, buffer_len)?;
+    check_ring_pointer(newest, buffer_len)?;
+
+    let mut position = newest;
+    // Every record is at least as long as its header, which bounds the walk even if the pointers are inconsistent
+    for _ in 0..buffer_len / ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+        let header = read_cyclic(card, buffer_len, position, ACTIVITY_DAILY_RECORD_HEADER_LENGTH)?;
+        let previous_record_length = u16::from_be_bytes([header[0], header[1]]) as usize;
+        let record_length = u16::from_be_bytes([header[2], header[3]]) as usize;
+        if record_length < ACTIVITY_DAILY_RECORD_HEADER_LENGTH {
+            break;
+        }
+
+        if let Some(date) = parse_time_real(&[header[4], header[5], header[6], header[7]]).map(|date| date.date_naive()) {
+            if date < from {
+                break;
+            }
+            if date <= to {
+                let record = read_cyclic(card, buffer_len, position, record_length)?;
+                days.extend(parse_activity_day(&record)?);
+            }
+        }
+
+        // The oldest record has no previous record
+        if position == oldest || previous_record_length == 0 {
+            break;
+        }
+        position = (position + buffer_len - previous_record_length % buffer_len) % buffer_len;
+    }
+
+    days.reverse();
+    Ok(days)
+}
@@ -0,0 +1,674 @@
+use log::{debug, error, trace, warn};
+
+use crate::error::CardReaderError;
+use crate::fcp::{parse_fcp, Fcp};
+use crate::hex::format_hex;
+use crate::transport::{reader_limits, CardProtocol, CardTransport};
+
+pub const SELECT_MF_COMMAND: &[u8] = b"\x00\xA4\x00\x0C\x02\x3F\x00";
+pub const SELECT_DF_COMMAND: &[u8] = b"\x00\xA4\x04\x0C\x06";
+pub const SELECT_APPLICATION_COMMAND: &[u8] = b"\x00\xA4\x04\x00";
+pub const SELECT_EF_UNDER_DF_COMMAND: &[u8] = b"\x00\xA4\x02\x0C\x02";
+pub const SELECT_EF_UNDER_DF_WITH_FCP_COMMAND: &[u8] = b"\x00\xA4\x02\x04\x02";
+pub const READ_BINARY_COMMAND: &[u8] = b"\x00\xB0";
+pub const PERFORM_HASH_OF_FILE_COMMAND: &[u8] = b"\x80\x2A\x90\x00";
+pub const COMPUTE_DIGITAL_SIGNATURE_COMMAND: &[u8] = b"\x00\x2A\x9E\x9A\x80";
+pub const COMPUTE_DIGITAL_SIGNATURE_GEN2_COMMAND: &[u8] = b"\x00\x2A\x9E\x9A\x00";
+pub const MANAGE_SECURITY_ENVIRONMENT_COMMAND: &[u8] = b"\x00\x22";
+pub const VERIFY_CERTIFICATE_COMMAND: &[u8] = b"\x00\x2A\x00\xAE";
+pub const INTERNAL_AUTHENTICATE_COMMAND: &[u8] = b"\x00\x88\x00\x00";
+pub const GET_CHALLENGE_COMMAND: &[u8] = b"\x00\x84\x00\x00\x08";
+pub const EXTERNAL_AUTHENTICATE_COMMAND: &[u8] = b"\x00\x82\x00\x00";
+pub const GET_RESPONSE_COMMAND: &[u8] = b"\x00\xC0\x00\x00";
+pub const VERIFY_COMMAND: &[u8] = b"\x00\x20\x00\x00";
+
+pub const STATUS_WORD_SUCCESS: (u8, u8) = (0x90, 0x00);
+pub const STATUS_WORD_END_OF_FILE: (u8, u8) = (0x62, 0x82);
+pub const STATUS_WORD_WRONG_OFFSET: (u8, u8) = (0x6B, 0x00);
+pub const STATUS_WORD_WRONG_LENGTH: (u8, u8) = (0x67, 0x00);
+pub const STATUS_WORD_FILE_NOT_FOUND: (u8, u8) = (0x6A, 0x82);
+pub const STATUS_WORD_WRONG_PARAMETERS: (u8, u8) = (0x6A, 0x86);
+pub const STATUS_WORD_REFERENCED_DATA_NOT_FOUND: (u8, u8) = (0x6A, 0x88);
+pub const STATUS_WORD_INSTRUCTION_NOT_SUPPORTED: (u8, u8) = (0x6D, 0x00);
+pub const STATUS_WORD_SECURITY_STATUS_NOT_SATISFIED: (u8, u8) = (0x69, 0x82);
+pub const STATUS_WORD_AUTHENTICATION_METHOD_BLOCKED: (u8, u8) = (0x69, 0x83);
+pub const STATUS_WORD_NO_CURRENT_EF: (u8, u8) = (0x69, 0x86);
+pub const STATUS_WORD_CLASS_NOT_SUPPORTED: (u8, u8) = (0x6E, 0x00);
+/// SW1 of `63 CX`, meaning a verification failed with X tries remaining
+pub const SW1_VERIFICATION_FAILED: u8 = 0x63;
+/// SW1 of `61 XX`, meaning XX more bytes of response data are available with GET RESPONSE
+pub const SW1_MORE_DATA: u8 = 0x61;
+/// SW1 of `6C XX`, meaning the command must be re-issued with XX as its Le
+pub const SW1_WRONG_LE: u8 = 0x6C;
+
+/// Maximum number of bytes requested with a single READ BINARY command
+pub const MAX_READ_BINARY_LENGTH: u8 = 0xFF;
+/// Smallest READ BINARY length [`read_ef_fully`] falls back to for cards that reject longer reads
+pub const MIN_READ_BINARY_LENGTH: u8 = 16;
+/// Maximum length of an EF addressable with READ BINARY offsets
+pub const MAX_EF_LENGTH: u16 = 0x7FFF;
+
+/// Selects the master file (MF) of the card
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_select_mf_apdu(card: &impl CardTransport) -> Result<Vec<u8>, CardReaderError> {
+    transmit_apdu(card, SELECT_MF_COMMAND)
+}
+
+/// Variant of the SELECT command that selected a DF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfSelectionMethod {
+    /// Full application identifier without response data (`P1=04 P2=0C`)
+    FullAid,
+    /// Full application identifier, returning the FCI (`P1=04 P2=00`)
+    FullAidWithFci,
+    /// Partial application identifier, i.e. all but the last byte, returning the FCI (`P1=04 P2=00`)
+    PartialAid,
+}
+
+impl DfSelectionMethod {
+    /// The selection variants in the order they are tried
+    pub const ALL: [DfSelectionMethod; 3] = [DfSelectionMethod::FullAid, DfSelectionMethod::FullAidWithFci, DfSelectionMethod::PartialAid];
+
+    /// Builds the SELECT command of this variant
+    ///
+    /// # Arguments
+    /// - `df` - The application identifier of the DF
+    ///
+    /// # Returns
+    /// The command APDU
+    pub fn apdu(self, df: &[u8]) -> Vec<u8> {
+        let name = match self {
+            DfSelectionMethod::FullAid | DfSelectionMethod::FullAidWithFci => df,
+            DfSelectionMethod::PartialAid => &df[..df.len().saturating_sub(1)],
+        };
+
+        match self {
+            DfSelectionMethod::FullAid => {
+                let mut select_df_apdu = SELECT_DF_COMMAND[..4].to_vec();
+                select_df_apdu.push(name.len() as u8);
+                select_df_apdu.extend_from_slice(name);
+                select_df_apdu
+            }
+            DfSelectionMethod::FullAidWithFci | DfSelectionMethod::PartialAid => {
+                let mut select_df_apdu = SELECT_APPLICATION_COMMAND.to_vec();
+                select_df_apdu.push(name.len() as u8);
+                select_df_apdu.extend_from_slice(name);
+                select_df_apdu.push(0x00);
+                select_df_apdu
+            }
+        }
+    }
+}
+
+/// Selects a dedicated file (DF) by its application identifier, trying the SELECT variants in turn
+///
+/// Some older cards don't accept the full application identifier with `P2=0C`. If the card refuses a variant with
+/// an error status word, the next one of [`DfSelectionMethod::ALL`] is tried.
+///
+/// # Arguments
+/// - `card` - The smart card to select the DF on
+/// - `df` - The application identifier of the DF
+///
+/// # Returns
+/// The variant the card accepted and its response, or the error of the first variant if the card refused them all
+pub fn select_df(card: &impl CardTransport, df: &[u8]) -> Result<(DfSelectionMethod, Vec<u8>), CardReaderError> {
+    let mut first_error = None;
+    for method in DfSelectionMethod::ALL {
+        match transmit_apdu(card, &method.apdu(df)) {
+            Ok(response) => {
+                debug!("Selected DF {} with {:?}", format_hex(df), method);
+                return Ok((method, response));
+            }
+            Err(e @ CardReaderError::UnexpectedStatusWord(_, _)) => {
+                first_error.get_or_insert(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(first_error.expect("At least one selection variant is tried"))
+}
+
+/// Selects a dedicated file (DF) by its application identifier
+///
+/// See [`select_df`] for the SELECT variants that are tried.
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `df` - The application identifier of the DF
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_select_df_apdu(card: &impl CardTransport, df: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    select_df(card, df).map(|(_, response)| response)
+}
+
+/// Selects an application by its full application identifier (AID), requesting its file control parameters
+///
+/// # Arguments
+/// - `card` - The smart card to select the application on
+/// - `aid` - The application identifier
+///
+/// # Returns
+/// The file control parameters of the application
+pub fn select_application(card: &impl CardTransport, aid: &[u8]) -> Result<Fcp, CardReaderError> {
+    let mut select_application_apdu = SELECT_APPLICATION_COMMAND.to_vec();
+    select_application_apdu.push(aid.len() as u8);
+    select_application_apdu.extend_from_slice(aid);
+    select_application_apdu.push(0x00);
+    Fcp::new(&transmit_apdu(card, &select_application_apdu)?)
+}
+
+/// Selects an elementary file (EF) under the currently selected DF
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `ef` - The file identifier of the EF
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_select_ef_under_df_apdu(card: &impl CardTransport, ef: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut select_ef_apdu = SELECT_EF_UNDER_DF_COMMAND.to_vec();
+    select_ef_apdu.extend_from_slice(ef);
+    transmit_apdu(card, &select_ef_apdu)
+}
+
+/// Selects an EF under the currently selected DF, requesting its file control parameters (FCP)
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `ef` - The file identifier of the EF
+///
+/// # Returns
+/// The FCP template returned by the smart card
+pub fn transmit_select_ef_with_fcp_apdu(card: &impl CardTransport, ef: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut select_ef_apdu = SELECT_EF_UNDER_DF_WITH_FCP_COMMAND.to_vec();
+    select_ef_apdu.extend_from_slice(ef);
+    select_ef_apdu.push(0x00);
+    transmit_apdu(card, &select_ef_apdu)
+}
+
+/// Reads binary data from the currently selected EF
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `offset` - The offset to start reading from, sent as P1 (high byte) and P2 (low byte)
+/// - `length` - The number of bytes to read
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_read_binary_apdu(card: &impl CardTransport, offset: u16, length: u8) -> Result<Vec<u8>, CardReaderError> {
+    let mut read_binary_apdu = READ_BINARY_COMMAND.to_vec();
+    read_binary_apdu.extend_from_slice(&offset.to_be_bytes());
+    read_binary_apdu.push(length);
+    transmit_apdu(card, &read_binary_apdu)
+}
+
+/// Makes the card compute the hash of the currently selected EF for a subsequent [`transmit_compute_digital_signature_apdu`]
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_perform_hash_of_file_apdu(card: &impl CardTransport) -> Result<Vec<u8>, CardReaderError> {
+    transmit_apdu(card, PERFORM_HASH_OF_FILE_COMMAND)
+}
+
+/// Makes the card sign the hash previously computed with [`transmit_perform_hash_of_file_apdu`]
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+///
+/// # Returns
+/// The 128 byte signature
+pub fn transmit_compute_digital_signature_apdu(card: &impl CardTransport) -> Result<Vec<u8>, CardReaderError> {
+    transmit_apdu(card, COMPUTE_DIGITAL_SIGNATURE_COMMAND)
+}
+
+/// Makes a Gen2 application sign the hash previously computed with [`transmit_perform_hash_of_file_apdu`]
+///
+/// The length of an ECDSA signature depends on the curve of the card key, so any length is accepted.
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+///
+/// # Returns
+/// The signature
+pub fn transmit_compute_digital_signature_gen2_apdu(card: &impl CardTransport) -> Result<Vec<u8>, CardReaderError> {
+    transmit_apdu(card, COMPUTE_DIGITAL_SIGNATURE_GEN2_COMMAND)
+}
+
+/// Sets a key reference in the security environment of the card with MANAGE SECURITY ENVIRONMENT
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `p1` - `81` to set the key verifying certificates, `C1` to set the key used for authentication
+/// - `key_reference` - The 8 byte reference of the key
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_manage_security_environment_apdu(card: &impl CardTransport, p1: u8, key_reference: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut mse_apdu = MANAGE_SECURITY_ENVIRONMENT_COMMAND.to_vec();
+    mse_apdu.extend_from_slice(&[p1, 0xB6, key_reference.len() as u8 + 2, 0x83, key_reference.len() as u8]);
+    mse_apdu.extend_from_slice(key_reference);
+    transmit_apdu(card, &mse_apdu)
+}
+
+/// Makes the card verify a certificate with PERFORM SECURITY OPERATION: VERIFY CERTIFICATE
+///
+/// The certificate is verified with the key previously set with [`transmit_manage_security_environment_apdu`].
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `certificate` - The certificate
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_verify_certificate_apdu(card: &impl CardTransport, certificate: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut verify_certificate_apdu = VERIFY_CERTIFICATE_COMMAND.to_vec();
+    verify_certificate_apdu.push(certificate.len() as u8);
+    verify_certificate_apdu.extend_from_slice(certificate);
+    transmit_apdu(card, &verify_certificate_apdu)
+}
+
+/// Makes the card authenticate itself with INTERNAL AUTHENTICATE
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `challenge` - The challenge, followed by the reference of the key the card encrypts its response with
+///
+/// # Returns
+/// The authentication token of the card
+pub fn transmit_internal_authenticate_apdu(card: &impl CardTransport, challenge: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut internal_authenticate_apdu = INTERNAL_AUTHENTICATE_COMMAND.to_vec();
+    internal_authenticate_apdu.push(challenge.len() as u8);
+    internal_authenticate_apdu.extend_from_slice(challenge);
+    internal_authenticate_apdu.push(0x80);
+    transmit_apdu(card, &internal_authenticate_apdu)
+}
+
+/// Requests an 8 byte random challenge from the card with GET CHALLENGE
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+///
+/// # Returns
+/// The challenge
+pub fn transmit_get_challenge_apdu(card: &impl CardTransport) -> Result<Vec<u8>, CardReaderError> {
+    transmit_apdu(card, GET_CHALLENGE_COMMAND)
+}
+
+/// Authenticates to the card with EXTERNAL AUTHENTICATE
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `cryptogram` - The response to the challenge previously returned by [`transmit_get_challenge_apdu`]
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_external_authenticate_apdu(card: &impl CardTransport, cryptogram: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut external_authenticate_apdu = EXTERNAL_AUTHENTICATE_COMMAND.to_vec();
+    external_authenticate_apdu.push(cryptogram.len() as u8);
+    external_authenticate_apdu.extend_from_slice(cryptogram);
+    transmit_apdu(card, &external_authenticate_apdu)
+}
+
+/// Verifies a PIN with VERIFY
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `pin` - The PIN, padded to its full length
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_verify_apdu(card: &impl CardTransport, pin: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let mut verify_apdu = VERIFY_COMMAND.to_vec();
+    verify_apdu.push(pin.len() as u8);
+    verify_apdu.extend_from_slice(pin);
+    transmit_apdu(card, &verify_apdu)
+}
+
+/// Reads binary data from the currently selected EF using an extended-length APDU
+///
+/// The command is encoded as `00 B0 P1 P2 00 Le_hi Le_lo`, which lets supporting cards and readers return more than 256 bytes at once.
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `offset` - The offset to start reading from, sent as P1 (high byte) and P2 (low byte)
+/// - `length` - The number of bytes to read
+///
+/// # Returns
+/// The response from the smart card
+pub fn transmit_read_binary_ext(card: &impl CardTransport, offset: u16, length: u16) -> Result<Vec<u8>, CardReaderError> {
+    let mut read_binary_apdu = READ_BINARY_COMMAND.to_vec();
+    read_binary_apdu.extend_from_slice(&offset.to_be_bytes());
+    read_binary_apdu.push(0x00);
+    read_binary_apdu.extend_from_slice(&length.to_be_bytes());
+    transmit_apdu(card, &read_binary_apdu)
+}
+
+/// Reads the whole currently selected EF with a single extended-length READ BINARY
+///
+/// Falls back to [`read_ef_fully`] if the card rejects the extended length with `67 00`.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `file_len` - The length of the EF in bytes
+///
+/// # Returns
+/// The contents of the EF
+pub fn read_ef_fully_ext(card: &impl CardTransport, file_len: u16) -> Result<Vec<u8>, CardReaderError> {
+    match transmit_read_binary_ext(card, 0, file_len) {
+        Ok(mut data) => {
+            data.truncate(file_len as usize);
+            Ok(data)
+        }
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_WRONG_LENGTH => {
+            warn!("Extended length READ BINARY rejected, retrying with short reads");
+            read_ef_fully(card, file_len, None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads the whole currently selected EF in the way the reader supports best
+///
+/// The EF is read with a single extended length READ BINARY, see [`read_ef_fully_ext`], if the reader reports room
+/// for the whole EF in one response. Otherwise, including readers that don't report their limits, it is read in short
+/// chunks with [`read_ef_fully`].
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `file_len` - The length of the EF in bytes
+///
+/// # Returns
+/// The contents of the EF
+pub fn read_ef_fully_auto(card: &impl CardTransport, file_len: u16) -> Result<Vec<u8>, CardReaderError> {
+    let limits = reader_limits(card);
+    if limits.supports_extended && limits.max_recv >= file_len as usize + 2 {
+        debug!("Reading {} bytes with an extended length READ BINARY, the reader allows {}", file_len, limits.max_recv);
+        read_ef_fully_ext(card, file_len)
+    } else {
+        read_ef_fully(card, file_len, None)
+    }
+}
+
+/// Reads the whole currently selected EF using as many READ BINARY commands as needed
+///
+/// Reading stops early if the card reports a wrong offset (`6B 00`), which means the file is shorter than `file_len`.
+/// Cards rejecting long reads with `67 00` or `6C XX` are read in halved chunks down to [`MIN_READ_BINARY_LENGTH`]
+/// bytes, see [`read_ef_fully_with_min_chunk`].
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `file_len` - The length of the EF in bytes
+/// - `progress` - Optional callback invoked with the number of bytes read so far and `file_len` after each chunk
+///
+/// # Returns
+/// The contents of the EF
+pub fn read_ef_fully(card: &impl CardTransport, file_len: u16, progress: Option<&mut dyn FnMut(usize, usize)>) -> Result<Vec<u8>, CardReaderError> {
+    read_ef_fully_with_min_chunk(card, file_len, MIN_READ_BINARY_LENGTH, progress)
+}
+
+/// Reads the whole currently selected EF using as many READ BINARY commands as needed, halving the chunk size when the
+/// card rejects a read
+///
+/// Reads start at [`MAX_READ_BINARY_LENGTH`] bytes. When the card answers `67 00` or `6C XX`, the read is retried
+/// with half the length, and the smaller length is kept for the rest of the EF. The read fails with the status word
+/// of the card once the length would drop below `min_chunk`, which is raised to 1 as a READ BINARY can't ask for
+/// 0 bytes.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `file_len` - The length of the EF in bytes
+/// - `min_chunk` - The smallest chunk size to try
+/// - `progress` - Optional callback invoked with the number of bytes read so far and `file_len` after each chunk
+///
+/// # Returns
+/// The contents of the EF
+pub fn read_ef_fully_with_min_chunk(
+    card: &impl CardTransport,
+    file_len: u16,
+    min_chunk: u8,
+    mut progress: Option<&mut dyn FnMut(usize, usize)>,
+) -> Result<Vec<u8>, CardReaderError> {
+    // Le 00 asks for 256 bytes, so the chunk size must not be halved down to 0
+    let min_chunk = min_chunk.max(1);
+    let mut data = Vec::with_capacity(file_len as usize);
+    let mut chunk_len = MAX_READ_BINARY_LENGTH;
+    while data.len() < file_len as usize {
+        let remaining = file_len as usize - data.len();
+        let length = remaining.min(chunk_len as usize) as u8;
+        let chunk = match transmit_read_binary_apdu(card, data.len() as u16, length) {
+            Ok(chunk) => chunk,
+            Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_WRONG_OFFSET => break,
+            Err(CardReaderError::UnexpectedStatusWord(sw1, sw2))
+                if ((sw1, sw2) == STATUS_WORD_WRONG_LENGTH || sw1 == SW1_WRONG_LE) && length / 2 >= min_chunk =>
+            {
+                chunk_len = length / 2;
+                debug!("Card rejected a {} byte READ BINARY with {:02X} {:02X}, reading {} bytes at a time", length, sw1, sw2, chunk_len);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if chunk.is_empty() {
+            break;
+        }
+        data.extend_from_slice(&chunk);
+        if let Some(progress) = progress.as_mut() {
+            progress(data.len().min(file_len as usize), file_len as usize);
+        }
+    }
+    data.truncate(file_len as usize);
+    Ok(data)
+}
+
+/// Selects an EF under the currently selected DF, taking its length from its FCP
+///
+/// Cards that refuse to return an FCP are sent a plain SELECT instead.
+///
+/// # Arguments
+/// - `card` - The smart card to select the EF on
+/// - `ef_id` - The file identifier of the EF
+///
+/// # Returns
+/// The readable length of the EF, or `None` if the card didn't report it
+pub fn select_ef_length(card: &impl CardTransport, ef_id: [u8; 2]) -> Result<Option<u16>, CardReaderError> {
+    match transmit_select_ef_with_fcp_apdu(card, &ef_id) {
+        Ok(fcp) => Ok(parse_fcp(&fcp).ok().and_then(|fcp| fcp.readable_length())),
+        Err(CardReaderError::UnexpectedStatusWord(_, _)) => {
+            transmit_select_ef_under_df_apdu(card, &ef_id)?;
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Selects an EF under the currently selected DF and reads all of it
+///
+/// The length of the EF is taken from the file size in its FCP, and the EF is read with
+/// [`read_ef_fully_auto`]. Cards that don't return an FCP are read in chunks until the end of the file is reached.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `ef_id` - The file identifier of the EF
+///
+/// # Returns
+/// The contents of the EF
+pub fn read_ef(card: &impl CardTransport, ef_id: [u8; 2]) -> Result<Vec<u8>, CardReaderError> {
+    match select_ef_length(card, ef_id)? {
+        Some(file_len) => read_ef_fully_auto(card, file_len.min(MAX_EF_LENGTH)),
+        None => read_ef_fully(card, MAX_EF_LENGTH, None),
+    }
+}
+
+/// Transmits an APDU to a smart card
+///
+/// If the card was reset or removed and reinserted, the transport reconnects to it. The reconnect leaves the card
+/// with the MF selected, so only SELECT commands of the MF or of a DF by name are retried. Other APDUs, e.g. READ
+/// BINARY of the EF selected before the reset, fail with `CardReset`, and the read has to be restarted from the
+/// selection of its DF, see [`retry_after_reset`](crate::transport::retry_after_reset). `CardReset` is also returned
+/// if the transport can't reconnect.
+///
+/// A `62 82` warning, which means the end of the file was reached before the requested number of bytes, is treated as a success.
+///
+/// Cards using T=0 may answer with `61 XX`, in which case the remaining response data is fetched with GET RESPONSE and
+/// concatenated, or with `6C XX`, in which case the command is re-issued once with XX as its Le. These status words
+/// are handled unless the transport reports that T=1 is in use.
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `apdu` - The APDU to transmit
+///
+/// # Returns
+/// The response data from the smart card without the trailing status word
+pub fn transmit_apdu(card: &impl CardTransport, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    let t0_status_words = card.protocol() != Some(CardProtocol::T1);
+    let mut response = transmit_logged(card, apdu)?;
+    let (_, (sw1, sw2)) = split_status_word(&response)?;
+    if t0_status_words && sw1 == SW1_WRONG_LE {
+        response = transmit_logged(card, &with_le(apdu, sw2))?;
+    }
+
+    let mut data = Vec::new();
+    loop {
+        let (chunk, status_word) = split_status_word(&response)?;
+        data.extend_from_slice(chunk);
+        if !t0_status_words || status_word.0 != SW1_MORE_DATA {
+            if status_word != STATUS_WORD_SUCCESS && status_word != STATUS_WORD_END_OF_FILE {
+                return Err(CardReaderError::UnexpectedStatusWord(status_word.0, status_word.1));
+            }
+            return Ok(data);
+        }
+
+        let mut get_response_apdu = GET_RESPONSE_COMMAND.to_vec();
+        get_response_apdu.push(status_word.1);
+        response = transmit_logged(card, &get_response_apdu)?;
+    }
+}
+
+/// Replaces the Le of a short command APDU, adding it if the command has none
+///
+/// # Arguments
+/// - `apdu` - The command APDU
+/// - `le` - The new Le
+///
+/// # Returns
+/// The command APDU with the new Le
+fn with_le(apdu: &[u8], le: u8) -> Vec<u8> {
+    let has_le = match apdu.get(4) {
+        None => false,
+        Some(_) if apdu.len() == 5 => true,
+        Some(&lc) => apdu.len() > 5 + lc as usize,
+    };
+
+    let mut apdu = apdu.to_vec();
+    if has_le {
+        apdu.pop();
+    }
+    apdu.push(le);
+    apdu
+}
+
+/// Transmits an APDU to a smart card, logging the command and the response
+///
+/// # Arguments
+/// - `card` - The smart card to transmit the APDU to
+/// - `apdu` - The APDU to transmit
+///
+/// # Returns
+/// The response from the smart card including the status word
+fn transmit_logged(card: &impl CardTransport, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+    trace!("> {}", format_hex(apdu));
+    let response = match card.transmit(apdu) {
+        Ok(response) => response,
+        #[cfg(feature = "pcsc")]
+        Err(CardReaderError::Pcsc(e @ (pcsc::Error::ResetCard | pcsc::Error::RemovedCard))) => {
+            if let Err(reconnect_error) = card.reconnect() {
+                error!("{}, failed to reconnect: {}", e, reconnect_error);
+                return Err(CardReaderError::CardReset);
+            }
+            if !is_independent_of_selection(apdu) {
+                warn!("{}, reconnected but the selection was lost", e);
+                return Err(CardReaderError::CardReset);
+            }
+            warn!("{}, reconnected and retrying the APDU", e);
+            match card.transmit(apdu) {
+                Ok(response) => response,
+                Err(e) => {
+                    error!("Failed to transmit APDU: {}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Err(e) => {
+            error!("Failed to transmit APDU: {}", e);
+            return Err(e);
+        }
+    };
+
+    let (data, status_word) = split_status_word(&response)?;
+    trace!("< {}", format_hex(data));
+    debug!("SW {:02X} {:02X}", status_word.0, status_word.1);
+    Ok(response)
+}
+
+/// Returns whether an APDU runs the same with the MF selected as with any other file selected, i.e. whether it can be
+/// retried after the card was reset
+///
+/// # Arguments
+/// - `apdu` - The command APDU
+#[cfg(feature = "pcsc")]
+fn is_independent_of_selection(apdu: &[u8]) -> bool {
+    // SELECT of the MF (P1=00) or of a DF by its name (P1=04)
+    matches!(apdu, [0x00, 0xA4, 0x00 | 0x04, ..])
+}
+
+/// Splits a response APDU into its data and the trailing SW1/SW2 status word
+///
+/// # Arguments
+/// - `response` - The response APDU
+///
+/// # Returns
+/// A tuple containing the response data and the status word
+pub fn split_status_word(response: &[u8]) -> Result<(&[u8], (u8, u8)), CardReaderError> {
+    if response.len() < 2 {
+        return Err(CardReaderError::Truncated);
+    }
+    let (data, status_word) = response.split_at(response.len() - 2);
+    Ok((data, (status_word[0], status_word[1])))
+}
+
+/// Describes the meaning of a status word
+///
+/// # Arguments
+/// - `sw1` - The first byte of the status word
+/// - `sw2` - The second byte of the status word
+///
+/// # Returns
+/// A short description of the status word, or `unknown status word` for status words not defined by ISO/IEC 7816-4
+pub fn status_word_meaning(sw1: u8, sw2: u8) -> &'static str {
+    match (sw1, sw2) {
+        STATUS_WORD_SUCCESS => "OK",
+        STATUS_WORD_END_OF_FILE => "end of file reached before reading Le bytes",
+        STATUS_WORD_WRONG_OFFSET => "wrong parameters P1-P2, e.g. an offset beyond the end of the EF",
+        STATUS_WORD_WRONG_LENGTH => "wrong length",
+        STATUS_WORD_FILE_NOT_FOUND => "file not found",
+        STATUS_WORD_WRONG_PARAMETERS => "incorrect parameters P1-P2",
+        STATUS_WORD_REFERENCED_DATA_NOT_FOUND => "referenced data not found",
+        STATUS_WORD_INSTRUCTION_NOT_SUPPORTED => "instruction not supported",
+        STATUS_WORD_CLASS_NOT_SUPPORTED => "class not supported",
+        STATUS_WORD_SECURITY_STATUS_NOT_SATISFIED => "security status not satisfied",
+        STATUS_WORD_AUTHENTICATION_METHOD_BLOCKED => "authentication method blocked",
+        STATUS_WORD_NO_CURRENT_EF => "command not allowed, no EF selected",
+        (SW1_VERIFICATION_FAILED, _) => "verification failed",
+        (SW1_MORE_DATA, _) => "more response data available",
+        (SW1_WRONG_LE, _) => "wrong Le, the exact length is in SW2",
+        _ => "unknown status word",
+    }
+}
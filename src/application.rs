@@ -0,0 +1,211 @@
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, STATUS_WORD_FILE_NOT_FOUND};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_APPLICATION_IDENTIFICATION_EF, TACHOGRAPH_APPLICATION_IDENTIFICATION_V2_EF};
+use crate::error::CardReaderError;
+use crate::parse::Reader;
+use crate::transport::CardTransport;
+
+/// Length of the Gen1 application identification
+pub const APPLICATION_IDENTIFICATION_LENGTH: usize = 10;
+/// Length of the Gen2 application identification
+pub const GEN2_APPLICATION_IDENTIFICATION_LENGTH: usize = 17;
+/// Length of the Gen2v2 application identification
+pub const APPLICATION_IDENTIFICATION_V2_LENGTH: usize = 10;
+
+/// Type of a tachograph card, i.e. the `typeOfTachographCardId` of the application identification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CardType {
+    Driver,
+    Workshop,
+    Control,
+    Company,
+    Unknown(u8),
+}
+
+impl From<u8> for CardType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => CardType::Driver,
+            0x02 => CardType::Workshop,
+            0x03 => CardType::Control,
+            0x04 => CardType::Company,
+            other => CardType::Unknown(other),
+        }
+    }
+}
+
+/// Reads the type of the card from the application identification of the tachograph application
+///
+/// Only the first byte is read, as the application identification of company, control and workshop cards is laid out
+/// differently from that of driver cards.
+///
+/// # Arguments
+/// - `card` - The smart card to read the card type from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The type of the card
+pub fn read_card_type(card: &impl CardTransport, generation: Option<Generation>) -> Result<CardType, CardReaderError> {
+    select_tachograph_ef(card, generation, TACHOGRAPH_APPLICATION_IDENTIFICATION_EF)?;
+    let data = read_ef_fully(card, 1, None)?;
+    let card_type = data.first().ok_or(CardReaderError::Truncated)?;
+    Ok(CardType::from(*card_type))
+}
+
+/// The application identification of a driver card, i.e. its structure version and the capacity of its record EFs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AppId {
+    /// Generation of the application the identification was read from
+    pub generation: Generation,
+    /// Type of the card
+    pub card_type: CardType,
+    /// Version of the card structure, major version first
+    pub structure_version: [u8; 2],
+    /// Number of event records the card stores per event type
+    pub events_per_type: u8,
+    /// Number of fault records the card stores per fault type
+    pub faults_per_type: u8,
+    /// Number of bytes available for driver activity records
+    pub activity_structure_length: u16,
+    /// Number of vehicle records the card stores
+    pub vehicle_records: u16,
+    /// Number of place records the card stores
+    pub place_records: u16,
+    /// Number of GNSS accumulated driving records the card stores, Gen2 only
+    pub gnss_ad_records: Option<u16>,
+    /// Number of specific condition records the card stores, Gen2 only
+    pub specific_condition_records: Option<u16>,
+    /// Number of vehicle unit records the card stores, Gen2 only
+    pub vehicle_unit_records: Option<u16>,
+}
+
+impl AppId {
+    /// Parses the application identification
+    ///
+    /// Gen1 stores the number of place records in a single byte and has no counts for the records added in Gen2.
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF
+    /// - `generation` - The generation of the application the EF was read from
+    ///
+    /// # Returns
+    /// The application identification
+    pub fn new(data: &[u8], generation: Generation) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        let card_type = CardType::from(reader.take_u8()?);
+        let structure_version = reader.take_array()?;
+        let events_per_type = reader.take_u8()?;
+        let faults_per_type = reader.take_u8()?;
+        let activity_structure_length = reader.take_u16_be()?;
+        let vehicle_records = reader.take_u16_be()?;
+
+        let (place_records, gnss_ad_records, specific_condition_records, vehicle_unit_records) = match generation {
+            Generation::Gen1 => (reader.take_u8()? as u16, None, None, None),
+            Generation::Gen2 => (reader.take_u16_be()?, Some(reader.take_u16_be()?), Some(reader.take_u16_be()?), Some(reader.take_u16_be()?)),
+        };
+
+        Ok(AppId {
+            generation,
+            card_type,
+            structure_version,
+            events_per_type,
+            faults_per_type,
+            activity_structure_length,
+            vehicle_records,
+            place_records,
+            gnss_ad_records,
+            specific_condition_records,
+            vehicle_unit_records,
+        })
+    }
+}
+
+/// Reads the application identification of the tachograph application
+///
+/// # Arguments
+/// - `card` - The smart card to read the application identification from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The application identification
+pub fn read_application_identification(card: &impl CardTransport, generation: Option<Generation>) -> Result<AppId, CardReaderError> {
+    let generation = select_tachograph_ef(card, generation, TACHOGRAPH_APPLICATION_IDENTIFICATION_EF)?;
+    let length = match generation {
+        Generation::Gen1 => APPLICATION_IDENTIFICATION_LENGTH,
+        Generation::Gen2 => GEN2_APPLICATION_IDENTIFICATION_LENGTH,
+    };
+    let data = read_ef_fully(card, length as u16, None)?;
+    AppId::new(&data, generation)
+}
+
+/// The Gen2v2 application identification of a driver card, i.e. the capacity of the record EFs added in Gen2v2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AppIdV2 {
+    /// Number of border crossing records the card stores
+    pub border_crossing_records: u16,
+    /// Number of load/unload records the card stores
+    pub load_unload_records: u16,
+    /// Number of load type entry records the card stores
+    pub load_type_entry_records: u16,
+    /// Maximum length of the VU configuration data
+    pub vu_configuration_length_range: u16,
+}
+
+impl AppIdV2 {
+    /// Parses the Gen2v2 application identification
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (10 bytes)
+    ///
+    /// # Returns
+    /// The application identification
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        // Length of the following data
+        reader.take_u16_be()?;
+        Ok(AppIdV2 {
+            border_crossing_records: reader.take_u16_be()?,
+            load_unload_records: reader.take_u16_be()?,
+            load_type_entry_records: reader.take_u16_be()?,
+            vu_configuration_length_range: reader.take_u16_be()?,
+        })
+    }
+}
+
+/// Reads the Gen2v2 application identification of the Gen2 tachograph application
+///
+/// # Arguments
+/// - `card` - The smart card to read the application identification from
+///
+/// # Returns
+/// The application identification, or `None` for cards older than Gen2v2 that don't have the EF
+pub fn read_application_identification_v2(card: &impl CardTransport) -> Result<Option<AppIdV2>, CardReaderError> {
+    match select_tachograph_ef(card, Some(Generation::Gen2), TACHOGRAPH_APPLICATION_IDENTIFICATION_V2_EF) {
+        Ok(_) => {}
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let data = read_ef_fully(card, APPLICATION_IDENTIFICATION_V2_LENGTH as u16, None)?;
+    AppIdV2::new(&data).map(Some)
+}
+
+/// Computes the length of a record EF from a record count of the application identification
+///
+/// The counts come from the card, so a damaged application identification could give a length that overflows or
+/// doesn't fit in the 16 bits of a file size.
+///
+/// # Arguments
+/// - `header_length` - The number of bytes before the records, e.g. for the newest record pointer
+/// - `record_count` - The number of records in the EF
+/// - `record_length` - The length of a single record
+///
+/// # Returns
+/// The length of the EF, or `InvalidEfLength` if it doesn't fit in 16 bits
+pub fn record_ef_length(header_length: usize, record_count: usize, record_length: usize) -> Result<u16, CardReaderError> {
+    record_count
+        .checked_mul(record_length)
+        .and_then(|length| length.checked_add(header_length))
+        .and_then(|length| u16::try_from(length).ok())
+        .ok_or(CardReaderError::InvalidEfLength { header_length, record_count, record_length })
+}
@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::card::Generation;
+use crate::driver_card::{read_full_card, DriverCard};
+use crate::error::CardReaderError;
+use crate::transport::CardTransport;
+
+/// Runs a blocking operation on a card on the tokio blocking thread pool
+///
+/// The card is locked for the duration of the operation, so operations on the same card never interleave their
+/// APDUs.
+///
+/// # Arguments
+/// - `card` - The smart card to run the operation on
+/// - `operation` - The blocking operation
+///
+/// # Returns
+/// The result of the operation
+pub async fn with_card_blocking<T, R, F>(card: Arc<Mutex<T>>, operation: F) -> Result<R, CardReaderError>
+where
+    T: CardTransport + Send + 'static,
+    R: Send + 'static,
+    F: FnOnce(&T) -> Result<R, CardReaderError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let card = card.lock().unwrap_or_else(PoisonError::into_inner);
+        operation(&card)
+    })
+    .await?
+}
+
+/// Reads all supported EFs from the tachograph application of the card without blocking the async runtime
+///
+/// # Arguments
+/// - `card` - The smart card to read
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The data read from the card
+pub async fn read_full_card_async<T>(card: Arc<Mutex<T>>, generation: Option<Generation>) -> Result<DriverCard, CardReaderError>
+where
+    T: CardTransport + Send + 'static,
+{
+    with_card_blocking(card, move |card| read_full_card(card, generation)).await
+}
@@ -0,0 +1,64 @@
+#[cfg(feature = "pcsc")]
+use pcsc::Attribute;
+use serde::Serialize;
+
+use crate::error::CardReaderError;
+use crate::parse::take_n;
+
+/// The answer to reset (ATR) of a card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Atr {
+    pub raw: Vec<u8>,
+    /// Transmission protocols indicated in the interface bytes, e.g. 0 for T=0 and 1 for T=1
+    pub protocols: Vec<u8>,
+    /// Historical bytes, identifying the chip and its operating system
+    pub historical_bytes: Vec<u8>,
+}
+
+/// Reads the ATR of the connected card
+///
+/// # Arguments
+/// - `card` - The connected card
+///
+/// # Returns
+/// The ATR, or an empty vector if the reader doesn't provide it
+#[cfg(feature = "pcsc")]
+pub fn card_atr(card: &pcsc::Card) -> Vec<u8> {
+    card.get_attribute_owned(Attribute::AtrString).unwrap_or_default()
+}
+
+/// Parses an ATR into its protocols and historical bytes
+///
+/// # Arguments
+/// - `atr` - The ATR
+///
+/// # Returns
+/// The parsed ATR
+pub fn parse_atr(atr: &[u8]) -> Result<Atr, CardReaderError> {
+    let (header, mut remaining) = take_n(2, atr)?;
+    let historical_length = (header[1] & 0x0F) as usize;
+    let mut indicator = header[1];
+    let mut protocols = Vec::new();
+
+    loop {
+        // The high nibble of T0 and of each TDi tells which of TA, TB, TC and TD follow
+        let interface_length = (indicator >> 4).count_ones() as usize;
+        let (interface_bytes, rest) = take_n(interface_length, remaining)?;
+        remaining = rest;
+
+        if indicator & 0x80 == 0 {
+            break;
+        }
+        let td = interface_bytes[interface_length - 1];
+        protocols.push(td & 0x0F);
+        indicator = td;
+    }
+
+    let (historical_bytes, _) = take_n(historical_length, remaining)?;
+
+    Ok(Atr {
+        raw: atr.to_vec(),
+        protocols,
+        historical_bytes: historical_bytes.to_vec(),
+    })
+}
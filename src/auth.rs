@@ -0,0 +1,210 @@
+use num_bigint::BigUint;
+use rand::RngCore;
+use sha1::{Digest, Sha1};
+
+use crate::apdu::{
+    transmit_external_authenticate_apdu, transmit_get_challenge_apdu, transmit_internal_authenticate_apdu, transmit_manage_security_environment_apdu,
+    transmit_verify_apdu, transmit_verify_certificate_apdu, STATUS_WORD_AUTHENTICATION_METHOD_BLOCKED, STATUS_WORD_WRONG_LENGTH, SW1_VERIFICATION_FAILED,
+};
+use crate::error::CardReaderError;
+use crate::parse::take_n;
+use crate::transport::CardTransport;
+use crate::verify::{left_pad, read_card_certificate, rsa_apply, CardCertificate, RsaPublicKey, RSA_MODULUS_LENGTH};
+
+/// `P1` of MANAGE SECURITY ENVIRONMENT setting the key that verifies the next certificate
+const MSE_SET_CERTIFICATE_VERIFICATION_KEY: u8 = 0x81;
+/// `P1` of MANAGE SECURITY ENVIRONMENT setting the key of the party the card authenticates with
+const MSE_SET_AUTHENTICATION_KEY: u8 = 0xC1;
+
+/// Length of the random padding in an authentication token
+const PRND_LENGTH: usize = 90;
+/// Length of the key fragment each party contributes to the session key
+const KEY_FRAGMENT_LENGTH: usize = 16;
+/// Length of a workshop card PIN once padded with `FF` bytes
+const PIN_LENGTH: usize = 8;
+
+/// A Gen1 RSA private key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPrivateKey {
+    pub modulus: Vec<u8>,
+    pub private_exponent: Vec<u8>,
+}
+
+impl RsaPrivateKey {
+    /// Applies the private key to a message, i.e. computes `message ^ d mod n`
+    fn apply(&self, message: &[u8]) -> Vec<u8> {
+        rsa_apply(&self.modulus, &self.private_exponent, message)
+    }
+}
+
+/// The credentials of an authenticating party, e.g. a workshop or a control card holder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderKey {
+    /// Private key of the party
+    pub private_key: RsaPrivateKey,
+    /// Certificate of the public key of the party (194 bytes)
+    pub certificate: Vec<u8>,
+    /// Certificate of the member state key that signed the certificate of the party (194 bytes)
+    pub member_state_certificate: Vec<u8>,
+    /// The European root public key, used to verify the certificates of both the party and the card
+    pub root_key: RsaPublicKey,
+}
+
+/// Keys of a session established with [`authenticate_gen1`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gen1Session {
+    /// TDES session key, i.e. the key fragments of the card and the reader XORed together
+    pub session_key: [u8; 16],
+    /// Initial send sequence counter for secure messaging
+    pub send_sequence_counter: [u8; 8],
+}
+
+/// Runs the Gen1 mutual authentication with the tachograph application of the card
+///
+/// The card verifies the certificate chain of the reader key, authenticates itself with INTERNAL AUTHENTICATE and
+/// then the reader authenticates with GET CHALLENGE and EXTERNAL AUTHENTICATE. Both parties contribute a key
+/// fragment to the returned session keys.
+///
+/// Gen1 driver cards allow READ BINARY on all their EFs without authentication. The authentication unlocks the
+/// commands with a `PRO SM` access condition, i.e. UPDATE BINARY of the EFs under the tachograph DF and reading
+/// them with secure messaging, and it is a prerequisite for the control and workshop card specific commands.
+///
+/// # Arguments
+/// - `card` - The smart card to authenticate with
+/// - `reader_key` - The credentials to authenticate with
+///
+/// # Returns
+/// The session keys, or `AuthenticationFailed` if the card could not prove its identity
+pub fn authenticate_gen1(card: &impl CardTransport, reader_key: &ReaderKey) -> Result<Gen1Session, CardReaderError> {
+    let member_state_certificate = CardCertificate::new(&reader_key.member_state_certificate, &reader_key.root_key)?;
+    let reader_certificate = CardCertificate::new(&reader_key.certificate, &member_state_certificate.public_key)?;
+    let reader_reference = reader_certificate.public_key.key_identifier;
+    let card_certificate = read_card_certificate(card, &reader_key.root_key)?;
+    let card_reference = card_certificate.public_key.key_identifier;
+
+    // Let the card verify the certificate chain of the reader key
+    transmit_manage_security_environment_apdu(card, MSE_SET_CERTIFICATE_VERIFICATION_KEY, &member_state_certificate.authority_reference)?;
+    transmit_verify_certificate_apdu(card, &reader_key.member_state_certificate)?;
+    transmit_manage_security_environment_apdu(card, MSE_SET_CERTIFICATE_VERIFICATION_KEY, &reader_certificate.authority_reference)?;
+    transmit_verify_certificate_apdu(card, &reader_key.certificate)?;
+    transmit_manage_security_environment_apdu(card, MSE_SET_AUTHENTICATION_KEY, &reader_reference)?;
+
+    // Internal authentication, the card signs the challenge and encrypts the signature for the reader
+    let mut rnd1 = [0; 8];
+    rand::thread_rng().fill_bytes(&mut rnd1);
+    let token = transmit_internal_authenticate_apdu(card, &[rnd1.as_slice(), &reader_reference].concat())?;
+    let signature = reader_key.private_key.apply(&token);
+    let card_key_fragment = open_token(&signature, &card_certificate.public_key, &[&rnd1, &reader_reference])?;
+
+    // External authentication, the reader signs the challenge of the card and encrypts the signature for the card
+    let rnd3 = transmit_get_challenge_apdu(card)?;
+    let mut reader_key_fragment = [0; KEY_FRAGMENT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut reader_key_fragment);
+    let token = seal_token(&reader_key_fragment, &reader_key.private_key, &card_certificate.public_key, &[&rnd3, &card_reference]);
+    transmit_external_authenticate_apdu(card, &token)?;
+
+    let mut session_key = [0; 16];
+    for (i, byte) in session_key.iter_mut().enumerate() {
+        *byte = reader_key_fragment[i] ^ card_key_fragment[i];
+    }
+
+    let mut send_sequence_counter = [0; 8];
+    send_sequence_counter[..4].copy_from_slice(rnd3.get(4..8).ok_or(CardReaderError::Truncated)?);
+    send_sequence_counter[4..].copy_from_slice(&rnd1[4..]);
+
+    Ok(Gen1Session { session_key, send_sequence_counter })
+}
+
+/// Verifies the PIN of a workshop card with VERIFY
+///
+/// The PIN consists of 4 to 8 ASCII digits and is padded with `FF` bytes to 8 bytes before it is sent.
+///
+/// # Arguments
+/// - `card` - The smart card to verify the PIN with
+/// - `pin` - The PIN as ASCII digits, e.g. `b"1234"`
+///
+/// # Returns
+/// `WrongPin` with the remaining tries if the card rejected the PIN, `PinBlocked` if no tries are left and
+/// `InvalidPinLength` if the PIN is longer than 8 bytes or the card refused its length
+pub fn verify_pin(card: &impl CardTransport, pin: &[u8]) -> Result<(), CardReaderError> {
+    if pin.len() > PIN_LENGTH {
+        return Err(CardReaderError::InvalidPinLength);
+    }
+
+    let mut padded_pin = [0xFF; PIN_LENGTH];
+    padded_pin[..pin.len()].copy_from_slice(pin);
+
+    match transmit_verify_apdu(card, &padded_pin) {
+        Ok(_) => Ok(()),
+        Err(CardReaderError::UnexpectedStatusWord(SW1_VERIFICATION_FAILED, sw2)) if sw2 & 0xF0 == 0xC0 => match sw2 & 0x0F {
+            0 => Err(CardReaderError::PinBlocked),
+            tries_left => Err(CardReaderError::WrongPin { tries_left }),
+        },
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_AUTHENTICATION_METHOD_BLOCKED => Err(CardReaderError::PinBlocked),
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_WRONG_LENGTH => Err(CardReaderError::InvalidPinLength),
+        Err(e) => Err(e),
+    }
+}
+
+/// Verifies the signed authentication token of the other party and extracts its key fragment
+///
+/// The token is `6A || PRND || K || H || BC` signed with the key of the other party, where `H` is the SHA-1 hash of
+/// `PRND || K` followed by the challenge and the key reference.
+///
+/// # Arguments
+/// - `signature` - The decrypted signature of the token
+/// - `public_key` - Public key of the party that signed the token
+/// - `challenge` - The challenge and the key reference the token must be bound to
+///
+/// # Returns
+/// The key fragment of the other party
+fn open_token(signature: &[u8], public_key: &RsaPublicKey, challenge: &[&[u8]]) -> Result<Vec<u8>, CardReaderError> {
+    let mut token = public_key.apply(signature);
+    if token.last() != Some(&0xBC) {
+        // The signer sends the smaller of the signature and the modulus minus the signature
+        let modulus = BigUint::from_bytes_be(&public_key.modulus);
+        token = left_pad(&(modulus - BigUint::from_bytes_be(&token)).to_bytes_be(), RSA_MODULUS_LENGTH);
+    }
+
+    let (header, remaining) = take_n(1, &token)?;
+    let (padding_and_fragment, remaining) = take_n(PRND_LENGTH + KEY_FRAGMENT_LENGTH, remaining)?;
+    let (hash, trailer) = take_n(20, remaining)?;
+    if header != [0x6A] || trailer != [0xBC] {
+        return Err(CardReaderError::AuthenticationFailed);
+    }
+
+    let mut hasher = Sha1::new();
+    hasher.update(padding_and_fragment);
+    challenge.iter().for_each(|part| hasher.update(part));
+    if hasher.finalize().as_slice() != hash {
+        return Err(CardReaderError::AuthenticationFailed);
+    }
+
+    Ok(padding_and_fragment[PRND_LENGTH..].to_vec())
+}
+
+/// Builds an authentication token, signs it with the private key and encrypts it for the other party
+///
+/// # Arguments
+/// - `key_fragment` - The key fragment to send
+/// - `private_key` - Private key to sign the token with
+/// - `public_key` - Public key of the party the token is encrypted for
+/// - `challenge` - The challenge and the key reference to bind the token to
+///
+/// # Returns
+/// The encrypted token
+fn seal_token(key_fragment: &[u8], private_key: &RsaPrivateKey, public_key: &RsaPublicKey, challenge: &[&[u8]]) -> Vec<u8> {
+    let mut padding = [0; PRND_LENGTH];
+    rand::thread_rng().fill_bytes(&mut padding);
+
+    let mut hasher = Sha1::new();
+    hasher.update(padding);
+    hasher.update(key_fragment);
+    challenge.iter().for_each(|part| hasher.update(part));
+
+    let token = [&[0x6A], padding.as_slice(), key_fragment, hasher.finalize().as_slice(), &[0xBC]].concat();
+    let signature = BigUint::from_bytes_be(&private_key.apply(&token));
+    let complement = BigUint::from_bytes_be(&private_key.modulus) - &signature;
+    let signature = left_pad(&signature.min(complement).to_bytes_be(), RSA_MODULUS_LENGTH);
+    public_key.apply(&signature)
+}
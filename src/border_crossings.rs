@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, STATUS_WORD_FILE_NOT_FOUND};
+use crate::application::{read_application_identification_v2, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_BORDER_CROSSINGS_EF};
+use crate::error::CardReaderError;
+use crate::gnss::{GnssPlaceAuthRecord, GNSS_PLACE_AUTH_RECORD_LENGTH};
+use crate::nation::{nation_alpha, nation_name};
+use crate::parse::{check_ring_pointer, parse_odometer, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single `CardBorderCrossingRecord`
+pub const BORDER_CROSSING_RECORD_LENGTH: usize = 17;
+/// Maximum number of border crossing records a card stores, used if the card has no Gen2v2 application identification
+pub const NO_OF_BORDER_CROSSING_RECORDS: usize = 1100;
+
+/// A border crossing of the vehicle, recorded by Gen2v2 cards
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BorderCrossing {
+    /// `NationNumeric` code of the country the vehicle left
+    pub country_left: u8,
+    /// `NationNumeric` code of the country the vehicle entered
+    pub country_entered: u8,
+    /// Time of the crossing
+    pub time_stamp: DateTime<Utc>,
+    /// GNSS position of the vehicle at the crossing
+    pub gnss_place: GnssPlaceAuthRecord,
+    /// Odometer of the vehicle at the crossing, in kilometres
+    pub odometer: u32,
+}
+
+impl BorderCrossing {
+    /// Parses a single `CardBorderCrossingRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (17 bytes)
+    ///
+    /// # Returns
+    /// The parsed border crossing, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let ([country_left, country_entered], remaining) = take_array(record)?;
+        let (gnss_place, remaining) = take_n(GNSS_PLACE_AUTH_RECORD_LENGTH, remaining)?;
+        let (odometer, _) = take_array(remaining)?;
+
+        let gnss_place = GnssPlaceAuthRecord::new(gnss_place)?;
+        let Some(time_stamp) = gnss_place.time_stamp else {
+            return Ok(None);
+        };
+
+        Ok(Some(BorderCrossing {
+            country_left,
+            country_entered,
+            time_stamp,
+            gnss_place,
+            odometer: parse_odometer(&odometer),
+        }))
+    }
+
+    /// Returns the name of the country the vehicle left
+    pub fn country_left_name(&self) -> Option<&'static str> {
+        nation_name(self.country_left)
+    }
+
+    /// Returns the distinguishing sign of the country the vehicle left
+    pub fn country_left_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.country_left)
+    }
+
+    /// Returns the name of the country the vehicle entered
+    pub fn country_entered_name(&self) -> Option<&'static str> {
+        nation_name(self.country_entered)
+    }
+
+    /// Returns the distinguishing sign of the country the vehicle entered
+    pub fn country_entered_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.country_entered)
+    }
+}
+
+/// Parses the contents of the border crossings EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+///
+/// # Returns
+/// The border crossings in chronological order, skipping empty slots
+pub fn parse_border_crossings(data: &[u8]) -> Result<Vec<BorderCrossing>, CardReaderError> {
+    let (newest, records) = take_array(data)?;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let records = records.chunks_exact(BORDER_CROSSING_RECORD_LENGTH).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut border_crossings = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(border_crossing) = BorderCrossing::new(records[(newest + 1 + i) % records.len()])? {
+            border_crossings.push(border_crossing);
+        }
+    }
+    Ok(border_crossings)
+}
+
+/// Reads the border crossings from the Gen2 tachograph application of the card
+///
+/// The number of records comes from the Gen2v2 application identification of the card.
+///
+/// # Arguments
+/// - `card` - The smart card to read the border crossings from
+///
+/// # Returns
+/// The border crossings in chronological order, empty for cards older than Gen2v2 that don't have the EF
+pub fn read_border_crossings(card: &impl CardTransport) -> Result<Vec<BorderCrossing>, CardReaderError> {
+    let record_count = read_application_identification_v2(card)?.map_or(NO_OF_BORDER_CROSSING_RECORDS, |app_id| app_id.border_crossing_records as usize);
+    match select_tachograph_ef(card, Some(Generation::Gen2), TACHOGRAPH_BORDER_CROSSINGS_EF) {
+        Ok(_) => {}
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    }
+    let data = read_ef_fully(card, record_ef_length(2, record_count, BORDER_CROSSING_RECORD_LENGTH)?, None)?;
+    parse_border_crossings(&data)
+}
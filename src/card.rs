@@ -0,0 +1,383 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::apdu::{transmit_read_binary_apdu, transmit_select_df_apdu, transmit_select_ef_under_df_apdu, transmit_select_mf_apdu};
+use crate::error::CardReaderError;
+use crate::nation::{nation_alpha, nation_name};
+use crate::parse::{decode_codepage_string, Reader};
+use crate::transport::CardTransport;
+
+pub const TACHOGRAPH_DF: &[u8] = b"\xFF\x54\x41\x43\x48\x4F";
+pub const TACHOGRAPH_GEN2_DF: &[u8] = b"\xFF\x53\x4D\x52\x44\x54";
+
+pub const ICC_EF: &[u8] = b"\x00\x02";
+pub const IC_EF: &[u8] = b"\x00\x05";
+
+pub const TACHOGRAPH_APPLICATION_IDENTIFICATION_EF: &[u8] = b"\x05\x01";
+pub const TACHOGRAPH_EVENTS_DATA_EF: &[u8] = b"\x05\x02";
+pub const TACHOGRAPH_FAULTS_DATA_EF: &[u8] = b"\x05\x03";
+pub const TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF: &[u8] = b"\x05\x04";
+pub const TACHOGRAPH_VEHICLES_USED_EF: &[u8] = b"\x05\x05";
+pub const TACHOGRAPH_PLACES_EF: &[u8] = b"\x05\x06";
+pub const TACHOGRAPH_CURRENT_USAGE_EF: &[u8] = b"\x05\x07";
+pub const TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF: &[u8] = b"\x05\x08";
+pub const TACHOGRAPH_CALIBRATION_EF: &[u8] = b"\x05\x0A";
+pub const TACHOGRAPH_SENSOR_INSTALLATION_DATA_EF: &[u8] = b"\x05\x0B";
+pub const TACHOGRAPH_CARD_DOWNLOAD_EF: &[u8] = b"\x05\x0E";
+pub const TACHOGRAPH_IDENTIFICATION_EF: &[u8] = b"\x05\x20";
+pub const TACHOGRAPH_DRIVING_LICENCE_INFO_EF: &[u8] = b"\x05\x21";
+pub const TACHOGRAPH_SPECIFIC_CONDITIONS_EF: &[u8] = b"\x05\x22";
+pub const TACHOGRAPH_VEHICLE_UNITS_USED_EF: &[u8] = b"\x05\x23";
+pub const TACHOGRAPH_GNSS_PLACES_EF: &[u8] = b"\x05\x24";
+pub const TACHOGRAPH_APPLICATION_IDENTIFICATION_V2_EF: &[u8] = b"\x05\x25";
+pub const TACHOGRAPH_PLACES_AUTHENTICATION_EF: &[u8] = b"\x05\x26";
+pub const TACHOGRAPH_BORDER_CROSSINGS_EF: &[u8] = b"\x05\x28";
+pub const TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF: &[u8] = b"\x05\x29";
+pub const TACHOGRAPH_CARD_CERTIFICATE_EF: &[u8] = b"\xC1\x00";
+pub const TACHOGRAPH_CA_CERTIFICATE_EF: &[u8] = b"\xC1\x08";
+pub const TACHOGRAPH_CARD_MA_CERTIFICATE_EF: &[u8] = b"\xC1\x00";
+pub const TACHOGRAPH_CARD_SIGN_CERTIFICATE_EF: &[u8] = b"\xC1\x01";
+pub const TACHOGRAPH_LINK_CERTIFICATE_EF: &[u8] = b"\xC1\x09";
+
+pub const CARD_IDENTIFICATION_LENGTH: u8 = 0x41;
+pub const DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH: u8 = 0x4E;
+
+/// Generation of the tachograph application on the card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Generation {
+    /// First generation `TACHO` application
+    Gen1,
+    /// Second generation `SMRDT` application
+    Gen2,
+}
+
+impl Generation {
+    /// Returns the application identifier of the DF holding this generation's files
+    pub fn df(&self) -> &'static [u8] {
+        match self {
+            Generation::Gen1 => TACHOGRAPH_DF,
+            Generation::Gen2 => TACHOGRAPH_GEN2_DF,
+        }
+    }
+}
+
+/// Detects the newest tachograph application generation on the card
+///
+/// The Gen2 DF is tried first, falling back to the Gen1 DF on older cards that only carry the Gen1 application.
+/// The detected DF is left selected so subsequent reads target it.
+///
+/// # Arguments
+/// - `card` - The smart card to detect the generation of
+///
+/// # Returns
+/// The detected generation
+pub fn detect_generation(card: &impl CardTransport) -> Result<Generation, CardReaderError> {
+    match transmit_select_df_apdu(card, TACHOGRAPH_GEN2_DF) {
+        Ok(_) => return Ok(Generation::Gen2),
+        Err(CardReaderError::UnexpectedStatusWord(_, _)) => {}
+        Err(e) => return Err(e),
+    }
+
+    transmit_select_df_apdu(card, TACHOGRAPH_DF)?;
+    Ok(Generation::Gen1)
+}
+
+/// Selects the master file (MF) of the card
+///
+/// Leaves the MF selected, so EFs directly under it such as `EF_ICC` and `EF_IC` can be selected next. Any
+/// previously selected application is left, and has to be selected again with [`select_tachograph_df`] before its EFs
+/// can be selected.
+///
+/// # Arguments
+/// - `card` - The smart card to select the MF on
+pub fn select_mf(card: &impl CardTransport) -> Result<(), CardReaderError> {
+    transmit_select_mf_apdu(card)?;
+    Ok(())
+}
+
+/// Selects the tachograph application of the given generation, or the newest one on the card
+///
+/// Works from any selection state and leaves the application selected.
+///
+/// # Arguments
+/// - `card` - The smart card to select the application on
+/// - `generation` - The generation of the application to select, or `None` to detect the newest one
+///
+/// # Returns
+/// The generation of the selected application
+pub fn select_tachograph_df(card: &impl CardTransport, generation: Option<Generation>) -> Result<Generation, CardReaderError> {
+    match generation {
+        Some(generation) => {
+            transmit_select_df_apdu(card, generation.df())?;
+            Ok(generation)
+        }
+        None => detect_generation(card),
+    }
+}
+
+/// Selects an EF under the tachograph application of the given generation, or the newest one on the card
+///
+/// Works from any selection state, as the application is selected first, and leaves the EF selected.
+///
+/// # Arguments
+/// - `card` - The smart card to select the EF on
+/// - `generation` - The generation of the application to select the EF under, or `None` to use the newest one
+/// - `ef` - The file identifier of the EF
+///
+/// # Returns
+/// The generation of the application the EF was selected under
+pub fn select_tachograph_ef(card: &impl CardTransport, generation: Option<Generation>, ef: &[u8]) -> Result<Generation, CardReaderError> {
+    let generation = select_tachograph_df(card, generation)?;
+    transmit_select_ef_under_df_apdu(card, ef)?;
+    Ok(generation)
+}
+
+/// The tachograph applications present on a card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// The card has the Gen1 `TACHO` application
+    pub gen1: bool,
+    /// The card has the Gen2 `SMRDT` application
+    pub gen2: bool,
+    /// The Gen2 application is version 2, i.e. it has the EFs added for smart tachographs version 2
+    pub gen2v2: bool,
+}
+
+/// Probes which tachograph applications the card has
+///
+/// Both DFs are selected in turn, and the Gen2 application is taken to be version 2 if the load/unload operations EF,
+/// which only exists in version 2, can be selected under it. Cards with the Gen2 application are left with it
+/// selected.
+///
+/// # Arguments
+/// - `card` - The smart card to probe
+///
+/// # Returns
+/// The applications present on the card
+pub fn card_capabilities(card: &impl CardTransport) -> Result<Capabilities, CardReaderError> {
+    let gen1 = is_selectable(transmit_select_df_apdu(card, TACHOGRAPH_DF))?;
+    let gen2 = is_selectable(transmit_select_df_apdu(card, TACHOGRAPH_GEN2_DF))?;
+    let gen2v2 = gen2 && is_selectable(transmit_select_ef_under_df_apdu(card, TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF))?;
+    Ok(Capabilities { gen1, gen2, gen2v2 })
+}
+
+/// Turns the result of a SELECT into whether the file exists, treating any status word as a missing file
+fn is_selectable(result: Result<Vec<u8>, CardReaderError>) -> Result<bool, CardReaderError> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(CardReaderError::UnexpectedStatusWord(_, _)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// A driver card number, i.e. the 16 character `cardNumber` of a driver card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CardNumber {
+    /// The card number as stored on the card
+    pub raw: String,
+    /// Identification of the driver (14 characters)
+    pub driver_identification: String,
+    /// Incremented each time the card is replaced
+    pub replacement_index: char,
+    /// Incremented each time the card is renewed
+    pub renewal_index: char,
+}
+
+impl CardNumber {
+    /// Parses a driver card number into its sub-fields
+    ///
+    /// # Arguments
+    /// - `raw` - The 16 character card number
+    ///
+    /// # Returns
+    /// The parsed card number
+    pub fn parse(raw: &str) -> Result<Self, CardReaderError> {
+        let chars = raw.chars().collect::<Vec<char>>();
+        if chars.len() < 16 {
+            return Err(CardReaderError::Truncated);
+        }
+
+        Ok(CardNumber {
+            raw: raw.to_string(),
+            driver_identification: chars[..14].iter().collect(),
+            replacement_index: chars[14],
+            renewal_index: chars[15],
+        })
+    }
+
+    /// Returns whether the card replaces a lost, stolen or malfunctioning card
+    pub fn is_replacement(&self) -> bool {
+        self.replacement_index != '0'
+    }
+
+    /// Returns whether the card is a renewal of an expired card
+    pub fn is_renewal(&self) -> bool {
+        self.renewal_index != '0'
+    }
+}
+
+/// Returns a stable key identifying the driver a card was issued to
+///
+/// The key is the driver identification of the card number without the replacement and renewal indices, trimmed and
+/// in upper case, so two cards with the same key belong to the same driver even if one replaces or renews the other.
+/// Member states assign the identifications independently, so combine the key with
+/// [`CardIdentification::issuing_member_state`] when deduplicating cards of several member states.
+///
+/// # Arguments
+/// - `card` - The card number
+///
+/// # Returns
+/// The 14 character driver identification
+pub fn driver_identifier(card: &CardNumber) -> String {
+    card.driver_identification.trim().to_uppercase()
+}
+
+/// A `FullCardNumber`, identifying any tachograph card by its type, issuing member state and card number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FullCardNumber {
+    /// `EquipmentType` of the card
+    pub card_type: u8,
+    /// `NationNumeric` code of the member state that issued the card
+    pub issuing_member_state: u8,
+    pub card_number: String,
+}
+
+impl FullCardNumber {
+    /// Parses a `FullCardNumber`
+    ///
+    /// # Arguments
+    /// - `data` - The full card number (18 bytes)
+    ///
+    /// # Returns
+    /// The parsed full card number
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        let card_type = reader.take_u8()?;
+        let issuing_member_state = reader.take_u8()?;
+        let card_number = reader.take(16)?;
+
+        Ok(FullCardNumber {
+            card_type,
+            issuing_member_state,
+            card_number: String::from_utf8(card_number.to_vec())?.trim_end_matches(['\0', ' ']).to_string(),
+        })
+    }
+}
+
+/// Identification of a driver card and its holder
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CardIdentification {
+    /// `NationNumeric` code of the member state that issued the card
+    pub issuing_member_state: u8,
+    pub card_number: CardNumber,
+    /// Beginning of the validity of the card
+    pub card_validity_begin: Option<DateTime<Utc>>,
+    /// End of the validity of the card
+    pub card_expiry_date: Option<DateTime<Utc>>,
+    pub first_name: String,
+    pub last_name: String,
+    /// Birth date of the holder, `None` if the card does not store one
+    pub birth_date: Option<NaiveDate>,
+    pub preferred_language: String,
+}
+
+impl CardIdentification {
+    /// Parses the card identification from the contents of the identification EF
+    ///
+    /// # Arguments
+    /// - `card_identification` - The card identification part of the EF (65 bytes)
+    /// - `card_holder_identification` - The driver card holder identification part of the EF (78 bytes)
+    /// - `generation` - The generation of the application the EF was read from, which determines the name encodings
+    ///
+    /// # Returns
+    /// The parsed card identification
+    pub fn new(card_identification: &[u8], card_holder_identification: &[u8], generation: Generation) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(card_identification);
+        let issuing_member_state = reader.take_u8()?;
+        let card_number = CardNumber::parse(&String::from_utf8(reader.take(16)?.to_vec())?)?;
+        // Skip the name of the issuing authority and the issue date
+        reader.take(36 + 4)?;
+        let card_validity_begin = reader.take_time_real()?;
+        let card_expiry_date = reader.take_time_real()?;
+
+        let mut reader = Reader::new(card_holder_identification);
+        let last_name = decode_codepage_string(reader.take(36)?, generation);
+        let first_name = decode_codepage_string(reader.take(36)?, generation);
+        // Birth date is stored as BCDString where first two bytes denote the year and the last two bytes denote the month and day respectively
+        let birth_date = reader.take_datef()?;
+        let preferred_language = String::from_utf8(reader.take(2)?.to_vec())?;
+
+        Ok(CardIdentification {
+            issuing_member_state,
+            card_number,
+            card_validity_begin,
+            card_expiry_date,
+            first_name,
+            last_name,
+            birth_date,
+            preferred_language,
+        })
+    }
+
+    /// Returns the number of whole days left until the card expires
+    ///
+    /// # Arguments
+    /// - `now` - The time to count the days from
+    ///
+    /// # Returns
+    /// The number of days, negative if the card has already expired, or `None` if the card has no expiry date
+    pub fn days_until_expiry(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.card_expiry_date.map(|card_expiry_date| (card_expiry_date - now).num_days())
+    }
+
+    /// Returns the name of the member state that issued the card
+    pub fn issuing_member_state_name(&self) -> Option<&'static str> {
+        nation_name(self.issuing_member_state)
+    }
+
+    /// Returns the distinguishing sign of the member state that issued the card
+    pub fn issuing_member_state_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.issuing_member_state)
+    }
+}
+
+impl fmt::Display for CardIdentification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<20}{}, {}", "Driver:", self.last_name, self.first_name)?;
+        writeln!(f, "{:<20}{}", "Card:", self.card_number.raw)?;
+        match (self.issuing_member_state_name(), self.issuing_member_state_alpha()) {
+            (Some(name), Some(alpha)) => writeln!(f, "{:<20}{} ({})", "Issuing state:", name, alpha)?,
+            (Some(name), None) => writeln!(f, "{:<20}{}", "Issuing state:", name)?,
+            _ => writeln!(f, "{:<20}{:#04X}", "Issuing state:", self.issuing_member_state)?,
+        }
+        if let Some(card_expiry_date) = self.card_expiry_date {
+            writeln!(f, "{:<20}{}", "Card expiry:", card_expiry_date.format("%Y-%m-%d"))?;
+        }
+        match self.birth_date {
+            Some(birth_date) => writeln!(f, "{:<20}{}", "Birth date:", birth_date)?,
+            None => writeln!(f, "{:<20}not set", "Birth date:")?,
+        }
+        write!(f, "{:<20}{}", "Preferred language:", self.preferred_language)
+    }
+}
+
+/// Reads the card identification from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the identification from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The card identification
+pub fn read_card_identification(card: &impl CardTransport, generation: Option<Generation>) -> Result<CardIdentification, CardReaderError> {
+    // Select the identification file under the tachograph application
+    let generation = select_tachograph_ef(card, generation, TACHOGRAPH_IDENTIFICATION_EF)?;
+
+    // Read the card identification and the card holder identification from the identification file
+    let card_identification = transmit_read_binary_apdu(card, 0, CARD_IDENTIFICATION_LENGTH)?;
+    let card_holder_identification = transmit_read_binary_apdu(card, CARD_IDENTIFICATION_LENGTH as u16, DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH)?;
+
+    CardIdentification::new(&card_identification, &card_holder_identification, generation)
+}
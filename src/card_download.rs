@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+
+use crate::apdu::read_ef_fully;
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_CARD_DOWNLOAD_EF};
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, take_array};
+use crate::transport::CardTransport;
+
+pub const CARD_DOWNLOAD_LENGTH: u16 = 4;
+
+/// Parses the contents of the card download EF (`LastCardDownload`)
+///
+/// # Arguments
+/// - `data` - The contents of the EF (4 bytes)
+///
+/// # Returns
+/// The time of the last download of the card, or `None` if the card has never been downloaded
+pub fn parse_last_download(data: &[u8]) -> Result<Option<DateTime<Utc>>, CardReaderError> {
+    let (last_download, _) = take_array(data)?;
+    Ok(parse_time_real(&last_download))
+}
+
+/// Reads the time of the last download of the card from the tachograph application
+///
+/// # Arguments
+/// - `card` - The smart card to read the last download time from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The time of the last download of the card, or `None` if the card has never been downloaded
+pub fn read_last_download(card: &impl CardTransport, generation: Option<Generation>) -> Result<Option<DateTime<Utc>>, CardReaderError> {
+    select_tachograph_ef(card, generation, TACHOGRAPH_CARD_DOWNLOAD_EF)?;
+    let data = read_ef_fully(card, CARD_DOWNLOAD_LENGTH, None)?;
+    parse_last_download(&data)
+}
+
+/// Computes how many whole days have passed since the last download of the card
+///
+/// # Arguments
+/// - `last_download` - The time of the last download, as returned by [`read_last_download`]
+/// - `now` - The current time
+///
+/// # Returns
+/// The number of days since the last download, or `None` if the card has never been downloaded
+pub fn days_since_last_download(last_download: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<i64> {
+    last_download.map(|last_download| (now - last_download).num_days())
+}
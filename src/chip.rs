@@ -0,0 +1,135 @@
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, transmit_select_ef_under_df_apdu};
+use crate::card::{select_mf, ICC_EF, IC_EF};
+use crate::error::CardReaderError;
+use crate::parse::{parse_bcd_number, take_array, take_n};
+use crate::transport::CardTransport;
+
+pub const ICC_LENGTH: u16 = 25;
+pub const IC_LENGTH: u16 = 8;
+
+/// Identification of the card, i.e. the contents of `EF_ICC`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IccIdentification {
+    pub clock_stop: u8,
+    /// Serial number of the card, unique for the manufacturer and equipment type
+    pub serial_number: u32,
+    pub manufacturing_month: u8,
+    pub manufacturing_year: u16,
+    pub equipment_type: u8,
+    pub manufacturer_code: u8,
+    /// Type approval number of the card
+    pub card_approval_number: String,
+    pub card_personaliser_id: u8,
+    /// Alpha-2 country code of the module embedder
+    pub embedder_country_code: String,
+    pub module_embedder: u32,
+    pub manufacturer_information: u8,
+    pub ic_identifier: u16,
+}
+
+impl IccIdentification {
+    /// Parses the card identification from the contents of `EF_ICC`
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (25 bytes)
+    ///
+    /// # Returns
+    /// The parsed card identification
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let ([clock_stop], remaining) = take_array(data)?;
+        let (serial_number, remaining) = take_array(remaining)?;
+        let ([month, year], remaining) = take_array(remaining)?;
+        let ([equipment_type, manufacturer_code], remaining) = take_array(remaining)?;
+        let (card_approval_number, remaining) = take_n(8, remaining)?;
+        let ([card_personaliser_id], remaining) = take_array(remaining)?;
+        let (embedder_country_code, remaining) = take_n(2, remaining)?;
+        let (module_embedder, remaining) = take_n(2, remaining)?;
+        let ([manufacturer_information], remaining) = take_array(remaining)?;
+        let (ic_identifier, _) = take_array(remaining)?;
+
+        Ok(IccIdentification {
+            clock_stop,
+            serial_number: u32::from_be_bytes(serial_number),
+            manufacturing_month: parse_bcd_number(&[month])? as u8,
+            manufacturing_year: 2000 + parse_bcd_number(&[year])? as u16,
+            equipment_type,
+            manufacturer_code,
+            card_approval_number: String::from_utf8(card_approval_number.to_vec())?.trim_end_matches(['\0', ' ']).to_string(),
+            card_personaliser_id,
+            embedder_country_code: String::from_utf8(embedder_country_code.to_vec())?,
+            module_embedder: parse_bcd_number(module_embedder)?,
+            manufacturer_information,
+            ic_identifier: u16::from_be_bytes(ic_identifier),
+        })
+    }
+}
+
+/// Identification of the integrated circuit of the card, i.e. the contents of `EF_IC`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IcIdentification {
+    pub ic_serial_number: u32,
+    pub ic_manufacturing_references: [u8; 4],
+}
+
+impl IcIdentification {
+    /// Parses the chip identification from the contents of `EF_IC`
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (8 bytes)
+    ///
+    /// # Returns
+    /// The parsed chip identification
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let (ic_serial_number, remaining) = take_array(data)?;
+        let (ic_manufacturing_references, _) = take_array(remaining)?;
+
+        Ok(IcIdentification {
+            ic_serial_number: u32::from_be_bytes(ic_serial_number),
+            ic_manufacturing_references,
+        })
+    }
+}
+
+/// Selects an EF directly under the MF and reads it
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `ef` - The file identifier of the EF
+/// - `length` - The length of the EF
+fn read_mf_ef(card: &impl CardTransport, ef: &[u8], length: u16) -> Result<Vec<u8>, CardReaderError> {
+    select_mf(card)?;
+    transmit_select_ef_under_df_apdu(card, ef)?;
+    read_ef_fully(card, length, None)
+}
+
+/// Reads the card identification from `EF_ICC`
+///
+/// The MF is selected first, so any previously selected application is left. Readers of the tachograph EFs select
+/// their application themselves, so they can be called right after this without selecting it again.
+///
+/// # Arguments
+/// - `card` - The smart card to read the card identification from
+///
+/// # Returns
+/// The card identification
+pub fn read_icc(card: &impl CardTransport) -> Result<IccIdentification, CardReaderError> {
+    let data = read_mf_ef(card, ICC_EF, ICC_LENGTH)?;
+    IccIdentification::new(&data)
+}
+
+/// Reads the chip identification from `EF_IC`
+///
+/// The MF is selected first, so any previously selected application is left. Readers of the tachograph EFs select
+/// their application themselves, so they can be called right after this without selecting it again.
+///
+/// # Arguments
+/// - `card` - The smart card to read the chip identification from
+///
+/// # Returns
+/// The chip identification
+pub fn read_ic(card: &impl CardTransport) -> Result<IcIdentification, CardReaderError> {
+    let data = read_mf_ef(card, IC_EF, IC_LENGTH)?;
+    IcIdentification::new(&data)
+}
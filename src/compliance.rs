@@ -0,0 +1,63 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use crate::card_download::days_since_last_download;
+use crate::driver_card::DriverCard;
+use crate::events::EventType;
+
+/// Thresholds used when computing [`ComplianceFlags`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplianceConfig {
+    /// How many days before its expiry the card is considered to be expiring soon
+    pub expiry_warning_days: i64,
+    /// Maximum number of days between downloads of the card, 28 days in the EU
+    pub download_interval_days: i64,
+    /// How many days back events count as recent
+    pub recent_event_days: i64,
+}
+
+impl Default for ComplianceConfig {
+    fn default() -> Self {
+        ComplianceConfig {
+            expiry_warning_days: 30,
+            download_interval_days: 28,
+            recent_event_days: 28,
+        }
+    }
+}
+
+/// Yes/no answers to common compliance questions about a card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ComplianceFlags {
+    /// The card expires within the warning period, or has already expired
+    pub card_expiring_soon: bool,
+    /// The card has not been downloaded within the download interval, or has never been downloaded
+    pub download_overdue: bool,
+    /// A power supply interruption was recorded within the recent event period
+    pub recent_power_interruptions: bool,
+    /// A time overlap event is stored on the card
+    pub time_conflict_events_present: bool,
+    /// The card replaces a lost, stolen or damaged card
+    pub card_is_replacement: bool,
+}
+
+/// Computes the compliance flags of a card
+///
+/// # Arguments
+/// - `card` - The data read from the card
+/// - `config` - The thresholds to apply
+/// - `now` - The current time
+///
+/// # Returns
+/// The compliance flags
+pub fn compliance_flags(card: &DriverCard, config: &ComplianceConfig, now: DateTime<Utc>) -> ComplianceFlags {
+    let recent = now - TimeDelta::days(config.recent_event_days);
+
+    ComplianceFlags {
+        card_expiring_soon: card.identification.days_until_expiry(now).is_some_and(|days| days <= config.expiry_warning_days),
+        download_overdue: days_since_last_download(card.last_download, now).is_none_or(|days| days > config.download_interval_days),
+        recent_power_interruptions: card.events.iter().any(|event| event.kind() == EventType::PowerSupplyInterruption && event.end >= recent),
+        time_conflict_events_present: card.events.iter().any(|event| event.kind() == EventType::TimeOverlap),
+        card_is_replacement: card.identification.card_number.is_replacement(),
+    }
+}
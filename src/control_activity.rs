@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::card::{select_tachograph_ef, Generation, FullCardNumber, TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF};
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, parse_vehicle_registration, take_array, take_n};
+use crate::transport::CardTransport;
+
+pub const CONTROL_ACTIVITY_LENGTH: u16 = 46;
+
+/// The kinds of control carried out, decoded from the `ControlType` bitfield
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ControlType {
+    pub card_downloading: bool,
+    pub vu_downloading: bool,
+    pub printing: bool,
+    pub display: bool,
+    /// Roadside calibration checking, only recorded by Gen2 equipment
+    pub calibration_checking: bool,
+}
+
+impl From<u8> for ControlType {
+    fn from(bits: u8) -> Self {
+        ControlType {
+            card_downloading: bits & 0x80 != 0,
+            vu_downloading: bits & 0x40 != 0,
+            printing: bits & 0x20 != 0,
+            display: bits & 0x10 != 0,
+            calibration_checking: bits & 0x08 != 0,
+        }
+    }
+}
+
+/// The last control the card holder was subject to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControlActivity {
+    pub control_type: ControlType,
+    pub control_time: DateTime<Utc>,
+    /// Card of the control officer who carried out the control
+    pub control_card_number: FullCardNumber,
+    pub control_vehicle_registration_nation: u8,
+    pub control_vehicle_registration: String,
+    /// Begin of the period downloaded during the control
+    pub download_period_begin: Option<DateTime<Utc>>,
+    /// End of the period downloaded during the control
+    pub download_period_end: Option<DateTime<Utc>>,
+}
+
+impl ControlActivity {
+    /// Parses the `CardControlActivityDataRecord` from the contents of the control activity EF
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (46 bytes)
+    ///
+    /// # Returns
+    /// The parsed control activity, or `None` if no control has taken place
+    pub fn new(data: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (control_type, remaining) = take_n(1, data)?;
+        let (control_time, remaining) = take_array(remaining)?;
+        let (control_card_number, remaining) = take_n(18, remaining)?;
+        let (control_vehicle_registration, remaining) = take_n(15, remaining)?;
+        let (download_period_begin, remaining) = take_array(remaining)?;
+        let (download_period_end, _) = take_array(remaining)?;
+
+        let Some(control_time) = parse_time_real(&control_time) else {
+            return Ok(None);
+        };
+
+        let (control_vehicle_registration_nation, control_vehicle_registration) = parse_vehicle_registration(control_vehicle_registration)?;
+
+        Ok(Some(ControlActivity {
+            control_type: ControlType::from(control_type[0]),
+            control_time,
+            control_card_number: FullCardNumber::new(control_card_number)?,
+            control_vehicle_registration_nation,
+            control_vehicle_registration,
+            download_period_begin: parse_time_real(&download_period_begin),
+            download_period_end: parse_time_real(&download_period_end),
+        }))
+    }
+}
+
+/// Reads the last control activity from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the control activity from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The last control activity, or `None` if no control has taken place
+pub fn read_control_activity(card: &impl CardTransport, generation: Option<Generation>) -> Result<Option<ControlActivity>, CardReaderError> {
+    select_tachograph_ef(card, generation, TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF)?;
+    let data = read_ef_fully(card, CONTROL_ACTIVITY_LENGTH, None)?;
+    ControlActivity::new(&data)
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_CURRENT_USAGE_EF};
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, parse_vehicle_registration, take_array};
+use crate::transport::CardTransport;
+
+pub const CURRENT_USAGE_LENGTH: u16 = 19;
+
+/// The current or last session of the card in a vehicle unit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurrentUsage {
+    /// Insertion time of the card, `None` if the card is not inserted in a VU
+    pub session_open_time: Option<DateTime<Utc>>,
+    pub vehicle_registration_nation: u8,
+    pub vehicle_registration: String,
+}
+
+impl CurrentUsage {
+    /// Parses the current usage from the contents of the current usage EF
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (19 bytes)
+    ///
+    /// # Returns
+    /// The parsed current usage
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let (session_open_time, remaining) = take_array(data)?;
+        let (vehicle_registration_nation, vehicle_registration) = parse_vehicle_registration(remaining)?;
+
+        Ok(CurrentUsage {
+            session_open_time: parse_time_real(&session_open_time),
+            vehicle_registration_nation,
+            vehicle_registration,
+        })
+    }
+}
+
+/// Reads the current usage from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the current usage from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The current usage
+pub fn read_current_usage(card: &impl CardTransport, generation: Option<Generation>) -> Result<CurrentUsage, CardReaderError> {
+    select_tachograph_ef(card, generation, TACHOGRAPH_CURRENT_USAGE_EF)?;
+    let data = read_ef_fully(card, CURRENT_USAGE_LENGTH, None)?;
+    CurrentUsage::new(&data)
+}
@@ -0,0 +1,489 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::activity::{parse_driver_activity, ActivityDay};
+use crate::application::{read_application_identification, record_ef_length, AppId, APPLICATION_IDENTIFICATION_LENGTH, GEN2_APPLICATION_IDENTIFICATION_LENGTH};
+use crate::apdu::{
+    read_ef_fully, select_ef_length, transmit_compute_digital_signature_apdu, transmit_compute_digital_signature_gen2_apdu, transmit_perform_hash_of_file_apdu,
+    transmit_select_ef_under_df_apdu, transmit_select_mf_apdu, COMPUTE_DIGITAL_SIGNATURE_COMMAND, COMPUTE_DIGITAL_SIGNATURE_GEN2_COMMAND, MAX_EF_LENGTH, PERFORM_HASH_OF_FILE_COMMAND, SELECT_MF_COMMAND, STATUS_WORD_FILE_NOT_FOUND, STATUS_WORD_INSTRUCTION_NOT_SUPPORTED,
+    STATUS_WORD_REFERENCED_DATA_NOT_FOUND, STATUS_WORD_SUCCESS, STATUS_WORD_WRONG_OFFSET, STATUS_WORD_WRONG_PARAMETERS,
+};
+use crate::card::{
+    detect_generation, CARD_IDENTIFICATION_LENGTH, DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH, ICC_EF, IC_EF, TACHOGRAPH_APPLICATION_IDENTIFICATION_EF,
+    TACHOGRAPH_APPLICATION_IDENTIFICATION_V2_EF, TACHOGRAPH_BORDER_CROSSINGS_EF, TACHOGRAPH_CARD_CERTIFICATE_EF, TACHOGRAPH_CARD_DOWNLOAD_EF,
+    TACHOGRAPH_CARD_SIGN_CERTIFICATE_EF, TACHOGRAPH_CA_CERTIFICATE_EF, TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF, TACHOGRAPH_CURRENT_USAGE_EF, TACHOGRAPH_DF,
+    TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF, TACHOGRAPH_DRIVING_LICENCE_INFO_EF, TACHOGRAPH_EVENTS_DATA_EF, TACHOGRAPH_FAULTS_DATA_EF, TACHOGRAPH_GEN2_DF,
+    TACHOGRAPH_GNSS_PLACES_EF, TACHOGRAPH_IDENTIFICATION_EF, TACHOGRAPH_LINK_CERTIFICATE_EF, TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF,
+    TACHOGRAPH_PLACES_AUTHENTICATION_EF, TACHOGRAPH_PLACES_EF, TACHOGRAPH_SPECIFIC_CONDITIONS_EF, TACHOGRAPH_VEHICLES_USED_EF, TACHOGRAPH_VEHICLE_UNITS_USED_EF,
+    Generation,
+};
+use crate::border_crossings::{BORDER_CROSSING_RECORD_LENGTH, NO_OF_BORDER_CROSSING_RECORDS};
+use crate::card_download::CARD_DOWNLOAD_LENGTH;
+use crate::chip::{ICC_LENGTH, IC_LENGTH};
+use crate::control_activity::CONTROL_ACTIVITY_LENGTH;
+use crate::current_usage::CURRENT_USAGE_LENGTH;
+use crate::driver_card::{read_full_card, DriverCard};
+use crate::driving_licence::DRIVING_LICENCE_INFO_LENGTH;
+use crate::error::CardReaderError;
+use crate::events::{event_type_count, EVENT_RECORD_LENGTH};
+use crate::faults::{FAULT_RECORD_LENGTH, FAULT_TYPE_COUNT};
+use crate::gnss::GNSS_ACCUMULATED_DRIVING_RECORD_LENGTH;
+use crate::load_unload::{LOAD_UNLOAD_RECORD_LENGTH, NO_OF_LOAD_UNLOAD_RECORDS};
+use crate::parse::take_array;
+use crate::places::{GEN2_PLACE_RECORD_LENGTH, PLACE_AUTH_STATUS_RECORD_LENGTH, PLACE_RECORD_LENGTH};
+use crate::specific_conditions::SPECIFIC_CONDITION_RECORD_LENGTH;
+use crate::transport::CardTransport;
+use crate::vehicle_units::VEHICLE_UNIT_RECORD_LENGTH;
+use crate::vehicles::{GEN2_VEHICLE_RECORD_LENGTH, VEHICLE_RECORD_LENGTH};
+
+/// Appendix byte of a block holding the contents of an EF
+pub const DDD_APPENDIX_DATA: u8 = 0x00;
+/// Appendix byte of a block holding the signature of the preceding EF
+pub const DDD_APPENDIX_SIGNATURE: u8 = 0x01;
+/// Appendix byte of a block holding the contents of an EF under the Gen2 application
+pub const DDD_APPENDIX_GEN2_DATA: u8 = 0x02;
+/// Appendix byte of a block holding the signature of the preceding EF under the Gen2 application
+pub const DDD_APPENDIX_GEN2_SIGNATURE: u8 = 0x03;
+
+/// Length of a Gen1 RSA certificate
+pub const CERTIFICATE_LENGTH: u16 = 194;
+/// Length of the Gen1 specific conditions EF, which has no newest record pointer
+pub const SPECIFIC_CONDITIONS_LENGTH: u16 = 280;
+
+/// An EF included in a card download
+struct DownloadableEf {
+    id: &'static [u8],
+    /// Number of bytes to read, or `None` to take the size of the EF from its FCP
+    length: Option<u16>,
+    /// Whether the card signs the EF as part of the download
+    signed: bool,
+    /// Whether the EF is left out of the download if the card doesn't have it, for EFs added in later versions
+    optional: bool,
+}
+
+impl DownloadableEf {
+    /// An EF signed by the card
+    fn signed(id: &'static [u8], length: u16) -> Self {
+        DownloadableEf { id, length: Some(length), signed: true, optional: false }
+    }
+
+    /// An EF the card doesn't sign, i.e. a certificate or an EF of the MF
+    fn unsigned(id: &'static [u8], length: Option<u16>) -> Self {
+        DownloadableEf { id, length, signed: false, optional: false }
+    }
+
+    /// Marks the EF as missing from older cards
+    fn optional(self) -> Self {
+        DownloadableEf { optional: true, ..self }
+    }
+}
+
+/// Returns a record count of a Gen2 application identification, which Gen1 application identifications don't have
+fn gen2_record_count(record_count: Option<u16>) -> Result<usize, CardReaderError> {
+    record_count.map(usize::from).ok_or(CardReaderError::Truncated)
+}
+
+/// Returns the EFs of a tachograph application in the order they appear in a download
+///
+/// The lengths of the record EFs are computed from the record counts of the application identification.
+///
+/// # Arguments
+/// - `app_id` - The application identification of the application
+///
+/// # Returns
+/// The EFs to download
+fn downloadable_efs(app_id: &AppId) -> Result<Vec<DownloadableEf>, CardReaderError> {
+    let identification_length = CARD_IDENTIFICATION_LENGTH as u16 + DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH as u16;
+    let events_length = record_ef_length(0, event_type_count(app_id.generation) * app_id.events_per_type as usize, EVENT_RECORD_LENGTH)?;
+    let faults_length = record_ef_length(0, FAULT_TYPE_COUNT * app_id.faults_per_type as usize, FAULT_RECORD_LENGTH)?;
+    let activity_length = record_ef_length(4, app_id.activity_structure_length as usize, 1)?;
+
+    let efs = match app_id.generation {
+        Generation::Gen1 => vec![
+            DownloadableEf::signed(TACHOGRAPH_APPLICATION_IDENTIFICATION_EF, APPLICATION_IDENTIFICATION_LENGTH as u16),
+            DownloadableEf::unsigned(TACHOGRAPH_CARD_CERTIFICATE_EF, Some(CERTIFICATE_LENGTH)),
+            DownloadableEf::unsigned(TACHOGRAPH_CA_CERTIFICATE_EF, Some(CERTIFICATE_LENGTH)),
+            DownloadableEf::signed(TACHOGRAPH_IDENTIFICATION_EF, identification_length),
+            DownloadableEf::signed(TACHOGRAPH_CARD_DOWNLOAD_EF, CARD_DOWNLOAD_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_DRIVING_LICENCE_INFO_EF, DRIVING_LICENCE_INFO_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_EVENTS_DATA_EF, events_length),
+            DownloadableEf::signed(TACHOGRAPH_FAULTS_DATA_EF, faults_length),
+            DownloadableEf::signed(TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF, activity_length),
+            DownloadableEf::signed(TACHOGRAPH_VEHICLES_USED_EF, record_ef_length(2, app_id.vehicle_records as usize, VEHICLE_RECORD_LENGTH)?),
+            DownloadableEf::signed(TACHOGRAPH_PLACES_EF, record_ef_length(1, app_id.place_records as usize, PLACE_RECORD_LENGTH)?),
+            DownloadableEf::signed(TACHOGRAPH_CURRENT_USAGE_EF, CURRENT_USAGE_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF, CONTROL_ACTIVITY_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_SPECIFIC_CONDITIONS_EF, SPECIFIC_CONDITIONS_LENGTH).optional(),
+        ],
+        Generation::Gen2 => vec![
+            DownloadableEf::signed(TACHOGRAPH_APPLICATION_IDENTIFICATION_EF, GEN2_APPLICATION_IDENTIFICATION_LENGTH as u16),
+            // ECC certificates vary in length with the curve of their key
+            DownloadableEf::unsigned(TACHOGRAPH_CARD_SIGN_CERTIFICATE_EF, None),
+            DownloadableEf::unsigned(TACHOGRAPH_CA_CERTIFICATE_EF, None),
+            DownloadableEf::unsigned(TACHOGRAPH_LINK_CERTIFICATE_EF, None).optional(),
+            DownloadableEf::signed(TACHOGRAPH_IDENTIFICATION_EF, identification_length),
+            DownloadableEf::signed(TACHOGRAPH_CARD_DOWNLOAD_EF, CARD_DOWNLOAD_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_DRIVING_LICENCE_INFO_EF, DRIVING_LICENCE_INFO_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_EVENTS_DATA_EF, events_length),
+            DownloadableEf::signed(TACHOGRAPH_FAULTS_DATA_EF, faults_length),
+            DownloadableEf::signed(TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF, activity_length),
+            DownloadableEf::signed(TACHOGRAPH_VEHICLES_USED_EF, record_ef_length(2, app_id.vehicle_records as usize, GEN2_VEHICLE_RECORD_LENGTH)?),
+            DownloadableEf::signed(TACHOGRAPH_PLACES_EF, record_ef_length(2, app_id.place_records as usize, GEN2_PLACE_RECORD_LENGTH)?),
+            DownloadableEf::signed(TACHOGRAPH_CURRENT_USAGE_EF, CURRENT_USAGE_LENGTH),
+            DownloadableEf::signed(TACHOGRAPH_CONTROL_ACTIVITY_DATA_EF, CONTROL_ACTIVITY_LENGTH),
+            DownloadableEf::signed(
+                TACHOGRAPH_SPECIFIC_CONDITIONS_EF,
+                record_ef_length(2, gen2_record_count(app_id.specific_condition_records)?, SPECIFIC_CONDITION_RECORD_LENGTH)?,
+            ),
+            DownloadableEf::signed(
+                TACHOGRAPH_VEHICLE_UNITS_USED_EF,
+                record_ef_length(2, gen2_record_count(app_id.vehicle_unit_records)?, VEHICLE_UNIT_RECORD_LENGTH)?,
+            ),
+            DownloadableEf::signed(
+                TACHOGRAPH_GNSS_PLACES_EF,
+                record_ef_length(2, gen2_record_count(app_id.gnss_ad_records)?, GNSS_ACCUMULATED_DRIVING_RECORD_LENGTH)?,
+            ),
+            // Gen2v2 EFs, whose record counts are in the second application identification this crate doesn't parse
+            DownloadableEf { id: TACHOGRAPH_APPLICATION_IDENTIFICATION_V2_EF, length: None, signed: true, optional: true },
+            DownloadableEf::signed(TACHOGRAPH_PLACES_AUTHENTICATION_EF, record_ef_length(2, app_id.place_records as usize, PLACE_AUTH_STATUS_RECORD_LENGTH)?)
+                .optional(),
+            DownloadableEf::signed(TACHOGRAPH_BORDER_CROSSINGS_EF, record_ef_length(2, NO_OF_BORDER_CROSSING_RECORDS, BORDER_CROSSING_RECORD_LENGTH)?).optional(),
+            DownloadableEf::signed(TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF, record_ef_length(2, NO_OF_LOAD_UNLOAD_RECORDS, LOAD_UNLOAD_RECORD_LENGTH)?).optional(),
+        ],
+    };
+    Ok(efs)
+}
+
+/// Writes a single block of a download, i.e. the EF id, the appendix byte, the 2-byte length and the data
+///
+/// # Arguments
+/// - `writer` - The writer to write the block to
+/// - `ef` - The file identifier of the EF
+/// - `appendix` - The appendix byte denoting whether the block holds data or a signature
+/// - `data` - The contents of the block
+fn write_block(writer: &mut impl Write, ef: &[u8], appendix: u8, data: &[u8]) -> Result<(), CardReaderError> {
+    writer.write_all(ef)?;
+    writer.write_all(&[appendix])?;
+    writer.write_all(&(data.len() as u16).to_be_bytes())?;
+    writer.write_all(data)?;
+    Ok(())
+}
+
+/// Selects and reads an EF, writing its contents and, if requested, the signature computed by the card
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `writer` - The writer to write the blocks to
+/// - `ef` - The EF to download
+/// - `generation` - The generation of the application the EF is under, which determines the appendix of its blocks
+fn download_ef(card: &impl CardTransport, writer: &mut impl Write, ef: &DownloadableEf, generation: Generation) -> Result<(), CardReaderError> {
+    let id = [ef.id[0], ef.id[1]];
+    let selected = match ef.length {
+        Some(length) => transmit_select_ef_under_df_apdu(card, ef.id).map(|_| length),
+        None => select_ef_length(card, id).map(|length| length.unwrap_or(MAX_EF_LENGTH)),
+    };
+    let length = match selected {
+        Ok(length) => length,
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if ef.optional && (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if ef.signed {
+        transmit_perform_hash_of_file_apdu(card)?;
+    }
+
+    let (data_appendix, signature_appendix) = match generation {
+        Generation::Gen1 => (DDD_APPENDIX_DATA, DDD_APPENDIX_SIGNATURE),
+        Generation::Gen2 => (DDD_APPENDIX_GEN2_DATA, DDD_APPENDIX_GEN2_SIGNATURE),
+    };
+    let data = read_ef_fully(card, length, None)?;
+    write_block(writer, ef.id, data_appendix, &data)?;
+
+    if ef.signed {
+        let signature = match generation {
+            Generation::Gen1 => transmit_compute_digital_signature_apdu(card)?,
+            Generation::Gen2 => transmit_compute_digital_signature_gen2_apdu(card)?,
+        };
+        write_block(writer, ef.id, signature_appendix, &signature)?;
+    }
+    Ok(())
+}
+
+/// Downloads the card in the regulatory card download file structure
+///
+/// The download consists of `EF_ICC` and `EF_IC` from the MF followed by the EFs of the Gen1 tachograph application,
+/// which is present on both Gen1 and Gen2 cards, and on Gen2 cards by the EFs of the Gen2 application in blocks with
+/// the Gen2 appendices. Each EF under an application is followed by the signature the card computes over it. The
+/// lengths of the record EFs come from the application identification of each application.
+///
+/// The Gen2v2 GNSS place authentication and load type entry EFs are not included.
+///
+/// # Arguments
+/// - `card` - The smart card to download
+/// - `writer` - The writer to write the download to
+pub fn write_ddd(card: &impl CardTransport, writer: &mut impl Write) -> Result<(), CardReaderError> {
+    let newest_generation = detect_generation(card)?;
+
+    transmit_select_mf_apdu(card)?;
+    for (id, length) in [(ICC_EF, ICC_LENGTH), (IC_EF, IC_LENGTH)] {
+        let ef = DownloadableEf::unsigned(id, Some(length));
+        download_ef(card, writer, &ef, Generation::Gen1)?;
+    }
+
+    let generations: &[Generation] = match newest_generation {
+        Generation::Gen1 => &[Generation::Gen1],
+        Generation::Gen2 => &[Generation::Gen1, Generation::Gen2],
+    };
+    for &generation in generations {
+        // Reading the application identification leaves the DF of the application selected
+        let app_id = read_application_identification(card, Some(generation))?;
+        for ef in downloadable_efs(&app_id)? {
+            download_ef(card, writer, &ef, generation)?;
+        }
+    }
+    Ok(())
+}
+
+/// Downloads the card into a `.ddd` file
+///
+/// # Arguments
+/// - `card` - The smart card to download
+/// - `path` - The path of the file to write
+pub fn download_ddd(card: &impl CardTransport, path: &Path) -> Result<(), CardReaderError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_ddd(card, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// A single block of a download
+struct Block<'a> {
+    /// File identifier of the EF the block belongs to
+    id: [u8; 2],
+    /// Whether the block holds data or a signature, and of which generation
+    appendix: u8,
+    data: &'a [u8],
+}
+
+/// Splits a download into its blocks
+///
+/// # Arguments
+/// - `bytes` - The contents of the download
+///
+/// # Returns
+/// The blocks in the order they appear, or `Truncated` if the last block is incomplete
+fn split_blocks(bytes: &[u8]) -> Result<Vec<Block<'_>>, CardReaderError> {
+    let mut blocks = Vec::new();
+    let mut remaining = bytes;
+    while !remaining.is_empty() {
+        let (id, rest) = take_array::<2>(remaining)?;
+        let ([appendix], rest) = take_array(rest)?;
+        let (length, rest) = take_array(rest)?;
+        let length = u16::from_be_bytes(length) as usize;
+        if rest.len() < length {
+            return Err(CardReaderError::Truncated);
+        }
+        let (data, rest) = rest.split_at(length);
+        blocks.push(Block { id, appendix, data });
+        remaining = rest;
+    }
+    Ok(blocks)
+}
+
+/// A card transport answering APDUs from the blocks of a saved `.ddd` file
+///
+/// SELECT, READ BINARY, PERFORM HASH OF FILE and COMPUTE DIGITAL SIGNATURE are emulated from the stored blocks, so
+/// the same readers and parsers work for live cards and archived downloads. The MF EFs and the Gen1 application EFs
+/// share the Gen1 blocks; the Gen2 application can only be selected if the file holds Gen2 blocks.
+pub struct FileCard {
+    data: HashMap<(Generation, [u8; 2]), Vec<u8>>,
+    signatures: HashMap<(Generation, [u8; 2]), Vec<u8>>,
+    selected_generation: Cell<Generation>,
+    selected_ef: Cell<Option<[u8; 2]>>,
+}
+
+impl FileCard {
+    /// Splits the contents of a `.ddd` file into its blocks
+    ///
+    /// # Arguments
+    /// - `bytes` - The contents of the file
+    ///
+    /// # Returns
+    /// The card emulated from the file, or `Truncated` if the last block is incomplete
+    pub fn new(bytes: &[u8]) -> Result<Self, CardReaderError> {
+        let mut data = HashMap::new();
+        let mut signatures = HashMap::new();
+
+        for block in split_blocks(bytes)? {
+            match block.appendix {
+                DDD_APPENDIX_DATA => data.insert((Generation::Gen1, block.id), block.data.to_vec()),
+                DDD_APPENDIX_SIGNATURE => signatures.insert((Generation::Gen1, block.id), block.data.to_vec()),
+                DDD_APPENDIX_GEN2_DATA => data.insert((Generation::Gen2, block.id), block.data.to_vec()),
+                DDD_APPENDIX_GEN2_SIGNATURE => signatures.insert((Generation::Gen2, block.id), block.data.to_vec()),
+                _ => None,
+            };
+        }
+
+        Ok(FileCard {
+            data,
+            signatures,
+            selected_generation: Cell::new(Generation::Gen1),
+            selected_ef: Cell::new(None),
+        })
+    }
+
+    /// Reads a `.ddd` file
+    ///
+    /// # Arguments
+    /// - `path` - The path of the file
+    ///
+    /// # Returns
+    /// The card emulated from the file
+    pub fn open(path: &Path) -> Result<Self, CardReaderError> {
+        Self::new(&fs::read(path)?)
+    }
+
+    /// Returns the contents of the selected EF
+    fn selected(&self) -> Option<&Vec<u8>> {
+        self.data.get(&(self.selected_generation.get(), self.selected_ef.get()?))
+    }
+
+    /// Answers a READ BINARY of the selected EF
+    fn read_binary(&self, offset: usize, length: usize) -> Result<Vec<u8>, (u8, u8)> {
+        match self.selected() {
+            Some(data) if offset <= data.len() => Ok(data[offset..data.len().min(offset + length)].to_vec()),
+            Some(_) => Err(STATUS_WORD_WRONG_OFFSET),
+            None => Err(STATUS_WORD_FILE_NOT_FOUND),
+        }
+    }
+
+    /// Answers a SELECT of a DF by its application identifier
+    fn select_df(&self, aid: &[u8]) -> Result<Vec<u8>, (u8, u8)> {
+        let has_gen2 = self.data.keys().any(|(generation, _)| *generation == Generation::Gen2);
+        let generation = match aid {
+            _ if aid == TACHOGRAPH_DF => Generation::Gen1,
+            _ if aid == TACHOGRAPH_GEN2_DF && has_gen2 => Generation::Gen2,
+            _ => return Err(STATUS_WORD_FILE_NOT_FOUND),
+        };
+        self.selected_generation.set(generation);
+        self.selected_ef.set(None);
+        Ok(Vec::new())
+    }
+
+    /// Answers a SELECT of an EF under the selected DF, returning an FCP with its size if requested
+    fn select_ef(&self, ef: [u8; 2], with_fcp: bool) -> Result<Vec<u8>, (u8, u8)> {
+        let data = self.data.get(&(self.selected_generation.get(), ef)).ok_or(STATUS_WORD_FILE_NOT_FOUND)?;
+        self.selected_ef.set(Some(ef));
+        if !with_fcp {
+            return Ok(Vec::new());
+        }
+        let [length_hi, length_lo] = (data.len() as u16).to_be_bytes();
+        Ok(vec![0x62, 0x04, 0x80, 0x02, length_hi, length_lo])
+    }
+
+    /// Answers a command APDU, returning the response data or the status word of an error
+    fn answer(&self, apdu: &[u8]) -> Result<Vec<u8>, (u8, u8)> {
+        match apdu {
+            _ if apdu == SELECT_MF_COMMAND => {
+                self.selected_generation.set(Generation::Gen1);
+                self.selected_ef.set(None);
+                Ok(Vec::new())
+            }
+            [0x00, 0xA4, 0x04, 0x0C, 0x06, aid @ ..] => self.select_df(aid),
+            [0x00, 0xA4, 0x02, 0x0C, 0x02, id_hi, id_lo] => self.select_ef([*id_hi, *id_lo], false),
+            [0x00, 0xA4, 0x02, 0x04, 0x02, id_hi, id_lo, 0x00] => self.select_ef([*id_hi, *id_lo], true),
+            [0x00, 0xA4, ..] => Err(STATUS_WORD_WRONG_PARAMETERS),
+            [0x00, 0xB0, p1, p2, le] => {
+                let length = if *le == 0 { 256 } else { *le as usize };
+                self.read_binary(u16::from_be_bytes([*p1, *p2]) as usize, length)
+            }
+            [0x00, 0xB0, p1, p2, 0x00, le_hi, le_lo] => {
+                let length = match u16::from_be_bytes([*le_hi, *le_lo]) {
+                    0 => 65536,
+                    le => le as usize,
+                };
+                self.read_binary(u16::from_be_bytes([*p1, *p2]) as usize, length)
+            }
+            _ if apdu == PERFORM_HASH_OF_FILE_COMMAND => self.selected().map(|_| Vec::new()).ok_or(STATUS_WORD_FILE_NOT_FOUND),
+            _ if apdu == COMPUTE_DIGITAL_SIGNATURE_COMMAND || apdu == COMPUTE_DIGITAL_SIGNATURE_GEN2_COMMAND => self
+                .selected_ef
+                .get()
+                .and_then(|ef| self.signatures.get(&(self.selected_generation.get(), ef)))
+                .cloned()
+                .ok_or(STATUS_WORD_REFERENCED_DATA_NOT_FOUND),
+            _ => Err(STATUS_WORD_INSTRUCTION_NOT_SUPPORTED),
+        }
+    }
+}
+
+impl CardTransport for FileCard {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        match self.answer(apdu) {
+            Ok(mut response) => {
+                response.extend_from_slice(&[STATUS_WORD_SUCCESS.0, STATUS_WORD_SUCCESS.1]);
+                Ok(response)
+            }
+            Err((sw1, sw2)) => Ok(vec![sw1, sw2]),
+        }
+    }
+}
+
+/// Parses the contents of a saved `.ddd` file with the same parsers used for live cards
+///
+/// # Arguments
+/// - `bytes` - The contents of the file
+///
+/// # Returns
+/// The data of the driver card the file was downloaded from
+pub fn parse_ddd(bytes: &[u8]) -> Result<DriverCard, CardReaderError> {
+    read_full_card(&FileCard::new(bytes)?, None)
+}
+
+/// The driver activity of a download with the signature the card computed over it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignedDriverActivity {
+    /// Generation of the application the activity was downloaded from
+    pub generation: Generation,
+    /// The activity days in chronological order
+    pub days: Vec<ActivityDay>,
+    /// The signature block following the activity block, `None` if the download has none
+    pub signature: Option<Vec<u8>>,
+}
+
+/// Parses the driver activity out of a download, e.g. a saved `.ddd` file or the blocks of a single EF
+///
+/// The activity block of the Gen2 application (appendix `02`) is preferred over the Gen1 one (appendix `00`) when the
+/// download has both. Both generations store the same `CardActivityDailyRecord` layout, only the size of the cyclic
+/// buffer differs, so the blocks are parsed with [`parse_driver_activity`] and the signature block of the same
+/// generation is returned with the days.
+///
+/// # Arguments
+/// - `bytes` - The blocks of the download
+///
+/// # Returns
+/// The driver activity, `None` if the download has no driver activity block
+pub fn parse_driver_activity_blocks(bytes: &[u8]) -> Result<Option<SignedDriverActivity>, CardReaderError> {
+    let blocks = split_blocks(bytes)?;
+    let find_block = |appendix: u8| {
+        blocks
+            .iter()
+            .find(|block| block.id == TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF && block.appendix == appendix)
+            .map(|block| block.data)
+    };
+
+    let (generation, data, signature) = match (find_block(DDD_APPENDIX_GEN2_DATA), find_block(DDD_APPENDIX_DATA)) {
+        (Some(data), _) => (Generation::Gen2, data, find_block(DDD_APPENDIX_GEN2_SIGNATURE)),
+        (None, Some(data)) => (Generation::Gen1, data, find_block(DDD_APPENDIX_SIGNATURE)),
+        (None, None) => return Ok(None),
+    };
+
+    Ok(Some(SignedDriverActivity {
+        generation,
+        days: parse_driver_activity(data)?,
+        signature: signature.map(<[u8]>::to_vec),
+    }))
+}
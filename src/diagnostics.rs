@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+use pcsc::{Context, Protocols, ReaderState, ShareMode, State};
+use serde::Serialize;
+
+use crate::card::{read_card_identification, select_tachograph_df};
+use crate::error::CardReaderError;
+use crate::quirks::{QuirksTransport, ReaderQuirks};
+
+/// A step of the reader self-check, in the order the steps are run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCheck {
+    /// The PC/SC context is valid
+    ContextEstablished,
+    /// At least one reader is connected
+    ReaderPresent,
+    /// A card is present in one of the readers and can be connected to
+    CardPresent,
+    /// The tachograph application of the card can be selected
+    TachographDfSelectable,
+    /// The card identification EF can be read and parsed
+    IdentificationReadable,
+}
+
+impl DiagnosticCheck {
+    /// All the checks in the order they are run
+    pub const ALL: [DiagnosticCheck; 5] = [
+        DiagnosticCheck::ContextEstablished,
+        DiagnosticCheck::ReaderPresent,
+        DiagnosticCheck::CardPresent,
+        DiagnosticCheck::TachographDfSelectable,
+        DiagnosticCheck::IdentificationReadable,
+    ];
+}
+
+/// Outcome of a step of the self-check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Not run because an earlier step failed
+    Skipped,
+}
+
+/// Result of a single step of the self-check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticStep {
+    pub check: DiagnosticCheck,
+    pub status: CheckStatus,
+    /// How long the step took, zero for skipped steps
+    pub duration: Duration,
+    /// Why the step failed
+    pub error: Option<String>,
+}
+
+/// Results of the reader self-check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticsReport {
+    /// Name of the reader the card was found in
+    pub reader: Option<String>,
+    /// Result of every step, including the ones skipped after a failure
+    pub steps: Vec<DiagnosticStep>,
+}
+
+impl DiagnosticsReport {
+    /// Returns whether every step passed
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|step| step.status == CheckStatus::Passed)
+    }
+}
+
+/// Checks that the reader and the card are working, step by step
+///
+/// The steps are run in the order of [`DiagnosticCheck::ALL`] and stop at the first failure, so the first failed
+/// step tells whether the problem is in the PC/SC service, the reader or the card. The card is looked for in the
+/// first reader that reports one present.
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+///
+/// # Returns
+/// The result and the duration of each step
+pub fn diagnose(context: &Context) -> DiagnosticsReport {
+    let mut report = DiagnosticsReport { reader: None, steps: Vec::new() };
+    run_checks(context, &mut report);
+
+    for check in DiagnosticCheck::ALL.into_iter().skip(report.steps.len()) {
+        report.steps.push(DiagnosticStep { check, status: CheckStatus::Skipped, duration: Duration::ZERO, error: None });
+    }
+    report
+}
+
+/// Runs the steps of the self-check until one fails, adding their results to the report
+fn run_checks(context: &Context, report: &mut DiagnosticsReport) -> Option<()> {
+    run_step(report, DiagnosticCheck::ContextEstablished, || Ok(context.is_valid()?))?;
+
+    let readers = run_step(report, DiagnosticCheck::ReaderPresent, || {
+        let readers = context.list_readers_owned()?;
+        if readers.is_empty() {
+            return Err(CardReaderError::NoReaders);
+        }
+        Ok(readers)
+    })?;
+
+    let (reader, card) = run_step(report, DiagnosticCheck::CardPresent, || {
+        let mut reader_states = readers.iter().map(|reader| ReaderState::new(reader.as_c_str(), State::UNAWARE)).collect::<Vec<_>>();
+        context.get_status_change(Duration::ZERO, &mut reader_states)?;
+
+        let state = reader_states
+            .iter()
+            .find(|state| state.event_state().contains(State::PRESENT))
+            .ok_or(CardReaderError::Pcsc(pcsc::Error::NoSmartcard))?;
+        let card = context.connect(state.name(), ShareMode::Shared, Protocols::ANY)?;
+        Ok((state.name().to_string_lossy().into_owned(), card))
+    })?;
+
+    let card = QuirksTransport::new(&card, ReaderQuirks::for_reader(&reader));
+    report.reader = Some(reader);
+
+    let generation = run_step(report, DiagnosticCheck::TachographDfSelectable, || select_tachograph_df(&card, None))?;
+    run_step(report, DiagnosticCheck::IdentificationReadable, || read_card_identification(&card, Some(generation)))?;
+    Some(())
+}
+
+/// Runs a step of the self-check and adds its result to the report
+///
+/// # Returns
+/// The value produced by the step, or `None` if it failed
+fn run_step<T>(report: &mut DiagnosticsReport, check: DiagnosticCheck, step: impl FnOnce() -> Result<T, CardReaderError>) -> Option<T> {
+    let start = Instant::now();
+    let result = step();
+    let duration = start.elapsed();
+
+    match result {
+        Ok(value) => {
+            report.steps.push(DiagnosticStep { check, status: CheckStatus::Passed, duration, error: None });
+            Some(value)
+        }
+        Err(e) => {
+            report.steps.push(DiagnosticStep { check, status: CheckStatus::Failed, duration, error: Some(e.to_string()) });
+            None
+        }
+    }
+}
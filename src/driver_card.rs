@@ -0,0 +1,187 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+
+use crate::activity::{activity_changes, read_driver_activity, Activity, ActivityDay, Slot};
+use crate::application::{read_card_type, CardType};
+use crate::border_crossings::{read_border_crossings, BorderCrossing};
+use crate::card::{read_card_identification, select_tachograph_df, CardIdentification, Generation};
+use crate::card_download::read_last_download;
+use crate::control_activity::{read_control_activity, ControlActivity};
+use crate::current_usage::{read_current_usage, CurrentUsage};
+use crate::driving_licence::{read_driving_licence_info, DrivingLicenceInfo};
+use crate::error::CardReaderError;
+use crate::events::{read_events, Event};
+use crate::faults::{read_faults, Fault};
+use crate::load_unload::{read_load_unload_operations, LoadUnloadOperation};
+use crate::places::{read_places, read_places_authentication, PlaceAuthStatus, PlaceRecord};
+use crate::specific_conditions::{read_specific_conditions, SpecificCondition};
+use crate::transport::{CardTransport, TimeoutTransport};
+use crate::vehicle_units::{read_vehicle_units_used, VehicleUnitUsed};
+use crate::vehicles::{read_vehicles_used, VehicleUsed};
+use crate::verify::read_card_sign_certificate;
+
+/// All data read from a driver card
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DriverCard {
+    pub generation: Generation,
+    pub identification: CardIdentification,
+    /// Expiration date of the certificate of the key the card signs downloads with, only read from Gen2 cards as
+    /// Gen1 certificates can only be opened with the European root key
+    pub certificate_expiry: Option<DateTime<Utc>>,
+    /// Time of the last download of the card, `None` if the card has never been downloaded
+    pub last_download: Option<DateTime<Utc>>,
+    pub driving_licence_info: DrivingLicenceInfo,
+    pub events: Vec<Event>,
+    pub faults: Vec<Fault>,
+    pub activity: Vec<ActivityDay>,
+    pub vehicles_used: Vec<VehicleUsed>,
+    /// Vehicle units the card was inserted in, only recorded by Gen2 cards
+    pub vehicle_units_used: Option<Vec<VehicleUnitUsed>>,
+    pub places: Vec<PlaceRecord>,
+    /// Authentication statuses of the place GNSS positions, only recorded by Gen2 cards
+    pub places_authentication: Option<Vec<PlaceAuthStatus>>,
+    /// Border crossings of the vehicle, only recorded by Gen2 cards
+    pub border_crossings: Option<Vec<BorderCrossing>>,
+    /// Load/unload operations of the vehicle, only recorded by Gen2 cards
+    pub load_unload_operations: Option<Vec<LoadUnloadOperation>>,
+    pub current_usage: CurrentUsage,
+    pub control_activity: Option<ControlActivity>,
+    pub specific_conditions: Vec<SpecificCondition>,
+}
+
+impl DriverCard {
+    /// Returns whether the certificate of the card has lapsed
+    ///
+    /// # Arguments
+    /// - `now` - The time to check the expiry at
+    ///
+    /// # Returns
+    /// Whether the certificate expired before `now`, `false` if the expiry is not known
+    pub fn is_certificate_expired(&self, now: DateTime<Utc>) -> bool {
+        self.certificate_expiry.is_some_and(|certificate_expiry| certificate_expiry < now)
+    }
+
+    /// Drops the events and faults that began before a day, and the activity of the days before it
+    ///
+    /// # Arguments
+    /// - `since` - The first day to keep, starting at 00:00 UTC
+    pub fn retain_since(&mut self, since: NaiveDate) {
+        let start = since.and_time(NaiveTime::MIN).and_utc();
+        self.events.retain(|event| event.begin >= start);
+        self.faults.retain(|fault| fault.begin >= start);
+        self.activity.retain(|day| day.date >= since);
+    }
+
+    /// Returns the activity changes recorded on the card
+    ///
+    /// # Returns
+    /// An iterator over the time each activity began, the slot it was recorded in and the activity, in chronological order
+    pub fn activity_changes(&self) -> impl Iterator<Item = (DateTime<Utc>, Slot, Activity)> + '_ {
+        activity_changes(&self.activity)
+    }
+}
+
+impl fmt::Display for DriverCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<20}{:?}", "Generation:", self.generation)?;
+        writeln!(f, "{}", self.identification)?;
+        writeln!(f, "{:<20}{}", "Driving licence:", self.driving_licence_info.licence_number)?;
+        if let Some(certificate_expiry) = self.certificate_expiry {
+            let expired = if self.is_certificate_expired(Utc::now()) { " (expired)" } else { "" };
+            writeln!(f, "{:<20}{}{}", "Certificate expiry:", certificate_expiry.format("%Y-%m-%d"), expired)?;
+        }
+
+        writeln!(f, "\nEvents ({}):", self.events.len())?;
+        for event in &self.events {
+            writeln!(f, "  {}", event)?;
+        }
+
+        writeln!(f, "\nFaults ({}):", self.faults.len())?;
+        for fault in &self.faults {
+            writeln!(f, "  {}", fault)?;
+        }
+
+        write!(f, "\nActivity ({} days):", self.activity.len())?;
+        for day in &self.activity {
+            write!(f, "\n  {}", day.to_string().replace('\n', "\n  "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads all supported EFs from the tachograph application of the card
+///
+/// Only driver cards are supported, other card types fail with `CardReaderError::UnsupportedCardType` before any
+/// driver-only EF is read. When other processes may use the card at the same time, run the read in
+/// [`with_transaction`](crate::transport::with_transaction) so their APDUs can't interleave with it.
+///
+/// # Arguments
+/// - `card` - The smart card to read
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The data read from the card
+pub fn read_full_card(card: &impl CardTransport, generation: Option<Generation>) -> Result<DriverCard, CardReaderError> {
+    let generation = select_tachograph_df(card, generation)?;
+    let selected = Some(generation);
+
+    match read_card_type(card, selected)? {
+        CardType::Driver => {}
+        card_type => return Err(CardReaderError::UnsupportedCardType(card_type)),
+    }
+
+    Ok(DriverCard {
+        generation,
+        identification: read_card_identification(card, selected)?,
+        certificate_expiry: match generation {
+            Generation::Gen1 => None,
+            Generation::Gen2 => read_card_sign_certificate(card)?.expiration_date,
+        },
+        last_download: read_last_download(card, selected)?,
+        driving_licence_info: read_driving_licence_info(card, selected)?,
+        events: read_events(card, selected)?,
+        faults: read_faults(card, selected)?,
+        activity: read_driver_activity(card, selected)?,
+        vehicles_used: read_vehicles_used(card, selected)?,
+        vehicle_units_used: match generation {
+            Generation::Gen1 => None,
+            Generation::Gen2 => Some(read_vehicle_units_used(card)?),
+        },
+        places: read_places(card, selected)?,
+        places_authentication: match generation {
+            Generation::Gen1 => None,
+            Generation::Gen2 => Some(read_places_authentication(card)?),
+        },
+        border_crossings: match generation {
+            Generation::Gen1 => None,
+            Generation::Gen2 => Some(read_border_crossings(card)?),
+        },
+        load_unload_operations: match generation {
+            Generation::Gen1 => None,
+            Generation::Gen2 => Some(read_load_unload_operations(card)?),
+        },
+        current_usage: read_current_usage(card, selected)?,
+        control_activity: read_control_activity(card, selected)?,
+        specific_conditions: read_specific_conditions(card, selected)?,
+    })
+}
+
+/// Reads all supported EFs from the tachograph application of the card, giving up on APDUs the card doesn't answer in time
+///
+/// # Arguments
+/// - `card` - The smart card to read
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `timeout` - How long to wait for the card to answer each APDU, e.g. [`DEFAULT_TRANSMIT_TIMEOUT`](crate::transport::DEFAULT_TRANSMIT_TIMEOUT)
+///
+/// # Returns
+/// The data read from the card, or `TransmitTimeout` if the card stopped responding
+pub fn read_full_card_with_timeout<T>(card: Arc<T>, generation: Option<Generation>, timeout: Duration) -> Result<DriverCard, CardReaderError>
+where
+    T: CardTransport + Send + Sync + 'static,
+{
+    read_full_card(&TimeoutTransport::new(card, timeout), generation)
+}
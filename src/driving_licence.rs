@@ -0,0 +1,65 @@
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_DRIVING_LICENCE_INFO_EF};
+use crate::error::CardReaderError;
+use crate::nation::{nation_alpha, nation_name};
+use crate::parse::{decode_codepage_string, take_n};
+use crate::transport::CardTransport;
+
+pub const DRIVING_LICENCE_INFO_LENGTH: u16 = 53;
+
+/// Information about the driving licence of the card holder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DrivingLicenceInfo {
+    pub issuing_authority: String,
+    pub issuing_nation: u8,
+    pub licence_number: String,
+}
+
+impl DrivingLicenceInfo {
+    /// Parses the driving licence information from the contents of the driving licence info EF
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (53 bytes)
+    /// - `generation` - The generation of the application the EF was read from, which determines the authority encoding
+    ///
+    /// # Returns
+    /// The parsed driving licence information
+    pub fn new(data: &[u8], generation: Generation) -> Result<Self, CardReaderError> {
+        let (issuing_authority, remaining) = take_n(36, data)?;
+        let (issuing_nation, remaining) = take_n(1, remaining)?;
+        let (licence_number, _) = take_n(16, remaining)?;
+        let licence_number = String::from_utf8(licence_number.to_vec())?;
+
+        Ok(DrivingLicenceInfo {
+            issuing_authority: decode_codepage_string(issuing_authority, generation),
+            issuing_nation: issuing_nation[0],
+            licence_number: licence_number.trim_end_matches(['\0', ' ']).to_string(),
+        })
+    }
+
+    /// Returns the name of the nation that issued the driving licence
+    pub fn issuing_nation_name(&self) -> Option<&'static str> {
+        nation_name(self.issuing_nation)
+    }
+
+    /// Returns the distinguishing sign of the nation that issued the driving licence
+    pub fn issuing_nation_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.issuing_nation)
+    }
+}
+
+/// Reads the driving licence information from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the driving licence information from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The driving licence information
+pub fn read_driving_licence_info(card: &impl CardTransport, generation: Option<Generation>) -> Result<DrivingLicenceInfo, CardReaderError> {
+    let generation = select_tachograph_ef(card, generation, TACHOGRAPH_DRIVING_LICENCE_INFO_EF)?;
+    let data = read_ef_fully(card, DRIVING_LICENCE_INFO_LENGTH, None)?;
+    DrivingLicenceInfo::new(&data, generation)
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+
+use crate::hex::hexdump;
+
+/// Standard base64 alphabet of RFC 4648
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// Number of bytes encoded per line of hex output
+const HEX_LINE_LENGTH: usize = 32;
+/// Number of bytes encoded per write of base64 output, a multiple of 3 so that only the last block is padded
+const BASE64_BLOCK_LENGTH: usize = 3 * 1024;
+
+/// Encoding of the contents of an EF when dumping it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpEncoding {
+    /// Canonical hex and ASCII dump with offsets, see [`hexdump`]
+    Hexdump,
+    /// Plain lowercase hex, 32 bytes per line, e.g. for `xxd -r -p`
+    Hex,
+    /// Standard base64 with padding on a single line
+    Base64,
+    /// The bytes as is
+    Raw,
+}
+
+/// Writes the contents of an EF in an encoding
+///
+/// The output is written in blocks, so even the largest EFs are never encoded into a single string in memory, except
+/// for [`DumpEncoding::Hexdump`].
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `encoding` - The encoding to write the contents in
+/// - `writer` - The writer to write the encoded contents to
+pub fn write_encoded(data: &[u8], encoding: DumpEncoding, writer: &mut impl Write) -> io::Result<()> {
+    match encoding {
+        DumpEncoding::Hexdump => writer.write_all(hexdump(data).as_bytes()),
+        DumpEncoding::Hex => {
+            for line in data.chunks(HEX_LINE_LENGTH) {
+                let mut encoded = line.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+                encoded.push('\n');
+                writer.write_all(encoded.as_bytes())?;
+            }
+            Ok(())
+        }
+        DumpEncoding::Base64 => {
+            for block in data.chunks(BASE64_BLOCK_LENGTH) {
+                writer.write_all(&encode_base64(block))?;
+            }
+            writer.write_all(b"\n")
+        }
+        DumpEncoding::Raw => writer.write_all(data),
+    }
+}
+
+/// Encodes bytes as standard base64, padding the last group with `=`
+///
+/// # Arguments
+/// - `bytes` - The bytes to encode
+///
+/// # Returns
+/// The base64 characters
+pub fn encode_base64(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let [b0, b1, b2] = [0, 1, 2].map(|i| group.get(i).copied().unwrap_or(0));
+        let bits = u32::from_be_bytes([0, b0, b1, b2]);
+        let characters = [18, 12, 6, 0].map(|shift| BASE64_ALPHABET[(bits >> shift) as usize & 0x3F]);
+        // A group of n bytes is encoded in n + 1 characters, padded to 4
+        encoded.extend_from_slice(&characters[..group.len() + 1]);
+        encoded.extend(std::iter::repeat_n(b'=', 3 - group.len()));
+    }
+    encoded
+}
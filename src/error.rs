@@ -0,0 +1,145 @@
+use thiserror::Error;
+
+use crate::apdu::status_word_meaning;
+use crate::application::CardType;
+
+/// Errors that can occur while reading and parsing a card
+#[derive(Debug, Error)]
+pub enum CardReaderError {
+    /// The PC/SC layer failed to communicate with the reader or the card
+    #[cfg(feature = "pcsc")]
+    #[error("{0}")]
+    Pcsc(#[from] pcsc::Error),
+    /// The data was too short to contain the expected fields
+    #[error("Data is too short")]
+    Truncated,
+    /// A string field did not contain valid UTF-8
+    #[error("Invalid UTF-8 in string field")]
+    InvalidUtf8(#[from] std::string::FromUtf8Error),
+    /// The card responded with a status word other than `90 00`
+    #[error("Unexpected status word {0:02X} {1:02X} ({meaning})", meaning = status_word_meaning(*.0, *.1))]
+    UnexpectedStatusWord(u8, u8),
+    /// A BCD encoded field contained a nibble greater than 9
+    #[error("Invalid BCD value")]
+    InvalidBcd,
+    /// A date field did not contain a valid calendar date
+    #[error("Invalid date")]
+    InvalidDate,
+    /// A ring buffer pointer of a record EF pointed beyond the end of the buffer
+    #[error("Ring buffer pointer {pointer} is beyond the end of the buffer of length {file_len}")]
+    CorruptRingBuffer { pointer: usize, file_len: usize },
+    /// The record counts of the application identification give a record EF longer than a file can be
+    #[error("{record_count} records of {record_length} bytes after a {header_length} byte header don't fit in an EF")]
+    InvalidEfLength { header_length: usize, record_count: usize, record_length: usize },
+    /// A BER-TLV data object was malformed
+    #[error("Invalid TLV data")]
+    InvalidTlv,
+    /// No connected reader matched the requested name or index
+    #[error("Reader {selector:?} not found, available readers: {}", available.join(", "))]
+    ReaderNotFound { selector: String, available: Vec<String> },
+    /// No readers are connected to the system
+    #[error("No readers are connected")]
+    NoReaders,
+    /// No card was inserted before the timeout elapsed
+    #[error("Timed out waiting for a card")]
+    Timeout,
+    /// The card did not answer an APDU before the transmit timeout elapsed
+    #[error("The card did not respond within {0:?}")]
+    TransmitTimeout(std::time::Duration),
+    /// The card is not a driver card, so its data can't be read as one
+    #[error("Unsupported card type {0:?}")]
+    UnsupportedCardType(CardType),
+    /// The card was reset or removed during a read, losing the selected file, so the read must be restarted
+    #[error("The card was reset, the read must be restarted")]
+    CardReset,
+    /// The card was reset or removed and the transport can't reconnect to it
+    #[error("Reconnecting to the card is not supported")]
+    ReconnectUnsupported,
+    /// An EF registry could not be parsed
+    #[error("Invalid EF registry: {0}")]
+    InvalidRegistry(#[from] toml::de::Error),
+    /// The EF registry has no definition for the requested EF
+    #[error("Unknown EF {0:?}")]
+    UnknownEf(String),
+    /// A blocking card operation run on the async runtime panicked or was cancelled
+    #[cfg(feature = "tokio")]
+    #[error("Blocking card task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+    /// The thread reading the card in one of several readers panicked
+    #[error("Reading the card panicked")]
+    ReaderThreadPanicked,
+    /// A certificate was not signed with the key of its issuer
+    #[error("Invalid certificate")]
+    InvalidCertificate,
+    /// The card failed to prove its identity during mutual authentication
+    #[error("Card authentication failed")]
+    AuthenticationFailed,
+    /// The card rejected the PIN
+    #[error("Wrong PIN, {tries_left} tries left")]
+    WrongPin { tries_left: u8 },
+    /// The PIN is blocked after too many wrong tries
+    #[error("The PIN is blocked")]
+    PinBlocked,
+    /// The PIN was longer than the card accepts
+    #[error("Invalid PIN length")]
+    InvalidPinLength,
+    /// Secure messaging session keys were not valid AES keys
+    #[error("Invalid secure messaging session key")]
+    InvalidSessionKey,
+    /// A secure messaging response was malformed or its MAC did not verify
+    #[error("Secure messaging response failed verification")]
+    SecureMessaging,
+    /// Writing CSV output failed
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+    /// Reading or writing a file failed
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl CardReaderError {
+    /// Machine-readable name of the error variant, e.g. `unexpected_status_word`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "pcsc")]
+            CardReaderError::Pcsc(_) => "pcsc",
+            CardReaderError::Truncated => "truncated",
+            CardReaderError::InvalidUtf8(_) => "invalid_utf8",
+            CardReaderError::UnexpectedStatusWord(_, _) => "unexpected_status_word",
+            CardReaderError::InvalidBcd => "invalid_bcd",
+            CardReaderError::InvalidDate => "invalid_date",
+            CardReaderError::CorruptRingBuffer { .. } => "corrupt_ring_buffer",
+            CardReaderError::InvalidEfLength { .. } => "invalid_ef_length",
+            CardReaderError::InvalidTlv => "invalid_tlv",
+            CardReaderError::ReaderNotFound { .. } => "reader_not_found",
+            CardReaderError::NoReaders => "no_readers",
+            CardReaderError::Timeout => "timeout",
+            CardReaderError::TransmitTimeout(_) => "transmit_timeout",
+            CardReaderError::UnsupportedCardType(_) => "unsupported_card_type",
+            CardReaderError::CardReset => "card_reset",
+            CardReaderError::ReconnectUnsupported => "reconnect_unsupported",
+            CardReaderError::InvalidRegistry(_) => "invalid_registry",
+            CardReaderError::UnknownEf(_) => "unknown_ef",
+            #[cfg(feature = "tokio")]
+            CardReaderError::Join(_) => "join",
+            CardReaderError::ReaderThreadPanicked => "reader_thread_panicked",
+            CardReaderError::InvalidCertificate => "invalid_certificate",
+            CardReaderError::AuthenticationFailed => "authentication_failed",
+            CardReaderError::WrongPin { .. } => "wrong_pin",
+            CardReaderError::PinBlocked => "pin_blocked",
+            CardReaderError::InvalidPinLength => "invalid_pin_length",
+            CardReaderError::InvalidSessionKey => "invalid_session_key",
+            CardReaderError::SecureMessaging => "secure_messaging",
+            CardReaderError::Csv(_) => "csv",
+            CardReaderError::Io(_) => "io",
+        }
+    }
+
+    /// The status word the card responded with, if the error was caused by one
+    pub fn status_word(&self) -> Option<(u8, u8)> {
+        match self {
+            CardReaderError::UnexpectedStatusWord(sw1, sw2) => Some((*sw1, *sw2)),
+            _ => None,
+        }
+    }
+}
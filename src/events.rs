@@ -0,0 +1,218 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_EVENTS_DATA_EF};
+use crate::error::CardReaderError;
+use crate::nation::nation_alpha;
+use crate::parse::{parse_time_real, parse_vehicle_registration, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single `CardEventRecord`
+pub const EVENT_RECORD_LENGTH: usize = 24;
+/// Number of event groups the Gen1 events EF holds records for
+pub const EVENT_TYPE_COUNT_GEN1: usize = 6;
+/// Number of event groups the Gen2 events EF holds records for
+pub const EVENT_TYPE_COUNT_GEN2: usize = 11;
+/// Maximum number of records stored per event type, the card reports its own count in the application identification
+pub const NO_OF_EVENTS_PER_TYPE: usize = 12;
+
+/// Type of an event recorded on the card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EventType {
+    NoFurtherDetails,
+    InsertionOfNonValidCard,
+    CardConflict,
+    TimeOverlap,
+    DrivingWithoutAppropriateCard,
+    CardInsertionWhileDriving,
+    LastCardSessionNotCorrectlyClosed,
+    OverSpeeding,
+    PowerSupplyInterruption,
+    MotionDataError,
+    VehicleMotionConflict,
+    /// Vehicle unit related security breach attempt, with the detailed code
+    VuSecurityBreachAttempt(u8),
+    /// Motion sensor related security breach attempt, with the detailed code
+    SensorSecurityBreachAttempt(u8),
+    Unknown(u8),
+}
+
+impl From<u8> for EventType {
+    fn from(code: u8) -> Self {
+        match code {
+            0x00 => EventType::NoFurtherDetails,
+            0x01 => EventType::InsertionOfNonValidCard,
+            0x02 => EventType::CardConflict,
+            0x03 => EventType::TimeOverlap,
+            0x04 => EventType::DrivingWithoutAppropriateCard,
+            0x05 => EventType::CardInsertionWhileDriving,
+            0x06 => EventType::LastCardSessionNotCorrectlyClosed,
+            0x07 => EventType::OverSpeeding,
+            0x08 => EventType::PowerSupplyInterruption,
+            0x09 => EventType::MotionDataError,
+            0x0A => EventType::VehicleMotionConflict,
+            0x10..=0x1F => EventType::VuSecurityBreachAttempt(code),
+            0x20..=0x2F => EventType::SensorSecurityBreachAttempt(code),
+            _ => EventType::Unknown(code),
+        }
+    }
+}
+
+/// Describes an `EventFaultType` code of an event
+///
+/// # Arguments
+/// - `code` - The event type code
+///
+/// # Returns
+/// The description of the event type as given in the specification
+pub fn event_description(code: u8) -> &'static str {
+    match code {
+        0x00 => "No further details",
+        0x01 => "Insertion of a non-valid card",
+        0x02 => "Card conflict",
+        0x03 => "Time overlap",
+        0x04 => "Driving without an appropriate card",
+        0x05 => "Card insertion while driving",
+        0x06 => "Last card session not correctly closed",
+        0x07 => "Over speeding",
+        0x08 => "Power supply interruption",
+        0x09 => "Motion data error",
+        0x0A => "Vehicle motion conflict",
+        0x0B => "Time conflict (GNSS versus VU internal clock)",
+        0x0C => "Communication error with the remote communication facility",
+        0x0D => "Absence of position information from GNSS receiver",
+        0x0E => "Communication error with the external GNSS facility",
+        0x10 => "VU security breach attempt, no further details",
+        0x11 => "Motion sensor authentication failure",
+        0x12 => "Tachograph card authentication failure",
+        0x13 => "Unauthorised change of motion sensor",
+        0x14 => "Card data input integrity error",
+        0x15 => "Stored user data integrity error",
+        0x16 => "Internal data transfer error",
+        0x17 => "Unauthorised case opening",
+        0x18 => "Hardware sabotage",
+        0x19 => "Tamper detection of GNSS",
+        0x1A => "External GNSS facility authentication failure",
+        0x1B => "External GNSS facility certificate expired",
+        0x20 => "Sensor security breach attempt, no further details",
+        0x21 => "Motion sensor authentication failure",
+        0x22 => "Motion sensor stored data integrity error",
+        0x23 => "Motion sensor internal data transfer error",
+        0x24 => "Motion sensor unauthorised case opening",
+        0x25 => "Motion sensor hardware sabotage",
+        _ => "Unknown event",
+    }
+}
+
+/// An event recorded on the card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Event {
+    pub event_type: u8,
+    /// Description of the event type, see [`event_description`]
+    pub description: &'static str,
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub vehicle_registration_nation: u8,
+    pub vehicle_registration: String,
+}
+
+impl Event {
+    /// Parses a single `CardEventRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (24 bytes)
+    ///
+    /// # Returns
+    /// The parsed event, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (event_type, remaining) = take_n(1, record)?;
+        let (begin, remaining) = take_array(remaining)?;
+        let (end, remaining) = take_array(remaining)?;
+        let (vehicle_registration_nation, vehicle_registration) = parse_vehicle_registration(remaining)?;
+
+        let (Some(begin), Some(end)) = (parse_time_real(&begin), parse_time_real(&end)) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Event {
+            event_type: event_type[0],
+            description: event_description(event_type[0]),
+            begin,
+            end,
+            vehicle_registration_nation,
+            vehicle_registration,
+        }))
+    }
+
+    /// Returns the type of the event
+    pub fn kind(&self) -> EventType {
+        EventType::from(self.event_type)
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} - {}  {}",
+            self.begin.format("%Y-%m-%d %H:%M"),
+            self.end.format("%Y-%m-%d %H:%M"),
+            self.description
+        )?;
+        if !self.vehicle_registration.is_empty() {
+            let nation = nation_alpha(self.vehicle_registration_nation).unwrap_or("?");
+            write!(f, "  {} {}", nation, self.vehicle_registration)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of event groups in the events EF of an application generation
+///
+/// # Arguments
+/// - `generation` - The generation of the application
+pub fn event_type_count(generation: Generation) -> usize {
+    match generation {
+        Generation::Gen1 => EVENT_TYPE_COUNT_GEN1,
+        Generation::Gen2 => EVENT_TYPE_COUNT_GEN2,
+    }
+}
+
+/// Parses the contents of the events EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `events_per_type` - The number of records per event type, from the application identification
+/// - `generation` - The generation of the application the EF was read from, which determines the number of groups
+///
+/// # Returns
+/// The events in the EF, skipping empty slots
+pub fn parse_events(data: &[u8], events_per_type: usize, generation: Generation) -> Result<Vec<Event>, CardReaderError> {
+    let mut events = Vec::new();
+    for record in data.chunks_exact(EVENT_RECORD_LENGTH).take(event_type_count(generation) * events_per_type) {
+        if let Some(event) = Event::new(record)? {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// Reads the events from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the events from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The events stored on the card
+pub fn read_events(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<Event>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let events_per_type = app_id.events_per_type as usize;
+    select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_EVENTS_DATA_EF)?;
+    let data = read_ef_fully(card, record_ef_length(0, event_type_count(app_id.generation) * events_per_type, EVENT_RECORD_LENGTH)?, None)?;
+    parse_events(&data, events_per_type, app_id.generation)
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_FAULTS_DATA_EF};
+use crate::error::CardReaderError;
+use crate::nation::nation_alpha;
+use crate::parse::{parse_time_real, parse_vehicle_registration, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single `CardFaultRecord`
+pub const FAULT_RECORD_LENGTH: usize = 24;
+/// Number of fault groups (recording equipment and card faults) the faults EF holds records for
+pub const FAULT_TYPE_COUNT: usize = 2;
+/// Maximum number of records stored per fault group, the card reports its own count in the application identification
+pub const NO_OF_FAULTS_PER_TYPE: usize = 24;
+
+/// Type of a fault recorded on the card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FaultType {
+    RecordingEquipmentFault,
+    VuInternalFault,
+    PrinterFault,
+    DisplayFault,
+    DownloadingFault,
+    SensorFault,
+    InternalGnssReceiverFault,
+    ExternalGnssFacilityFault,
+    RemoteCommunicationFacilityFault,
+    ItsInterfaceFault,
+    CardFault,
+    /// Manufacturer specific fault, with the detailed code
+    ManufacturerSpecific(u8),
+    Unknown(u8),
+}
+
+impl From<u8> for FaultType {
+    fn from(code: u8) -> Self {
+        match code {
+            0x30 => FaultType::RecordingEquipmentFault,
+            0x31 => FaultType::VuInternalFault,
+            0x32 => FaultType::PrinterFault,
+            0x33 => FaultType::DisplayFault,
+            0x34 => FaultType::DownloadingFault,
+            0x35 => FaultType::SensorFault,
+            0x36 => FaultType::InternalGnssReceiverFault,
+            0x37 => FaultType::ExternalGnssFacilityFault,
+            0x38 => FaultType::RemoteCommunicationFacilityFault,
+            0x39 => FaultType::ItsInterfaceFault,
+            0x40 => FaultType::CardFault,
+            0xE0..=0xEF => FaultType::ManufacturerSpecific(code),
+            _ => FaultType::Unknown(code),
+        }
+    }
+}
+
+/// Describes an `EventFaultType` code of a fault
+///
+/// # Arguments
+/// - `code` - The fault type code
+///
+/// # Returns
+/// The description of the fault type as given in the specification
+pub fn fault_description(code: u8) -> &'static str {
+    match code {
+        0x30 => "Recording equipment fault, no further details",
+        0x31 => "VU internal fault",
+        0x32 => "Printer fault",
+        0x33 => "Display fault",
+        0x34 => "Downloading fault",
+        0x35 => "Sensor fault",
+        0x36 => "Internal GNSS receiver fault",
+        0x37 => "External GNSS facility fault",
+        0x38 => "Remote communication facility fault",
+        0x39 => "ITS interface fault",
+        0x40 => "Card fault, no further details",
+        0xE0..=0xEF => "Manufacturer specific fault",
+        _ => "Unknown fault",
+    }
+}
+
+/// A fault recorded on the card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fault {
+    pub fault_type: u8,
+    /// Description of the fault type, see [`fault_description`]
+    pub description: &'static str,
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub vehicle_registration_nation: u8,
+    pub vehicle_registration: String,
+}
+
+impl Fault {
+    /// Parses a single `CardFaultRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (24 bytes)
+    ///
+    /// # Returns
+    /// The parsed fault, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (fault_type, remaining) = take_n(1, record)?;
+        let (begin, remaining) = take_array(remaining)?;
+        let (end, remaining) = take_array(remaining)?;
+        let (vehicle_registration_nation, vehicle_registration) = parse_vehicle_registration(remaining)?;
+
+        let (Some(begin), Some(end)) = (parse_time_real(&begin), parse_time_real(&end)) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Fault {
+            fault_type: fault_type[0],
+            description: fault_description(fault_type[0]),
+            begin,
+            end,
+            vehicle_registration_nation,
+            vehicle_registration,
+        }))
+    }
+
+    /// Returns the type of the fault
+    pub fn kind(&self) -> FaultType {
+        FaultType::from(self.fault_type)
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} - {}  {}",
+            self.begin.format("%Y-%m-%d %H:%M"),
+            self.end.format("%Y-%m-%d %H:%M"),
+            self.description
+        )?;
+        if !self.vehicle_registration.is_empty() {
+            let nation = nation_alpha(self.vehicle_registration_nation).unwrap_or("?");
+            write!(f, "  {} {}", nation, self.vehicle_registration)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the contents of the faults EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `faults_per_type` - The number of records per fault type, from the application identification
+///
+/// # Returns
+/// The faults in the EF, skipping empty slots
+pub fn parse_faults(data: &[u8], faults_per_type: usize) -> Result<Vec<Fault>, CardReaderError> {
+    let mut faults = Vec::new();
+    for record in data.chunks_exact(FAULT_RECORD_LENGTH).take(FAULT_TYPE_COUNT * faults_per_type) {
+        if let Some(fault) = Fault::new(record)? {
+            faults.push(fault);
+        }
+    }
+    Ok(faults)
+}
+
+/// Reads the faults from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the faults from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The faults stored on the card
+pub fn read_faults(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<Fault>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let faults_per_type = app_id.faults_per_type as usize;
+    select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_FAULTS_DATA_EF)?;
+    let data = read_ef_fully(card, record_ef_length(0, FAULT_TYPE_COUNT * faults_per_type, FAULT_RECORD_LENGTH)?, None)?;
+    parse_faults(&data, faults_per_type)
+}
@@ -0,0 +1,73 @@
+use serde::Serialize;
+
+use crate::error::CardReaderError;
+use crate::tlv::{find_tlv, parse_tlv, Tlv};
+
+pub const FCP_TEMPLATE_TAG: u16 = 0x62;
+pub const FCI_TEMPLATE_TAG: u16 = 0x6F;
+pub const FILE_SIZE_TAG: u16 = 0x80;
+pub const TOTAL_FILE_SIZE_TAG: u16 = 0x81;
+pub const FILE_IDENTIFIER_TAG: u16 = 0x83;
+pub const DF_NAME_TAG: u16 = 0x84;
+
+/// File control parameters (FCP) returned by the card when selecting a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Fcp {
+    /// File identifier of the selected file
+    pub file_id: Option<[u8; 2]>,
+    /// Number of data bytes in the file
+    pub file_size: Option<u16>,
+    /// Number of bytes allocated for the file, including structural information
+    pub total_file_size: Option<u16>,
+    /// Application identifier of a selected DF
+    pub df_name: Option<Vec<u8>>,
+}
+
+impl Fcp {
+    /// Parses an FCP template
+    ///
+    /// Both the FCP (`62`) and the FCI (`6F`) templates are accepted. An empty response yields an FCP without any
+    /// parameters, and parameters missing from a short template are left unset.
+    ///
+    /// # Arguments
+    /// - `data` - The response data of a SELECT command
+    ///
+    /// # Returns
+    /// The parsed file control parameters
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let objects = parse_tlv(data)?;
+        let Some(template) = find_tlv(&objects, FCP_TEMPLATE_TAG).or_else(|| find_tlv(&objects, FCI_TEMPLATE_TAG)) else {
+            return if objects.is_empty() { Ok(Fcp::default()) } else { Err(CardReaderError::InvalidTlv) };
+        };
+        let parameters = template.children()?;
+
+        Ok(Fcp {
+            file_id: find_tlv(&parameters, FILE_IDENTIFIER_TAG).and_then(|file_id| file_id.value.as_slice().try_into().ok()),
+            file_size: find_tlv(&parameters, FILE_SIZE_TAG).map(parse_size),
+            total_file_size: find_tlv(&parameters, TOTAL_FILE_SIZE_TAG).map(parse_size),
+            df_name: find_tlv(&parameters, DF_NAME_TAG).map(|df_name| df_name.value.clone()),
+        })
+    }
+
+    /// Returns the number of bytes that can be read from the file, if known
+    pub fn readable_length(&self) -> Option<u16> {
+        self.file_size.or(self.total_file_size)
+    }
+}
+
+/// Parses the file control parameters returned by a SELECT command
+///
+/// # Arguments
+/// - `bytes` - The response data of the SELECT command
+///
+/// # Returns
+/// The parsed file control parameters, see [`Fcp::new`]
+pub fn parse_fcp(bytes: &[u8]) -> Result<Fcp, CardReaderError> {
+    Fcp::new(bytes)
+}
+
+/// Decodes a big endian size, saturating sizes that don't fit in 16 bits
+fn parse_size(object: &Tlv) -> u16 {
+    let size = object.value.iter().fold(0usize, |size, &byte| (size << 8) | byte as usize);
+    size.min(u16::MAX as usize) as u16
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, take_array, take_n};
+
+/// Length of a `GNSSPlaceRecord`
+pub const GNSS_PLACE_RECORD_LENGTH: usize = 11;
+/// Length of a `GNSSAccumulatedDrivingRecord`, i.e. a time, a `GNSSPlaceRecord` and an odometer value
+pub const GNSS_ACCUMULATED_DRIVING_RECORD_LENGTH: usize = 18;
+/// Length of a Gen2v2 `GNSSPlaceAuthRecord`
+pub const GNSS_PLACE_AUTH_RECORD_LENGTH: usize = 12;
+/// Value of a `GeoCoordinates` latitude or longitude when the position is unknown
+pub const UNKNOWN_GEO_COORDINATE: i32 = 0x7FFFFF;
+
+/// A GNSS position in decimal degrees, positive to the north and to the east
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GeoCoordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoCoordinates {
+    /// Parses a `GeoCoordinates` value, i.e. a 3-byte latitude followed by a 3-byte longitude
+    ///
+    /// # Arguments
+    /// - `bytes` - The 6 bytes of the `GeoCoordinates` value
+    ///
+    /// # Returns
+    /// The position, or `None` if the position is unknown
+    pub fn new(bytes: &[u8; 6]) -> Option<Self> {
+        let latitude = parse_geo_coordinate(&[bytes[0], bytes[1], bytes[2]])?;
+        let longitude = parse_geo_coordinate(&[bytes[3], bytes[4], bytes[5]])?;
+        Some(GeoCoordinates { latitude, longitude })
+    }
+}
+
+/// Parses a single latitude or longitude, encoded as a signed 3-byte `±DDMM.M` value multiplied by 10
+///
+/// # Arguments
+/// - `bytes` - The 3 bytes of the coordinate
+///
+/// # Returns
+/// The coordinate in decimal degrees, or `None` if it is unknown
+pub fn parse_geo_coordinate(bytes: &[u8; 3]) -> Option<f64> {
+    // Sign extend the 24-bit two's complement value
+    let value = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]) >> 8;
+    if value == UNKNOWN_GEO_COORDINATE {
+        return None;
+    }
+
+    let tenths_of_minutes = value.unsigned_abs();
+    let degrees = tenths_of_minutes / 1000;
+    let minutes = (tenths_of_minutes % 1000) as f64 / 10.0;
+    Some(value.signum() as f64 * (degrees as f64 + minutes / 60.0))
+}
+
+/// A GNSS position recorded by the VU
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GnssPlaceRecord {
+    /// Time the position was determined, `None` if no position was recorded
+    pub time_stamp: Option<DateTime<Utc>>,
+    /// Accuracy of the position in metres
+    pub gnss_accuracy: u8,
+    /// The position, `None` if no fix was available
+    pub geo_coordinates: Option<GeoCoordinates>,
+}
+
+impl GnssPlaceRecord {
+    /// Parses a `GNSSPlaceRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (11 bytes)
+    ///
+    /// # Returns
+    /// The parsed GNSS place
+    pub fn new(record: &[u8]) -> Result<Self, CardReaderError> {
+        let (time_stamp, remaining) = take_array(record)?;
+        let ([gnss_accuracy], remaining) = take_array(remaining)?;
+        let (geo_coordinates, _) = take_array(remaining)?;
+
+        Ok(GnssPlaceRecord {
+            time_stamp: parse_time_real(&time_stamp),
+            gnss_accuracy,
+            geo_coordinates: GeoCoordinates::new(&geo_coordinates),
+        })
+    }
+}
+
+/// A GNSS position recorded by the VU along with its authentication status, recorded by Gen2v2 cards
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GnssPlaceAuthRecord {
+    /// Time the position was determined, `None` if no position was recorded
+    pub time_stamp: Option<DateTime<Utc>>,
+    /// Accuracy of the position in metres
+    pub gnss_accuracy: u8,
+    /// The position, `None` if no fix was available
+    pub geo_coordinates: Option<GeoCoordinates>,
+    /// Whether the position was authenticated by the VU
+    pub authenticated: bool,
+}
+
+impl GnssPlaceAuthRecord {
+    /// Parses a `GNSSPlaceAuthRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (12 bytes)
+    ///
+    /// # Returns
+    /// The parsed GNSS place
+    pub fn new(record: &[u8]) -> Result<Self, CardReaderError> {
+        let (gnss_place, remaining) = take_n(GNSS_PLACE_RECORD_LENGTH, record)?;
+        let ([authentication_status], _) = take_array(remaining)?;
+        let gnss_place = GnssPlaceRecord::new(gnss_place)?;
+
+        Ok(GnssPlaceAuthRecord {
+            time_stamp: gnss_place.time_stamp,
+            gnss_accuracy: gnss_place.gnss_accuracy,
+            geo_coordinates: gnss_place.geo_coordinates,
+            authenticated: authentication_status == 1,
+        })
+    }
+}
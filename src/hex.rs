@@ -0,0 +1,42 @@
+/// Formats bytes as space separated hex
+///
+/// # Arguments
+/// - `bytes` - The bytes to format
+///
+/// # Returns
+/// The bytes as uppercase hex pairs separated by spaces
+pub fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<String>>().join(" ")
+}
+
+/// Formats bytes as a canonical hex and ASCII dump
+///
+/// Each line holds 16 bytes: the offset of the first byte, the bytes in hex split in two groups of 8, and the bytes
+/// as ASCII with non-printable bytes shown as `.`.
+///
+/// # Arguments
+/// - `bytes` - The bytes to dump
+///
+/// # Returns
+/// The dump, one line per 16 bytes followed by a line with the total length
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for (index, line) in bytes.chunks(16).enumerate() {
+        let hex = (0..16)
+            .map(|i| match line.get(i) {
+                Some(byte) => format!("{:02x}", byte),
+                None => "  ".to_string(),
+            })
+            .collect::<Vec<String>>();
+        let ascii = line
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect::<String>();
+
+        dump.push_str(&format!("{:08x}  {}  {}  |{}|\n", index * 16, hex[..8].join(" "), hex[8..].join(" "), ascii));
+    }
+
+    dump.push_str(&format!("{:08x}\n", bytes.len()));
+    dump
+}
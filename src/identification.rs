@@ -0,0 +1,222 @@
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::CardType;
+use crate::card::{
+    select_tachograph_ef, CardIdentification, Generation, CARD_IDENTIFICATION_LENGTH, DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH,
+    TACHOGRAPH_IDENTIFICATION_EF,
+};
+use crate::error::CardReaderError;
+use crate::nation::{nation_alpha, nation_name};
+use crate::parse::{decode_codepage_string, Reader};
+use crate::transport::CardTransport;
+
+/// Length of a `CompanyCardHolderIdentification`
+pub const COMPANY_CARD_HOLDER_IDENTIFICATION_LENGTH: usize = 74;
+/// Length of a `ControlCardHolderIdentification`
+pub const CONTROL_CARD_HOLDER_IDENTIFICATION_LENGTH: usize = 146;
+/// Length of a `WorkshopCardHolderIdentification`
+pub const WORKSHOP_CARD_HOLDER_IDENTIFICATION_LENGTH: usize = 146;
+
+/// Identification of a tachograph card and its holder, in the layout of the type of the card
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Identification {
+    Driver(CardIdentification),
+    Company(CompanyIdentification),
+    Control(ControlIdentification),
+    Workshop(WorkshopIdentification),
+}
+
+impl Identification {
+    /// Parses the contents of the identification EF in the layout of a card type
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF, i.e. the card identification (65 bytes) followed by the card holder
+    ///   identification of the card type
+    /// - `card_type` - The type of the card
+    /// - `generation` - The generation of the application the EF was read from, which determines the name encodings
+    ///
+    /// # Returns
+    /// The parsed identification, or `UnsupportedCardType` for unknown card types
+    pub fn new(data: &[u8], card_type: CardType, generation: Generation) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        let card_identification = reader.take(CARD_IDENTIFICATION_LENGTH as usize)?;
+        let holder = reader.remaining();
+
+        if card_type == CardType::Driver {
+            return Ok(Identification::Driver(CardIdentification::new(card_identification, holder, generation)?));
+        }
+
+        let mut reader = Reader::new(card_identification);
+        let issuing_member_state = reader.take_u8()?;
+        let card_number = String::from_utf8(reader.take(16)?.to_vec())?.trim_end_matches(['\0', ' ']).to_string();
+
+        match card_type {
+            CardType::Company => {
+                let mut reader = Reader::new(holder);
+                Ok(Identification::Company(CompanyIdentification {
+                    issuing_member_state,
+                    card_number,
+                    company_name: decode_codepage_string(reader.take(36)?, generation),
+                    company_address: decode_codepage_string(reader.take(36)?, generation),
+                    preferred_language: String::from_utf8(reader.take(2)?.to_vec())?,
+                }))
+            }
+            CardType::Control => {
+                let holder = OrganisationHolder::new(holder, generation)?;
+                Ok(Identification::Control(ControlIdentification {
+                    issuing_member_state,
+                    card_number,
+                    control_body_name: holder.name,
+                    control_body_address: holder.address,
+                    holder_last_name: holder.last_name,
+                    holder_first_name: holder.first_name,
+                    preferred_language: holder.preferred_language,
+                }))
+            }
+            CardType::Workshop => {
+                let holder = OrganisationHolder::new(holder, generation)?;
+                Ok(Identification::Workshop(WorkshopIdentification {
+                    issuing_member_state,
+                    card_number,
+                    workshop_name: holder.name,
+                    workshop_address: holder.address,
+                    holder_last_name: holder.last_name,
+                    holder_first_name: holder.first_name,
+                    preferred_language: holder.preferred_language,
+                }))
+            }
+            CardType::Driver | CardType::Unknown(_) => Err(CardReaderError::UnsupportedCardType(card_type)),
+        }
+    }
+
+    /// Returns the `NationNumeric` code of the member state that issued the card
+    pub fn issuing_member_state(&self) -> u8 {
+        match self {
+            Identification::Driver(identification) => identification.issuing_member_state,
+            Identification::Company(identification) => identification.issuing_member_state,
+            Identification::Control(identification) => identification.issuing_member_state,
+            Identification::Workshop(identification) => identification.issuing_member_state,
+        }
+    }
+
+    /// Returns the 16 character card number
+    pub fn card_number(&self) -> &str {
+        match self {
+            Identification::Driver(identification) => &identification.card_number.raw,
+            Identification::Company(identification) => &identification.card_number,
+            Identification::Control(identification) => &identification.card_number,
+            Identification::Workshop(identification) => &identification.card_number,
+        }
+    }
+
+    /// Returns the name of the member state that issued the card
+    pub fn issuing_member_state_name(&self) -> Option<&'static str> {
+        nation_name(self.issuing_member_state())
+    }
+
+    /// Returns the distinguishing sign of the member state that issued the card
+    pub fn issuing_member_state_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.issuing_member_state())
+    }
+}
+
+/// Identification of a company card and the company holding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompanyIdentification {
+    /// `NationNumeric` code of the member state that issued the card
+    pub issuing_member_state: u8,
+    /// The owner identification followed by the consecutive, replacement and renewal indices
+    pub card_number: String,
+    pub company_name: String,
+    pub company_address: String,
+    pub preferred_language: String,
+}
+
+/// Identification of a control card, the control body and the controller holding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControlIdentification {
+    /// `NationNumeric` code of the member state that issued the card
+    pub issuing_member_state: u8,
+    /// The owner identification followed by the consecutive, replacement and renewal indices
+    pub card_number: String,
+    pub control_body_name: String,
+    pub control_body_address: String,
+    pub holder_last_name: String,
+    pub holder_first_name: String,
+    pub preferred_language: String,
+}
+
+/// Identification of a workshop card, the workshop and the technician holding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkshopIdentification {
+    /// `NationNumeric` code of the member state that issued the card
+    pub issuing_member_state: u8,
+    /// The owner identification followed by the consecutive, replacement and renewal indices
+    pub card_number: String,
+    pub workshop_name: String,
+    pub workshop_address: String,
+    pub holder_last_name: String,
+    pub holder_first_name: String,
+    pub preferred_language: String,
+}
+
+/// The fields shared by the holder identifications of control and workshop cards
+struct OrganisationHolder {
+    name: String,
+    address: String,
+    last_name: String,
+    first_name: String,
+    preferred_language: String,
+}
+
+impl OrganisationHolder {
+    /// Parses a `ControlCardHolderIdentification` or a `WorkshopCardHolderIdentification`
+    fn new(data: &[u8], generation: Generation) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        Ok(OrganisationHolder {
+            name: decode_codepage_string(reader.take(36)?, generation),
+            address: decode_codepage_string(reader.take(36)?, generation),
+            last_name: decode_codepage_string(reader.take(36)?, generation),
+            first_name: decode_codepage_string(reader.take(36)?, generation),
+            preferred_language: String::from_utf8(reader.take(2)?.to_vec())?,
+        })
+    }
+}
+
+/// Returns the length of the card holder identification of a card type
+///
+/// # Arguments
+/// - `card_type` - The type of the card
+///
+/// # Returns
+/// The length in bytes, or `None` for unknown card types
+pub fn card_holder_identification_length(card_type: CardType) -> Option<usize> {
+    match card_type {
+        CardType::Driver => Some(DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH as usize),
+        CardType::Company => Some(COMPANY_CARD_HOLDER_IDENTIFICATION_LENGTH),
+        CardType::Control => Some(CONTROL_CARD_HOLDER_IDENTIFICATION_LENGTH),
+        CardType::Workshop => Some(WORKSHOP_CARD_HOLDER_IDENTIFICATION_LENGTH),
+        CardType::Unknown(_) => None,
+    }
+}
+
+/// Reads the identification of a card of any type from the tachograph application of the card
+///
+/// Unlike [`read_card_identification`](crate::card::read_card_identification), which assumes the driver card layout,
+/// the EF is parsed in the layout of the given card type, e.g. from
+/// [`read_card_type`](crate::application::read_card_type).
+///
+/// # Arguments
+/// - `card` - The smart card to read the identification from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `card_type` - The type of the card
+///
+/// # Returns
+/// The identification, or `UnsupportedCardType` for unknown card types
+pub fn read_identification(card: &impl CardTransport, generation: Option<Generation>, card_type: CardType) -> Result<Identification, CardReaderError> {
+    let holder_length = card_holder_identification_length(card_type).ok_or(CardReaderError::UnsupportedCardType(card_type))?;
+    let generation = select_tachograph_ef(card, generation, TACHOGRAPH_IDENTIFICATION_EF)?;
+    let data = read_ef_fully(card, (CARD_IDENTIFICATION_LENGTH as usize + holder_length) as u16, None)?;
+    Identification::new(&data, card_type, generation)
+}
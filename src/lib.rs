@@ -0,0 +1,58 @@
+//! Library for reading data from a tachograph driver card with a regular smart card reader.
+//!
+//! See [this](https://dtc.jrc.ec.europa.eu/iot_doc/EU%202016-799-EN.pdf) document for the specifications.
+//!
+//! # Selection state
+//!
+//! The card remembers the last selected DF and EF, and READ BINARY reads the selected EF. The `read_*` functions
+//! select everything they need themselves, so they work from any selection state: readers of the tachograph EFs
+//! select the application and their EF, and readers of the MF EFs such as [`chip::read_icc`] select the MF first.
+//! They leave the EF they read selected. The `transmit_*` functions of [`apdu`], and [`apdu::read_ef_fully`], work on
+//! whatever is selected, and [`card::select_mf`], [`card::select_tachograph_df`] and [`card::select_tachograph_ef`]
+//! set the selection.
+
+pub mod activity;
+pub mod apdu;
+pub mod application;
+#[cfg(feature = "tokio")]
+pub mod async_card;
+pub mod atr;
+pub mod auth;
+pub mod border_crossings;
+pub mod card;
+pub mod card_download;
+pub mod chip;
+pub mod compliance;
+pub mod control_activity;
+pub mod current_usage;
+pub mod ddd;
+#[cfg(feature = "pcsc")]
+pub mod diagnostics;
+pub mod driver_card;
+pub mod driving_licence;
+pub mod encoding;
+pub mod error;
+pub mod events;
+pub mod faults;
+pub mod fcp;
+pub mod gnss;
+pub mod hex;
+pub mod identification;
+pub mod load_unload;
+pub mod nation;
+pub mod parse;
+pub mod places;
+pub mod quirks;
+pub mod raw;
+#[cfg(feature = "pcsc")]
+pub mod reader;
+pub mod registry;
+pub mod ring;
+pub mod secure_messaging;
+pub mod sensor_installation;
+pub mod specific_conditions;
+pub mod tlv;
+pub mod transport;
+pub mod vehicle_units;
+pub mod vehicles;
+pub mod verify;
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, STATUS_WORD_FILE_NOT_FOUND};
+use crate::application::{read_application_identification_v2, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF};
+use crate::error::CardReaderError;
+use crate::gnss::{GnssPlaceAuthRecord, GNSS_PLACE_AUTH_RECORD_LENGTH};
+use crate::parse::{check_ring_pointer, parse_odometer, parse_time_real, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single `CardLoadUnloadRecord`
+pub const LOAD_UNLOAD_RECORD_LENGTH: usize = 20;
+/// Maximum number of load/unload records a card stores, used if the card has no Gen2v2 application identification
+pub const NO_OF_LOAD_UNLOAD_RECORDS: usize = 1624;
+
+/// Type of a load/unload operation, i.e. the `OperationType` of the record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LoadType {
+    Load,
+    Unload,
+    /// The vehicle was loaded and unloaded at the same time
+    SimultaneousLoadUnload,
+    Unknown(u8),
+}
+
+impl From<u8> for LoadType {
+    fn from(code: u8) -> Self {
+        match code {
+            1 => LoadType::Load,
+            2 => LoadType::Unload,
+            3 => LoadType::SimultaneousLoadUnload,
+            _ => LoadType::Unknown(code),
+        }
+    }
+}
+
+/// A load or unload operation of the vehicle, recorded by Gen2v2 cards
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadUnloadOperation {
+    pub time_stamp: DateTime<Utc>,
+    pub load_type: LoadType,
+    /// GNSS position of the vehicle at the operation
+    pub gnss_place: GnssPlaceAuthRecord,
+    /// Odometer of the vehicle at the operation, in kilometres
+    pub odometer: u32,
+}
+
+impl LoadUnloadOperation {
+    /// Parses a single `CardLoadUnloadRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (20 bytes)
+    ///
+    /// # Returns
+    /// The parsed operation, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (time_stamp, remaining) = take_array(record)?;
+        let ([operation_type], remaining) = take_array(remaining)?;
+        let (gnss_place, remaining) = take_n(GNSS_PLACE_AUTH_RECORD_LENGTH, remaining)?;
+        let (odometer, _) = take_array(remaining)?;
+
+        let Some(time_stamp) = parse_time_real(&time_stamp) else {
+            return Ok(None);
+        };
+
+        Ok(Some(LoadUnloadOperation {
+            time_stamp,
+            load_type: LoadType::from(operation_type),
+            gnss_place: GnssPlaceAuthRecord::new(gnss_place)?,
+            odometer: parse_odometer(&odometer),
+        }))
+    }
+}
+
+/// Parses the contents of the load/unload operations EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+///
+/// # Returns
+/// The operations in chronological order, skipping empty slots
+pub fn parse_load_unload_operations(data: &[u8]) -> Result<Vec<LoadUnloadOperation>, CardReaderError> {
+    let (newest, records) = take_array(data)?;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let records = records.chunks_exact(LOAD_UNLOAD_RECORD_LENGTH).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut operations = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(operation) = LoadUnloadOperation::new(records[(newest + 1 + i) % records.len()])? {
+            operations.push(operation);
+        }
+    }
+    Ok(operations)
+}
+
+/// Reads the load/unload operations from the Gen2 tachograph application of the card
+///
+/// The number of records comes from the Gen2v2 application identification of the card.
+///
+/// # Arguments
+/// - `card` - The smart card to read the operations from
+///
+/// # Returns
+/// The operations in chronological order, empty for Gen2v1 cards that don't have the EF
+pub fn read_load_unload_operations(card: &impl CardTransport) -> Result<Vec<LoadUnloadOperation>, CardReaderError> {
+    let record_count = read_application_identification_v2(card)?.map_or(NO_OF_LOAD_UNLOAD_RECORDS, |app_id| app_id.load_unload_records as usize);
+    match select_tachograph_ef(card, Some(Generation::Gen2), TACHOGRAPH_LOAD_UNLOAD_OPERATIONS_EF) {
+        Ok(_) => {}
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    }
+    let data = read_ef_fully(card, record_ef_length(2, record_count, LOAD_UNLOAD_RECORD_LENGTH)?, None)?;
+    parse_load_unload_operations(&data)
+}
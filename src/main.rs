@@ -1,169 +1,598 @@
-use pcsc::{Context, Error, Protocols, Scope, ShareMode};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc};
+use clap::{Parser, ValueEnum};
+use driver_card_reader::activity::read_driver_activity;
+use driver_card_reader::apdu::read_ef;
+use driver_card_reader::application::{read_card_type, CardType};
+use driver_card_reader::atr::{card_atr, parse_atr};
+use driver_card_reader::border_crossings::read_border_crossings;
+use driver_card_reader::card::{read_card_identification, select_tachograph_df, Generation};
+use driver_card_reader::card_download::read_last_download;
+use driver_card_reader::control_activity::read_control_activity;
+use driver_card_reader::current_usage::read_current_usage;
+use driver_card_reader::ddd::parse_ddd;
+use driver_card_reader::diagnostics::{diagnose, CheckStatus, DiagnosticsReport};
+use driver_card_reader::driver_card::read_full_card;
+use driver_card_reader::driving_licence::read_driving_licence_info;
+use driver_card_reader::encoding::{write_encoded, DumpEncoding};
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::events::read_events;
+use driver_card_reader::faults::read_faults;
+use driver_card_reader::hex::format_hex;
+use driver_card_reader::identification::read_identification;
+use driver_card_reader::load_unload::read_load_unload_operations;
+use driver_card_reader::places::{read_places, read_places_authentication};
+use driver_card_reader::quirks::{QuirksTransport, ReaderQuirks};
+use driver_card_reader::raw::{read_raw, RecordingTransport};
+use driver_card_reader::reader::{connect_with_retry, find_reader, wait_for_card, DEFAULT_CONNECT_RETRIES};
+use driver_card_reader::registry::{dump_all_efs, probe_efs, read_registered_ef, EfProbe, EfRegistry};
+use driver_card_reader::sensor_installation::read_sensor_installations;
+use driver_card_reader::specific_conditions::read_specific_conditions;
+use driver_card_reader::transport::{reconnect, retry_after_reset, with_transaction, CardProtocol, CardTransport};
+use driver_card_reader::vehicle_units::read_vehicle_units_used;
+use driver_card_reader::vehicles::read_vehicles_used;
+use pcsc::{Context, Error, Scope};
+use serde::Serialize;
+
+/// Reads data from a tachograph driver card with a regular smart card reader
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Name or index of the reader to use, defaults to the first connected reader
+    #[arg(long)]
+    reader: Option<String>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Generation of the tachograph application to read
+    #[arg(long = "gen", value_enum, default_value_t = GenerationArg::Auto)]
+    generation: GenerationArg,
+    /// Read a single EF instead of the whole card
+    #[arg(long, value_enum)]
+    ef: Option<Ef>,
+    /// With `--ef`, print the bytes of the EF alongside the parsed data
+    #[arg(long)]
+    raw: bool,
+    /// Only print the events, faults and activity from this day onwards
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    since: Option<NaiveDate>,
+    /// Print a hex dump of the raw contents of an EF, given as a hex file identifier (e.g. `0501`) or as the name of
+    /// an EF in the registry (e.g. `vehicles_used`)
+    #[arg(long, value_name = "EF")]
+    dump_raw: Option<String>,
+    /// With --dump-raw, the encoding to write the contents of the EF in
+    #[arg(long, value_enum, default_value_t = EncodingArg::Hexdump)]
+    encoding: EncodingArg,
+    /// With --dump-raw, write the contents of the EF to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+    /// Write the contents of every EF in the registry to `<name>.bin` files and a `manifest.json` in a directory
+    #[arg(long, value_name = "DIR")]
+    dump_all: Option<PathBuf>,
+    /// List the EFs of the registry that the card can select, with their lengths
+    #[arg(long)]
+    list_efs: bool,
+    /// Load the EF registry from a TOML file instead of using the embedded one
+    #[arg(long, value_name = "PATH")]
+    ef_registry: Option<PathBuf>,
+    /// Parse a saved `.ddd` card download instead of reading a card
+    #[arg(long, value_name = "PATH")]
+    ddd_file: Option<PathBuf>,
+    /// Wait for a card to be inserted instead of failing if the reader is empty
+    #[arg(long)]
+    wait: bool,
+    /// How many times to retry connecting when the reader is in use by another application
+    #[arg(long, default_value_t = DEFAULT_CONNECT_RETRIES)]
+    connect_retries: u32,
+    /// Transmission protocol to force, defaults to any protocol the reader negotiates
+    #[arg(long, value_enum)]
+    protocol: Option<ProtocolArg>,
+    /// Retry connecting in exclusive mode if connecting in shared mode fails
+    #[arg(long)]
+    exclusive_fallback: bool,
+    /// Check step by step that the reader and the card work, to tell reader problems from card problems
+    #[arg(long)]
+    diagnose: bool,
+    /// Print diagnostic information about the card, such as its ATR
+    #[arg(long)]
+    verbose: bool,
+    /// Same as `--format json`
+    #[arg(long, hide = true)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GenerationArg {
+    #[value(name = "1")]
+    Gen1,
+    #[value(name = "2")]
+    Gen2,
+    Auto,
+}
+
+impl GenerationArg {
+    fn generation(self) -> Option<Generation> {
+        match self {
+            GenerationArg::Gen1 => Some(Generation::Gen1),
+            GenerationArg::Gen2 => Some(Generation::Gen2),
+            GenerationArg::Auto => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EncodingArg {
+    Hexdump,
+    Hex,
+    Base64,
+    Raw,
+}
+
+impl EncodingArg {
+    fn encoding(self) -> DumpEncoding {
+        match self {
+            EncodingArg::Hexdump => DumpEncoding::Hexdump,
+            EncodingArg::Hex => DumpEncoding::Hex,
+            EncodingArg::Base64 => DumpEncoding::Base64,
+            EncodingArg::Raw => DumpEncoding::Raw,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ProtocolArg {
+    T0,
+    T1,
+}
+
+impl ProtocolArg {
+    fn protocol(self) -> CardProtocol {
+        match self {
+            ProtocolArg::T0 => CardProtocol::T0,
+            ProtocolArg::T1 => CardProtocol::T1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Ef {
+    Identification,
+    LastDownload,
+    DrivingLicenceInfo,
+    Events,
+    Faults,
+    DriverActivity,
+    VehiclesUsed,
+    VehicleUnitsUsed,
+    Places,
+    PlacesAuthentication,
+    BorderCrossings,
+    LoadUnloadOperations,
+    CurrentUsage,
+    ControlActivity,
+    SpecificConditions,
+    SensorInstallation,
+}
 
 fn main() -> Result<(), pcsc::Error> {
+    env_logger::init();
+    let args = Args::parse();
+    let format = if args.json { Format::Json } else { args.format };
+    let generation = args.generation.generation();
+    let protocol = args.protocol.map(ProtocolArg::protocol);
+
+    let registry = match &args.ef_registry {
+        Some(path) => match EfRegistry::load(path) {
+            Ok(registry) => registry,
+            Err(e) => fail(format, "Failed to load EF registry", &e),
+        },
+        None => EfRegistry::default(),
+    };
+
+    if let Some(path) = &args.ddd_file {
+        let mut driver_card = match std::fs::read(path).map_err(CardReaderError::from).and_then(|bytes| parse_ddd(&bytes)) {
+            Ok(driver_card) => driver_card,
+            Err(e) => fail(format, &format!("Failed to parse {}", path.display()), &e),
+        };
+        if let Some(since) = args.since {
+            driver_card.retain_since(since);
+        }
+
+        match format {
+            Format::Text => println!("{}", driver_card),
+            Format::Json => print_output(&driver_card, format),
+        }
+        return Ok(());
+    }
+
     let context = match Context::establish(Scope::User) {
         Ok(ctx) => ctx,
-        Err(e) => {
-            eprintln!("Failed to establish context: {}", e);
-            std::process::exit(1);
-        }
+        Err(e) => fail(format, "Failed to establish context", &e.into()),
     };
 
-    let mut readers_buf = [0; 2048];
-    let mut readers = match context.list_readers(&mut readers_buf) {
-        Ok(readers) => readers,
-        Err(e) => {
-            eprintln!("Failed to list readers: {}", e);
+    if args.diagnose {
+        let report = diagnose(&context);
+        match format {
+            Format::Text => print_diagnostics(&report),
+            Format::Json => print_output(&report, format),
+        }
+        if !report.passed() {
             std::process::exit(1);
         }
-    };
+        return Ok(());
+    }
 
-    let reader = match readers.next() {
-        Some(reader) => reader,
+    let reader = match &args.reader {
+        Some(selector) => match find_reader(&context, selector) {
+            Ok(reader) => reader,
+            Err(e) => fail(format, "Failed to find reader", &e),
+        },
         None => {
-            eprintln!("No readers are connected");
-            std::process::exit(1);
+            let mut readers_buf = [0; 2048];
+            let mut readers = match context.list_readers(&mut readers_buf) {
+                Ok(readers) => readers,
+                Err(e) => fail(format, "Failed to list readers", &e.into()),
+            };
+
+            match readers.next() {
+                Some(reader) => reader.to_owned(),
+                None => fail(format, "Failed to list readers", &CardReaderError::NoReaders),
+            }
         }
     };
 
-    println!("Using reader {:?}", reader);
+    if format == Format::Text {
+        println!("Using reader {:?}", reader);
+    }
 
-    let card = match context.connect(reader, ShareMode::Shared, Protocols::ANY) {
-        Ok(card) => card,
-        Err(Error::NoSmartcard) => {
-            eprintln!("A smartcard is not present in the reader");
-            std::process::exit(1);
+    let card = if args.wait {
+        match wait_for_card(&context, &reader, None, protocol) {
+            Ok(card) => card,
+            Err(e) => fail(format, "Failed to wait for card", &e),
         }
-        Err(e) => {
-            eprintln!("Failed to connect to card: {}", e);
-            std::process::exit(1);
+    } else {
+        match connect_with_retry(&context, &reader, args.connect_retries, args.exclusive_fallback, protocol) {
+            Ok((card, share_mode)) => {
+                if format == Format::Text {
+                    println!("Connected in {:?} share mode", share_mode);
+                }
+                card
+            }
+            Err(CardReaderError::Pcsc(Error::NoSmartcard)) if format == Format::Text => {
+                eprintln!("A smartcard is not present in the reader");
+                std::process::exit(1);
+            }
+            Err(e) => fail(format, "Failed to connect to card", &e),
         }
     };
 
-    let read_card_identification_apdu = b"\x00\xB0\x00\x00\x41";
-
-    // Select the tachograph application on the smart card
-    transmit_select_df_apdu(&card, TACHOGRAPH_DF)?;
-    // Select the identification file under the tachograph application
-    transmit_select_ef_under_df_apdu(&card, TACHOGRAPH_IDENTIFICATION_EF)?;
-
-    // Read the card identification file
-    let read_card_identification_response = transmit_apdu(&card, read_card_identification_apdu)?;
-    let (_, remaining) = take_n(1, &read_card_identification_response).unwrap();
-    let (card_number, _) = take_n(16, remaining).unwrap();
-    let driver_card_number = String::from_utf8(card_number.to_vec()).unwrap();
-    println!("Driver card number: {}", driver_card_number);
-
-    let read_card_holder_identification_apdu = b"\x00\xB0\x00\x41\x4E";
-    let card_holder_identification_response = transmit_apdu(&card, read_card_holder_identification_apdu)?;
-    let (card_holder_name, card_holder_remaining) = take_n(72, &card_holder_identification_response).unwrap();
-    let (last_name, remaining) = take_n(36, &card_holder_name).unwrap();
-    let (first_name, _) = take_n(36, &remaining).unwrap();
-    let (birth_date, remaining) = take_n(4, card_holder_remaining).unwrap();
-    let (preferred_language, _) = take_n(2, remaining).unwrap();
-    let first_name = String::from_utf8(first_name.to_vec()).unwrap();
-    let last_name = String::from_utf8(last_name.to_vec()).unwrap();
-    let first_name = first_name.trim();
-    let last_name = last_name.trim();
-    let preferred_language = String::from_utf8(preferred_language.to_vec()).unwrap();
-    println!("First name: {first_name}");
-    println!("Last name: {last_name}");
-
-    // Birth date is stored as BCDString where first two bytes denote the year and the last two bytes denote the month and day respectively
-    let year = bcdstring_from_byte_string(&format!("{:08b}{:08b}", birth_date[0], birth_date[1]));
-    let month = bcdstring_from_byte_string(&format!("{:08b}", birth_date[2]));
-    let day = bcdstring_from_byte_string(&format!("{:08b}", birth_date[3]));
-    println!("Year: {year}");
-    println!("month: {month}");
-    println!("day: {day}");
-    println!("Preferred language: {}", preferred_language);
+    if args.verbose {
+        print_atr(&card_atr(&card));
+        match CardTransport::protocol(&card) {
+            Some(protocol) => eprintln!("Protocol: {:?}", protocol),
+            None => eprintln!("Protocol: not available"),
+        }
+    }
+
+    let card_cell = RefCell::new(card);
+    let quirks = ReaderQuirks::for_reader(&reader.to_string_lossy());
+    if args.verbose && quirks != ReaderQuirks::default() {
+        eprintln!("Reader quirks: {:?}", quirks);
+    }
+    let card = QuirksTransport::new(&card_cell, quirks);
 
-    Ok(())
-}
+    if let Some(ef) = &args.dump_raw {
+        let data = match parse_ef_id(ef) {
+            Some(ef_id) => select_tachograph_df(&card, generation).and_then(|_| read_ef(&card, ef_id)),
+            None => read_registered_ef(&card, &registry, ef, generation),
+        };
+        match data {
+            Ok(data) => {
+                if let Err(e) = write_dump(&data, args.encoding.encoding(), args.output.as_deref()) {
+                    fail(format, "Failed to write EF", &CardReaderError::Io(e));
+                }
+            }
+            Err(e) => fail(format, "Failed to read EF", &e),
+        }
+        return Ok(());
+    }
 
-fn transmit_select_df_apdu(card: &pcsc::Card, df: &[u8]) -> Result<Vec<u8>, Error> {
-    let mut select_df_apdu = SELECT_DF_COMMAND.to_vec();
-    select_df_apdu.extend_from_slice(df);
-    transmit_apdu(card, &select_df_apdu)
-}
+    if let Some(dir) = &args.dump_all {
+        match dump_all_efs(&card, &registry, generation, dir) {
+            Ok(manifest) if format == Format::Text => {
+                let dumped = manifest.iter().filter(|entry| entry.error.is_none()).count();
+                println!("Dumped {} of {} EFs to {}", dumped, manifest.len(), dir.display());
+            }
+            Ok(manifest) => print_output(&manifest, format),
+            Err(e) => fail(format, "Failed to dump EFs", &e),
+        }
+        return Ok(());
+    }
 
-fn transmit_select_ef_under_df_apdu(card: &pcsc::Card, ef: &[u8]) -> Result<Vec<u8>, Error> {
-    let mut select_ef_apdu = SELECT_EF_UNDER_DF_COMMAND.to_vec();
-    select_ef_apdu.extend_from_slice(ef);
-    transmit_apdu(card, &select_ef_apdu)
-}
+    if args.list_efs {
+        match probe_efs(&card, &registry, generation) {
+            Ok(probes) if format == Format::Text => print_ef_table(&probes),
+            Ok(probes) => print_output(&probes, format),
+            Err(e) => fail(format, "Failed to list EFs", &e),
+        }
+        return Ok(());
+    }
 
-fn transmit_read_binary_apdu(card: &pcsc::Card, offset: u8, length: u8) -> Result<Vec<u8>, Error> {
-    let mut read_binary_apdu = READ_BINARY_COMMAND.to_vec();
-    read_binary_apdu.push(offset);
-    read_binary_apdu.push(length);
-    transmit_apdu(card, &read_binary_apdu)
-}
+    if let Some(ef) = args.ef {
+        if let Err(e) = print_ef(&card, ef, generation, format, args.raw, args.since.map(|since| since.and_time(NaiveTime::MIN).and_utc())) {
+            fail(format, "Failed to read EF", &e);
+        }
+        return Ok(());
+    }
 
-const SELECT_DF_COMMAND: &[u8] = b"\x00\xA4\x04\x0C\x06";
-const SELECT_EF_UNDER_DF_COMMAND: &[u8] = b"\x00\xA4\x02\x0C\x02";
-const READ_BINARY_COMMAND: &[u8] = b"\x00\xB0";
+    if format == Format::Json {
+        let read = || with_transaction(&mut card_cell.borrow_mut(), |card| read_full_card(&QuirksTransport::new(card, quirks), generation));
+        // The transaction holds a plain card that can't reconnect itself, so reconnect here before restarting the read
+        let result = match read() {
+            Err(CardReaderError::CardReset) => reconnect(&mut card_cell.borrow_mut()).and_then(|_| read()),
+            result => result,
+        };
+        let mut driver_card = match result {
+            Ok(driver_card) => driver_card,
+            Err(e) => fail(format, "Failed to read card", &e),
+        };
+        if let Some(since) = args.since {
+            driver_card.retain_since(since);
+        }
 
-const TACHOGRAPH_DF: &[u8] = b"\xFF\x54\x41\x43\x48\x4F";
-const TACHOGRAPH_GEN2_DF: &[u8] = b"\xFF\x53\x4D\x52\x44\x54";
+        print_output(&driver_card, format);
+        return Ok(());
+    }
 
-const TACHOGRAPH_IDENTIFICATION_EF: &[u8] = b"\x05\x20";
+    // Other card types have a different identification layout, so print it as is instead of the driver fields
+    match read_card_type(&card, generation) {
+        Ok(CardType::Driver) => {}
+        Ok(card_type) => {
+            match read_identification(&card, generation, card_type) {
+                Ok(identification) => print_output(&identification, format),
+                Err(e) => fail(format, "Failed to read card identification", &e),
+            }
+            return Ok(());
+        }
+        Err(e) => fail(format, "Failed to read card type", &e),
+    }
 
-const CARD_IDENTIFICATION_LENGTH: &[u8] = b"\x41";
-const DRIVER_CARD_HOLDER_IDENTIFICATION_LENGTH: &[u8] = b"\x4E";
+    let card_identification = match read_card_identification(&card, generation) {
+        Ok(card_identification) => card_identification,
+        Err(e) => fail(format, "Failed to read card identification", &e),
+    };
 
+    println!("Driver card number: {}", card_identification.card_number.raw);
+    println!("First name: {}", card_identification.first_name);
+    println!("Last name: {}", card_identification.last_name);
+    match card_identification.birth_date {
+        Some(birth_date) => {
+            println!("Year: {}", birth_date.year());
+            println!("month: {:02}", birth_date.month());
+            println!("day: {:02}", birth_date.day());
+        }
+        None => println!("Birth date: not set"),
+    }
+    println!("Preferred language: {}", card_identification.preferred_language);
 
-/// Converts a byte string to a BCD string
+    Ok(())
+}
+
+/// Parses a file identifier given as 4 hex digits
+///
+/// # Arguments
+/// - `value` - The file identifier, e.g. `0501`
 ///
-/// VERY EXPERIMENTAL, see [Binacy-Coded Decimal](https://en.wikipedia.org/wiki/Binary-coded_decimal)
+/// # Returns
+/// The file identifier, or `None` if the value is not 4 hex digits
+fn parse_ef_id(value: &str) -> Option<[u8; 2]> {
+    let value = value.trim_start_matches("0x");
+    if value.len() != 4 {
+        return None;
+    }
+    u16::from_str_radix(value, 16).ok().map(u16::to_be_bytes)
+}
+
+/// Prints the ATR of the card and its historical bytes to stderr
 ///
 /// # Arguments
-/// - `data` - A string of bytes
-fn bcdstring_from_byte_string(data: &str) -> String {
-    data.chars()
-    .collect::<Vec<char>>()
-    .chunks(4)
-    .map(|chunk|
-        u8::from_str_radix(&chunk.iter().collect::<String>(), 2)
-        .unwrap()
-        .to_string())
-    .collect::<Vec<String>>()
-    .join("")
-}
-
-/// Takes the first `n` bytes from a byte slice
+/// - `atr` - The ATR of the card
+fn print_atr(atr: &[u8]) {
+    if atr.is_empty() {
+        eprintln!("ATR: not available");
+        return;
+    }
+
+    eprintln!("ATR: {}", format_hex(atr));
+    match parse_atr(atr) {
+        Ok(atr) => eprintln!("Historical bytes: {}", format_hex(&atr.historical_bytes)),
+        Err(e) => eprintln!("Failed to parse ATR: {}", e),
+    }
+}
+
+/// Prints the results of probing for EFs as a table
 ///
 /// # Arguments
-/// - `n` - The number of bytes to take
-/// - `data` - The byte slice to take the bytes from
+/// - `probes` - The probe results
+fn print_ef_table(probes: &[EfProbe]) {
+    println!("{:<4}  {:<32}  {:<7}  Length", "ID", "Name", "Present");
+    for probe in probes {
+        let length = probe.length.map(|length| length.to_string()).unwrap_or_else(|| "-".to_string());
+        let present = if probe.present { "yes" } else { "no" };
+        println!("{:02X}{:02X}  {:<32}  {:<7}  {}", probe.id[0], probe.id[1], probe.name, present, length);
+    }
+}
+
+/// Prints the results of the self-check as a table
 ///
-/// # Returns
-/// A tuple containing the first `n` bytes and the remaining bytes
-fn take_n(n: usize, data: &[u8]) -> Result<(&[u8], &[u8]), std::io::Error> {
-    if data.len() < n {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            "Data is too short",
-        ));
+/// # Arguments
+/// - `report` - The results of the self-check
+fn print_diagnostics(report: &DiagnosticsReport) {
+    if let Some(reader) = &report.reader {
+        println!("Reader: {}", reader);
+    }
+    for step in &report.steps {
+        let status = match step.status {
+            CheckStatus::Passed => "ok",
+            CheckStatus::Failed => "FAILED",
+            CheckStatus::Skipped => "skipped",
+        };
+        match &step.error {
+            Some(error) => println!("{:<24}  {:<7}  {:>10.1?}  {}", format!("{:?}", step.check), status, step.duration, error),
+            None => println!("{:<24}  {:<7}  {:>10.1?}", format!("{:?}", step.check), status, step.duration),
+        }
+    }
+}
+
+/// Reads a single EF from the card and prints it
+///
+/// # Arguments
+/// - `card` - The smart card to read the EF from
+/// - `ef` - The EF to read
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `format` - The output format
+/// - `raw` - Whether to print the bytes of the EF alongside the parsed data
+/// - `since` - Start of the first day of events, faults and activity to print, or `None` to print all of them
+fn print_ef(card: &impl CardTransport, ef: Ef, generation: Option<Generation>, format: Format, raw: bool, since: Option<DateTime<Utc>>) -> Result<(), CardReaderError> {
+    match ef {
+        Ef::Identification => print_read(card, format, raw, |card| read_card_identification(card, generation))?,
+        Ef::LastDownload => print_read(card, format, raw, |card| read_last_download(card, generation))?,
+        Ef::DrivingLicenceInfo => print_read(card, format, raw, |card| read_driving_licence_info(card, generation))?,
+        Ef::Events => print_read(card, format, raw, |card| Ok(retain_since(read_events(card, generation)?, since, |event| event.begin)))?,
+        Ef::Faults => print_read(card, format, raw, |card| Ok(retain_since(read_faults(card, generation)?, since, |fault| fault.begin)))?,
+        Ef::DriverActivity => print_read(card, format, raw, |card| Ok(retain_since(read_driver_activity(card, generation)?, since, |day| day.date.and_time(NaiveTime::MIN).and_utc())))?,
+        Ef::VehiclesUsed => print_read(card, format, raw, |card| read_vehicles_used(card, generation))?,
+        Ef::VehicleUnitsUsed => print_read(card, format, raw, |card| read_vehicle_units_used(card))?,
+        Ef::Places => print_read(card, format, raw, |card| read_places(card, generation))?,
+        Ef::PlacesAuthentication => print_read(card, format, raw, |card| read_places_authentication(card))?,
+        Ef::BorderCrossings => print_read(card, format, raw, |card| read_border_crossings(card))?,
+        Ef::LoadUnloadOperations => print_read(card, format, raw, |card| read_load_unload_operations(card))?,
+        Ef::CurrentUsage => print_read(card, format, raw, |card| read_current_usage(card, generation))?,
+        Ef::ControlActivity => print_read(card, format, raw, |card| read_control_activity(card, generation))?,
+        Ef::SpecificConditions => print_read(card, format, raw, |card| read_specific_conditions(card, generation))?,
+        Ef::SensorInstallation => print_read(card, format, raw, |card| read_sensor_installations(card))?,
     }
-    Ok(data.split_at(n))
+    Ok(())
 }
 
-/// Transmits an APDU to a smart card
+/// Drops the records that began before a point in time
 ///
 /// # Arguments
-/// - `card` - The smart card to transmit the APDU to
-/// - `apdu` - The APDU to transmit
+/// - `records` - The records
+/// - `since` - The earliest beginning of the records to keep, or `None` to keep all records
+/// - `begin` - Returns the beginning of a record
 ///
 /// # Returns
-/// The response from the smart card
-fn transmit_apdu(card: &pcsc::Card, apdu: &[u8]) -> Result<Vec<u8>, Error> {
-    let mut rapdu_buf = [0; 1024];
-    match card.transmit(apdu, &mut rapdu_buf) {
-        Ok(response) => Ok(response.to_vec()),
-        Err(e) => {
-            eprintln!("Failed to transmit APDU: {}", e);
-            Err(e)
+/// The records that began at or after `since`
+fn retain_since<T>(mut records: Vec<T>, since: Option<DateTime<Utc>>, begin: impl Fn(&T) -> DateTime<Utc>) -> Vec<T> {
+    if let Some(since) = since {
+        records.retain(|record| begin(record) >= since);
+    }
+    records
+}
+
+/// Reads a value from the card and prints it, optionally together with the bytes it was parsed from
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `format` - The output format
+/// - `raw` - Whether to print the bytes of the EF alongside the parsed data
+/// - `read` - The reader function
+fn print_read<C: CardTransport, T: Serialize + Debug>(
+    card: &C,
+    format: Format,
+    raw: bool,
+    read: impl Fn(&RecordingTransport<&C>) -> Result<T, CardReaderError>,
+) -> Result<(), CardReaderError> {
+    let value = retry_after_reset(card, |card| read_raw(card, &read))?;
+    if raw {
+        print_output(&value, format);
+    } else {
+        print_output(&value.parsed, format);
+    }
+    Ok(())
+}
+
+/// Writes the contents of an EF to a file, or to stdout if no path is given
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `encoding` - The encoding to write the contents in
+/// - `path` - The file to write to
+fn write_dump(data: &[u8], encoding: DumpEncoding, path: Option<&Path>) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    write_encoded(data, encoding, &mut writer)?;
+    writer.flush()
+}
+
+/// Error envelope printed in the JSON output format when the tool fails
+#[derive(Serialize)]
+struct ErrorEnvelope {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail {
+    kind: &'static str,
+    message: String,
+    sw1: Option<u8>,
+    sw2: Option<u8>,
+}
+
+/// Reports an error in the given output format and exits with a non-zero status
+///
+/// In the text format the error is printed to stderr, in the JSON format an error envelope is printed to stdout.
+///
+/// # Arguments
+/// - `format` - The output format
+/// - `context` - What the tool was doing when the error occurred
+/// - `error` - The error
+fn fail(format: Format, context: &str, error: &CardReaderError) -> ! {
+    let message = format!("{}: {}", context, error);
+    match format {
+        Format::Text => eprintln!("{}", message),
+        Format::Json => {
+            let status_word = error.status_word();
+            let envelope = ErrorEnvelope {
+                error: ErrorDetail { kind: error.kind(), message, sw1: status_word.map(|(sw1, _)| sw1), sw2: status_word.map(|(_, sw2)| sw2) },
+            };
+            match serde_json::to_string_pretty(&envelope) {
+                Ok(output) => println!("{}", output),
+                Err(e) => eprintln!("Failed to serialize error: {}", e),
+            }
         }
     }
+    std::process::exit(1);
+}
+
+/// Prints a value in the given output format
+///
+/// # Arguments
+/// - `value` - The value to print
+/// - `format` - The output format
+fn print_output<T: Serialize + Debug>(value: &T, format: Format) {
+    match format {
+        Format::Text => println!("{:#?}", value),
+        Format::Json => match serde_json::to_string_pretty(value) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("Failed to serialize output: {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
 }
@@ -0,0 +1,130 @@
+/// Countries and regions by their `NationNumeric` code, with their `NationAlpha` code and name
+const NATIONS: &[(u8, &str, &str)] = &[
+    (0x00, "", "No information available"),
+    (0x01, "A", "Austria"),
+    (0x02, "AL", "Albania"),
+    (0x03, "AND", "Andorra"),
+    (0x04, "ARM", "Armenia"),
+    (0x05, "AZ", "Azerbaijan"),
+    (0x06, "B", "Belgium"),
+    (0x07, "BG", "Bulgaria"),
+    (0x08, "BIH", "Bosnia and Herzegovina"),
+    (0x09, "BY", "Belarus"),
+    (0x0A, "CH", "Switzerland"),
+    (0x0B, "CY", "Cyprus"),
+    (0x0C, "CZ", "Czech Republic"),
+    (0x0D, "D", "Germany"),
+    (0x0E, "DK", "Denmark"),
+    (0x0F, "E", "Spain"),
+    (0x10, "EST", "Estonia"),
+    (0x11, "F", "France"),
+    (0x12, "FIN", "Finland"),
+    (0x13, "FL", "Liechtenstein"),
+    (0x14, "FR", "Faroe Islands"),
+    (0x15, "UK", "United Kingdom"),
+    (0x16, "GE", "Georgia"),
+    (0x17, "GR", "Greece"),
+    (0x18, "H", "Hungary"),
+    (0x19, "HR", "Croatia"),
+    (0x1A, "I", "Italy"),
+    (0x1B, "IRL", "Ireland"),
+    (0x1C, "IS", "Iceland"),
+    (0x1D, "KZ", "Kazakhstan"),
+    (0x1E, "L", "Luxembourg"),
+    (0x1F, "LT", "Lithuania"),
+    (0x20, "LV", "Latvia"),
+    (0x21, "M", "Malta"),
+    (0x22, "MC", "Monaco"),
+    (0x23, "MD", "Republic of Moldova"),
+    (0x24, "MK", "North Macedonia"),
+    (0x25, "N", "Norway"),
+    (0x26, "NL", "The Netherlands"),
+    (0x27, "P", "Portugal"),
+    (0x28, "PL", "Poland"),
+    (0x29, "RO", "Romania"),
+    (0x2A, "RSM", "San Marino"),
+    (0x2B, "RUS", "Russian Federation"),
+    (0x2C, "S", "Sweden"),
+    (0x2D, "SK", "Slovakia"),
+    (0x2E, "SLO", "Slovenia"),
+    (0x2F, "TM", "Turkmenistan"),
+    (0x30, "TR", "Turkey"),
+    (0x31, "UA", "Ukraine"),
+    (0x32, "V", "Vatican City"),
+    (0x33, "YU", "Yugoslavia"),
+    (0x34, "MNE", "Montenegro"),
+    (0x35, "SRB", "Serbia"),
+    (0x36, "UZ", "Uzbekistan"),
+    (0x37, "TJ", "Tajikistan"),
+    (0xFD, "EC", "European Community"),
+    (0xFE, "EUR", "Rest of Europe"),
+    (0xFF, "WLD", "Rest of the world"),
+];
+
+/// `NationNumeric` code of Spain, the only nation whose places record a region
+pub const NATION_SPAIN: u8 = 0x0F;
+
+/// Autonomous communities of Spain by their `RegionNumeric` code, with their `RegionAlpha` code and name
+const SPANISH_REGIONS: &[(u8, &str, &str)] = &[
+    (0x00, "", "No information available"),
+    (0x01, "AN", "Andalucía"),
+    (0x02, "AR", "Aragón"),
+    (0x03, "AST", "Asturias"),
+    (0x04, "C", "Cantabria"),
+    (0x05, "CAT", "Cataluña"),
+    (0x06, "CL", "Castilla-León"),
+    (0x07, "CM", "Castilla-La-Mancha"),
+    (0x08, "CV", "Valencia"),
+    (0x09, "EXT", "Extremadura"),
+    (0x0A, "G", "Galicia"),
+    (0x0B, "IB", "Baleares"),
+    (0x0C, "IC", "Canarias"),
+    (0x0D, "LR", "La Rioja"),
+    (0x0E, "M", "Madrid"),
+    (0x0F, "MU", "Murcia"),
+    (0x10, "NA", "Navarra"),
+    (0x11, "PV", "País Vasco"),
+];
+
+/// Returns the name of the country or region denoted by a `NationNumeric` code
+///
+/// # Arguments
+/// - `code` - The `NationNumeric` code
+///
+/// # Returns
+/// The name of the country or region, or `None` if the code is reserved for future use
+pub fn nation_name(code: u8) -> Option<&'static str> {
+    NATIONS.iter().find(|(c, _, _)| *c == code).map(|(_, _, name)| *name)
+}
+
+/// Returns the distinguishing sign of the country or region denoted by a `NationNumeric` code, e.g. `D` for Germany
+///
+/// # Arguments
+/// - `code` - The `NationNumeric` code
+///
+/// # Returns
+/// The `NationAlpha` code, or `None` if the code is reserved for future use or denotes no information
+pub fn nation_alpha(code: u8) -> Option<&'static str> {
+    NATIONS
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, alpha, _)| *alpha)
+        .filter(|alpha| !alpha.is_empty())
+}
+
+/// Returns the name of the region denoted by a `RegionNumeric` code within a nation
+///
+/// Regions are only defined for Spain.
+///
+/// # Arguments
+/// - `nation` - The `NationNumeric` code of the country
+/// - `region` - The `RegionNumeric` code
+///
+/// # Returns
+/// The name of the region, or `None` if the nation has no regions or the code is reserved for future use
+pub fn region_name(nation: u8, region: u8) -> Option<&'static str> {
+    if nation != NATION_SPAIN {
+        return None;
+    }
+    SPANISH_REGIONS.iter().find(|(c, _, _)| *c == region).map(|(_, _, name)| *name)
+}
@@ -0,0 +1,317 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::card::Generation;
+use crate::error::CardReaderError;
+
+/// Code page byte of a Gen2 string field encoded in UTF-8
+pub const CODE_PAGE_UTF8: u8 = 0xFE;
+/// Code page byte of a Gen2 string field encoded in UTF-16BE
+pub const CODE_PAGE_UTF16_BE: u8 = 0xFD;
+
+/// Decodes a BCD encoded value, two decimal digits per byte
+///
+/// See [Binary-Coded Decimal](https://en.wikipedia.org/wiki/Binary-coded_decimal)
+///
+/// # Arguments
+/// - `bytes` - The BCD encoded bytes
+///
+/// # Returns
+/// The decimal digits, or `InvalidBcd` if a nibble is not a decimal digit
+pub fn decode_bcd(bytes: &[u8]) -> Result<String, CardReaderError> {
+    let mut digits = String::with_capacity(bytes.len() * 2);
+    for nibble in bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0F]) {
+        if nibble > 9 {
+            return Err(CardReaderError::InvalidBcd);
+        }
+        digits.push((b'0' + nibble) as char);
+    }
+    Ok(digits)
+}
+
+/// Encodes decimal digits as BCD, two digits per byte, i.e. the inverse of [`decode_bcd`]
+///
+/// # Arguments
+/// - `value` - The decimal digits, padded with a leading zero if there is an odd number of them
+///
+/// # Returns
+/// The BCD encoded bytes, or `InvalidBcd` if the value contains other characters than decimal digits
+pub fn encode_bcd(value: &str) -> Result<Vec<u8>, CardReaderError> {
+    let mut nibbles = value
+        .chars()
+        .map(|c| c.to_digit(10).map(|digit| digit as u8).ok_or(CardReaderError::InvalidBcd))
+        .collect::<Result<Vec<u8>, CardReaderError>>()?;
+    if nibbles.len() % 2 == 1 {
+        nibbles.insert(0, 0);
+    }
+    Ok(nibbles.chunks_exact(2).map(|pair| (pair[0] << 4) | pair[1]).collect())
+}
+
+/// Decodes a BCD encoded value that is filled with `0xFF` when not set
+///
+/// # Arguments
+/// - `bytes` - The BCD encoded bytes
+///
+/// # Returns
+/// The decimal digits, or `None` if the value is not set
+pub fn decode_optional_bcd(bytes: &[u8]) -> Result<Option<String>, CardReaderError> {
+    if bytes.iter().all(|&byte| byte == 0xFF) {
+        return Ok(None);
+    }
+    decode_bcd(bytes).map(Some)
+}
+
+/// Parses a `Datef` value, i.e. a BCD encoded date with a 2-byte year followed by a 1-byte month and a 1-byte day
+///
+/// # Arguments
+/// - `bytes` - The 4 bytes of the `Datef` value
+///
+/// # Returns
+/// The date, `None` if the date is not set (all `0x00` or all `0xFF` bytes), or `InvalidDate` if it is not a valid calendar date
+pub fn parse_datef(bytes: &[u8; 4]) -> Result<Option<NaiveDate>, CardReaderError> {
+    if bytes.iter().all(|&byte| byte == 0x00) {
+        return Ok(None);
+    }
+    let Some(digits) = decode_optional_bcd(bytes)? else {
+        return Ok(None);
+    };
+
+    let year = digits[..4].parse().map_err(|_| CardReaderError::InvalidBcd)?;
+    let month = digits[4..6].parse().map_err(|_| CardReaderError::InvalidBcd)?;
+    let day = digits[6..].parse().map_err(|_| CardReaderError::InvalidBcd)?;
+    NaiveDate::from_ymd_opt(year, month, day)
+        .map(Some)
+        .ok_or(CardReaderError::InvalidDate)
+}
+
+/// Parses a BCD encoded number, two decimal digits per byte
+///
+/// # Arguments
+/// - `bytes` - The BCD encoded bytes
+///
+/// # Returns
+/// The decoded number, or `InvalidBcd` if a nibble is not a decimal digit
+pub fn parse_bcd_number(bytes: &[u8]) -> Result<u32, CardReaderError> {
+    bytes.iter().try_fold(0, |number, byte| {
+        let (high, low) = (byte >> 4, byte & 0x0F);
+        if high > 9 || low > 9 {
+            return Err(CardReaderError::InvalidBcd);
+        }
+        Ok(number * 100 + high as u32 * 10 + low as u32)
+    })
+}
+
+/// Parses an `OdometerShort` value, i.e. a 3-byte big-endian distance in kilometres
+///
+/// # Arguments
+/// - `bytes` - The 3 bytes of the odometer value
+///
+/// # Returns
+/// The odometer value in kilometres
+pub fn parse_odometer(bytes: &[u8; 3]) -> u32 {
+    u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]])
+}
+
+/// Parses a `TimeReal` value, i.e. 4 big-endian bytes of seconds since 1970-01-01 00:00 UTC
+///
+/// # Arguments
+/// - `bytes` - The 4 bytes of the `TimeReal` value
+///
+/// # Returns
+/// The timestamp, or `None` if the value is not set (all-zero bytes)
+pub fn parse_time_real(bytes: &[u8; 4]) -> Option<DateTime<Utc>> {
+    match u32::from_be_bytes(*bytes) {
+        0 => None,
+        seconds => DateTime::from_timestamp(seconds as i64, 0),
+    }
+}
+
+/// Decodes a string that is prefixed with a one byte code page identifier
+///
+/// The code page identifies the ISO/IEC 8859 part used for the characters (e.g. 1 = ISO 8859-1, 2 = ISO 8859-2),
+/// with 80 and 85 denoting KOI8-R and KOI8-U. Unknown code pages are decoded as ISO 8859-1.
+/// Trailing `0x00` and `0x20` padding is trimmed.
+///
+/// # Arguments
+/// - `bytes` - The code page byte followed by the encoded characters
+///
+/// # Returns
+/// The decoded string
+pub fn decode_ia5_codepage(bytes: &[u8]) -> String {
+    let Some((&code_page, data)) = bytes.split_first() else {
+        return String::new();
+    };
+
+    let end = data.iter().rposition(|&b| b != 0x00 && b != 0x20).map_or(0, |i| i + 1);
+    let data = &data[..end];
+
+    let encoding = match code_page {
+        2 => encoding_rs::ISO_8859_2,
+        3 => encoding_rs::ISO_8859_3,
+        4 => encoding_rs::ISO_8859_4,
+        5 => encoding_rs::ISO_8859_5,
+        6 => encoding_rs::ISO_8859_6,
+        7 => encoding_rs::ISO_8859_7,
+        8 => encoding_rs::ISO_8859_8,
+        9 => encoding_rs::WINDOWS_1254,
+        10 => encoding_rs::ISO_8859_10,
+        13 => encoding_rs::ISO_8859_13,
+        14 => encoding_rs::ISO_8859_14,
+        15 => encoding_rs::ISO_8859_15,
+        16 => encoding_rs::ISO_8859_16,
+        80 => encoding_rs::KOI8_R,
+        85 => encoding_rs::KOI8_U,
+        // ISO 8859-1 maps every byte directly to the Unicode code point of the same value
+        _ => return data.iter().map(|&b| b as char).collect(),
+    };
+
+    let (decoded, _) = encoding.decode_without_bom_handling(data);
+    decoded.into_owned()
+}
+
+/// Decodes a string that is prefixed with a one byte code page identifier, according to the generation of its EF
+///
+/// Gen2 name and authority fields may also be encoded in UTF-8 ([`CODE_PAGE_UTF8`]) or UTF-16BE
+/// ([`CODE_PAGE_UTF16_BE`]). Other code pages, and every Gen1 field, are decoded with [`decode_ia5_codepage`].
+/// A trailing odd byte of a UTF-16BE field is ignored, and trailing `0x00` and `0x20` padding is trimmed.
+///
+/// # Arguments
+/// - `bytes` - The code page byte followed by the encoded characters
+/// - `generation` - The generation of the application the field was read from
+///
+/// # Returns
+/// The decoded string
+pub fn decode_codepage_string(bytes: &[u8], generation: Generation) -> String {
+    let encoding = match (generation, bytes.first()) {
+        (Generation::Gen2, Some(&CODE_PAGE_UTF8)) => encoding_rs::UTF_8,
+        (Generation::Gen2, Some(&CODE_PAGE_UTF16_BE)) => encoding_rs::UTF_16BE,
+        _ => return decode_ia5_codepage(bytes),
+    };
+
+    // A 36 byte field leaves an odd number of bytes for UTF-16BE, the last one of which is only padding
+    let mut encoded = &bytes[1..];
+    if encoding == encoding_rs::UTF_16BE {
+        encoded = &encoded[..encoded.len() & !1];
+    }
+    let (decoded, _) = encoding.decode_without_bom_handling(encoded);
+    decoded.trim_end_matches(['\0', ' ']).to_string()
+}
+
+/// Checks that a ring buffer pointer points within the buffer before the buffer is walked from it
+///
+/// # Arguments
+/// - `pointer` - The pointer, either a record index or a byte offset
+/// - `len` - The number of records, or bytes, in the buffer
+///
+/// # Returns
+/// `CorruptRingBuffer` if the buffer is not empty and the pointer is beyond its end
+pub fn check_ring_pointer(pointer: usize, len: usize) -> Result<(), CardReaderError> {
+    if len > 0 && pointer >= len {
+        return Err(CardReaderError::CorruptRingBuffer { pointer, file_len: len });
+    }
+    Ok(())
+}
+
+/// Parses a `VehicleRegistrationIdentification`, i.e. a nation code followed by a code page prefixed registration number
+///
+/// # Arguments
+/// - `data` - The vehicle registration identification (15 bytes)
+///
+/// # Returns
+/// A tuple containing the `NationNumeric` code of the registering nation and the registration number
+pub fn parse_vehicle_registration(data: &[u8]) -> Result<(u8, String), CardReaderError> {
+    let (nation, remaining) = take_n(1, data)?;
+    let (number, _) = take_n(14, remaining)?;
+    Ok((nation[0], decode_ia5_codepage(number)))
+}
+
+/// Takes the first `n` bytes from a byte slice
+///
+/// # Arguments
+/// - `n` - The number of bytes to take
+/// - `data` - The byte slice to take the bytes from
+///
+/// # Returns
+/// A tuple containing the first `n` bytes and the remaining bytes
+pub fn take_n(n: usize, data: &[u8]) -> Result<(&[u8], &[u8]), CardReaderError> {
+    if data.len() < n {
+        return Err(CardReaderError::Truncated);
+    }
+    Ok(data.split_at(n))
+}
+
+/// Takes the first `N` bytes from a byte slice as a fixed-size array
+///
+/// # Arguments
+/// - `data` - The byte slice to take the bytes from
+///
+/// # Returns
+/// A tuple containing the first `N` bytes and the remaining bytes
+pub fn take_array<const N: usize>(data: &[u8]) -> Result<([u8; N], &[u8]), CardReaderError> {
+    let (taken, remaining) = take_n(N, data)?;
+    let mut array = [0; N];
+    array.copy_from_slice(taken);
+    Ok((array, remaining))
+}
+
+/// A cursor reading fixed-length fields from the front of a byte slice
+///
+/// Every read returns `Truncated` instead of panicking when fewer bytes remain than the field needs.
+#[derive(Debug, Clone, Copy)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a cursor at the start of a byte slice
+    ///
+    /// # Arguments
+    /// - `data` - The bytes to read
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    /// Takes the next `n` bytes
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], CardReaderError> {
+        let (taken, remaining) = take_n(n, self.data)?;
+        self.data = remaining;
+        Ok(taken)
+    }
+
+    /// Takes the next `N` bytes as a fixed-size array
+    pub fn take_array<const N: usize>(&mut self) -> Result<[u8; N], CardReaderError> {
+        let (taken, remaining) = take_array(self.data)?;
+        self.data = remaining;
+        Ok(taken)
+    }
+
+    /// Takes the next byte
+    pub fn take_u8(&mut self) -> Result<u8, CardReaderError> {
+        let [byte] = self.take_array()?;
+        Ok(byte)
+    }
+
+    /// Takes the next 2 bytes as a big-endian number
+    pub fn take_u16_be(&mut self) -> Result<u16, CardReaderError> {
+        Ok(u16::from_be_bytes(self.take_array()?))
+    }
+
+    /// Takes the next 4 bytes as a big-endian number
+    pub fn take_u32_be(&mut self) -> Result<u32, CardReaderError> {
+        Ok(u32::from_be_bytes(self.take_array()?))
+    }
+
+    /// Takes the next 4 bytes as a `TimeReal` value, `None` if the value is not set
+    pub fn take_time_real(&mut self) -> Result<Option<DateTime<Utc>>, CardReaderError> {
+        Ok(parse_time_real(&self.take_array()?))
+    }
+
+    /// Takes the next 4 bytes as a `Datef` value, `None` if the date is not set
+    pub fn take_datef(&mut self) -> Result<Option<NaiveDate>, CardReaderError> {
+        parse_datef(&self.take_array()?)
+    }
+
+    /// Returns the bytes that have not been read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, STATUS_WORD_FILE_NOT_FOUND};
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_PLACES_AUTHENTICATION_EF, TACHOGRAPH_PLACES_EF};
+use crate::error::CardReaderError;
+use crate::gnss::{GnssPlaceRecord, GNSS_PLACE_RECORD_LENGTH};
+use crate::nation::region_name;
+use crate::parse::{check_ring_pointer, parse_odometer, parse_time_real, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single Gen1 `PlaceRecord`
+pub const PLACE_RECORD_LENGTH: usize = 10;
+/// Length of a single Gen2 `PlaceRecord`, which additionally holds a GNSS place record
+pub const GEN2_PLACE_RECORD_LENGTH: usize = 21;
+/// Maximum number of place records stored on a Gen1 card, the card reports its own count in the application identification
+pub const NO_OF_CARD_PLACE_RECORDS: usize = 112;
+/// Length of a single Gen2v2 `PlaceAuthStatusRecord`
+pub const PLACE_AUTH_STATUS_RECORD_LENGTH: usize = 5;
+
+/// Type of a place entry, i.e. whether a daily work period began or ended and how the time was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EntryType {
+    /// Begin, related time is the card insertion time or the time of entry
+    Begin,
+    /// End, related time is the card withdrawal time or the time of entry
+    End,
+    /// Begin, related time was manually entered
+    BeginManual,
+    /// End, related time was manually entered
+    EndManual,
+    /// Begin, related time was assumed by the VU
+    BeginAssumed,
+    /// End, related time was assumed by the VU
+    EndAssumed,
+    Unknown(u8),
+}
+
+impl From<u8> for EntryType {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => EntryType::Begin,
+            1 => EntryType::End,
+            2 => EntryType::BeginManual,
+            3 => EntryType::EndManual,
+            4 => EntryType::BeginAssumed,
+            5 => EntryType::EndAssumed,
+            _ => EntryType::Unknown(code),
+        }
+    }
+}
+
+/// A place where a daily work period began or ended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlaceRecord {
+    pub entry_time: DateTime<Utc>,
+    pub entry_type: EntryType,
+    /// `NationNumeric` code of the country
+    pub country: u8,
+    /// `RegionNumeric` code of the region within the country
+    pub region: u8,
+    /// Name of the region, only set for places in Spain
+    pub region_name: Option<&'static str>,
+    /// Odometer of the vehicle at the time of the entry, in kilometres
+    pub odometer: u32,
+    /// GNSS position of the vehicle at the time of the entry, only recorded by Gen2 cards
+    pub entry_gnss_place: Option<GnssPlaceRecord>,
+}
+
+impl PlaceRecord {
+    /// Parses a single `PlaceRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (10 bytes, or 21 bytes for Gen2 records)
+    ///
+    /// # Returns
+    /// The parsed place, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (entry_time, remaining) = take_array(record)?;
+        let (entry_type, remaining) = take_n(1, remaining)?;
+        let (country, remaining) = take_n(1, remaining)?;
+        let (region, remaining) = take_n(1, remaining)?;
+        let (odometer, remaining) = take_array(remaining)?;
+
+        let Some(entry_time) = parse_time_real(&entry_time) else {
+            return Ok(None);
+        };
+
+        Ok(Some(PlaceRecord {
+            entry_time,
+            entry_type: EntryType::from(entry_type[0]),
+            country: country[0],
+            region: region[0],
+            region_name: region_name(country[0], region[0]),
+            odometer: parse_odometer(&odometer),
+            entry_gnss_place: match take_n(GNSS_PLACE_RECORD_LENGTH, remaining) {
+                Ok((gnss_place, _)) => Some(GnssPlaceRecord::new(gnss_place)?),
+                Err(_) => None,
+            },
+        }))
+    }
+}
+
+/// Parses the contents of the places EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `generation` - The generation of the application the EF was read from
+/// - `record_count` - The number of place records, from the application identification
+///
+/// # Returns
+/// The places in chronological order, skipping empty slots
+pub fn parse_places(data: &[u8], generation: Generation, record_count: usize) -> Result<Vec<PlaceRecord>, CardReaderError> {
+    // Gen1 stores the newest record pointer in a single byte, Gen2 in two bytes
+    let (newest, records, record_length) = match generation {
+        Generation::Gen1 => {
+            let (newest, records) = take_n(1, data)?;
+            (newest[0] as usize, records, PLACE_RECORD_LENGTH)
+        }
+        Generation::Gen2 => {
+            let (newest, records) = take_array(data)?;
+            (u16::from_be_bytes(newest) as usize, records, GEN2_PLACE_RECORD_LENGTH)
+        }
+    };
+
+    let records = records.chunks_exact(record_length).take(record_count).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut places = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(place) = PlaceRecord::new(records[(newest + 1 + i) % records.len()])? {
+            places.push(place);
+        }
+    }
+    Ok(places)
+}
+
+/// Reads the places from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the places from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The places stored on the card in chronological order
+pub fn read_places(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<PlaceRecord>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let record_count = app_id.place_records as usize;
+    let generation = select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_PLACES_EF)?;
+    let length = match generation {
+        Generation::Gen1 => record_ef_length(1, record_count, PLACE_RECORD_LENGTH)?,
+        Generation::Gen2 => record_ef_length(2, record_count, GEN2_PLACE_RECORD_LENGTH)?,
+    };
+    let data = read_ef_fully(card, length, None)?;
+    parse_places(&data, generation, record_count)
+}
+
+/// Authentication status of the GNSS position of a place, recorded by Gen2v2 cards
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlaceAuthStatus {
+    /// Entry time of the place the status applies to
+    pub entry_time: DateTime<Utc>,
+    /// Whether the GNSS position was authenticated by the VU
+    pub authenticated: bool,
+}
+
+impl PlaceAuthStatus {
+    /// Parses a single `PlaceAuthStatusRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (5 bytes)
+    ///
+    /// # Returns
+    /// The parsed authentication status, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (entry_time, remaining) = take_array(record)?;
+        let ([authentication_status], _) = take_array(remaining)?;
+
+        let Some(entry_time) = parse_time_real(&entry_time) else {
+            return Ok(None);
+        };
+
+        Ok(Some(PlaceAuthStatus {
+            entry_time,
+            authenticated: authentication_status == 1,
+        }))
+    }
+}
+
+/// Parses the contents of the places authentication EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+///
+/// # Returns
+/// The authentication statuses in chronological order, skipping empty slots
+pub fn parse_places_authentication(data: &[u8]) -> Result<Vec<PlaceAuthStatus>, CardReaderError> {
+    let (newest, records) = take_array(data)?;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let records = records.chunks_exact(PLACE_AUTH_STATUS_RECORD_LENGTH).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut statuses = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(status) = PlaceAuthStatus::new(records[(newest + 1 + i) % records.len()])? {
+            statuses.push(status);
+        }
+    }
+    Ok(statuses)
+}
+
+/// Reads the authentication statuses of the place GNSS positions from the Gen2 tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the authentication statuses from
+///
+/// # Returns
+/// The authentication statuses in chronological order, empty for cards older than Gen2v2 that don't have the EF
+pub fn read_places_authentication(card: &impl CardTransport) -> Result<Vec<PlaceAuthStatus>, CardReaderError> {
+    let app_id = read_application_identification(card, Some(Generation::Gen2))?;
+    match select_tachograph_ef(card, Some(Generation::Gen2), TACHOGRAPH_PLACES_AUTHENTICATION_EF) {
+        Ok(_) => {}
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    }
+    let data = read_ef_fully(card, record_ef_length(2, app_id.place_records as usize, PLACE_AUTH_STATUS_RECORD_LENGTH)?, None)?;
+    parse_places_authentication(&data)
+}
@@ -0,0 +1,117 @@
+use log::{debug, trace};
+
+use crate::error::CardReaderError;
+use crate::hex::format_hex;
+use crate::transport::{CardProtocol, CardTransport, ReaderLimits};
+
+/// GET DATA pseudo-APDU of PC/SC contactless readers, returning the UID of the card
+pub const GET_DATA_UID_COMMAND: &[u8] = b"\xFF\xCA\x00\x00\x00";
+
+/// Adjustments to the APDUs sent through a reader that doesn't handle the standard commands
+///
+/// The default quirks leave every APDU unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReaderQuirks {
+    /// P2 to send SELECT commands with instead of `0C`, e.g. `00` for readers that drop SELECT commands without
+    /// response data. An Le byte is added to the commands whose P2 is replaced.
+    pub select_p2: Option<u8>,
+    /// Whether to send [`GET_DATA_UID_COMMAND`] before each SELECT by DF name, for readers that lose track of the
+    /// contactless card between commands
+    pub get_data_before_select: bool,
+    /// Maximum number of bytes to request with a single READ BINARY
+    pub max_read_length: Option<u8>,
+}
+
+/// Quirks of the contactless interface of ACS readers
+const ACS_CONTACTLESS_QUIRKS: ReaderQuirks = ReaderQuirks {
+    select_p2: Some(0x00),
+    get_data_before_select: true,
+    max_read_length: Some(0xF0),
+};
+
+/// Known readers by the substrings of their PC/SC names, with their quirks
+const KNOWN_QUIRKS: &[(&[&str], ReaderQuirks)] = &[
+    (&["ACR1252", "PICC"], ACS_CONTACTLESS_QUIRKS),
+    (&["ACR122"], ACS_CONTACTLESS_QUIRKS),
+];
+
+impl ReaderQuirks {
+    /// Looks up the quirks of a reader by its PC/SC name
+    ///
+    /// # Arguments
+    /// - `name` - The name of the reader, e.g. `ACS ACR1252 Dual Reader [ACR1252 Dual Reader PICC] 00 00`
+    ///
+    /// # Returns
+    /// The quirks of the reader, or the default quirks for unknown readers
+    pub fn for_reader(name: &str) -> Self {
+        KNOWN_QUIRKS
+            .iter()
+            .find(|(patterns, _)| patterns.iter().all(|pattern| name.contains(pattern)))
+            .map(|(_, quirks)| *quirks)
+            .unwrap_or_default()
+    }
+
+    /// Adjusts an APDU for the reader
+    ///
+    /// # Arguments
+    /// - `apdu` - The APDU to send
+    ///
+    /// # Returns
+    /// The APDU to send through the reader instead
+    pub fn adjust(&self, apdu: &[u8]) -> Vec<u8> {
+        match (apdu, self.select_p2, self.max_read_length) {
+            ([0x00, 0xA4, p1, 0x0C, lc, data @ ..], Some(p2), _) if data.len() == *lc as usize => [&[0x00, 0xA4, *p1, p2, *lc], data, &[0x00]].concat(),
+            ([0x00, 0xB0, p1, p2, le], _, Some(max)) if *le == 0 || *le > max => vec![0x00, 0xB0, *p1, *p2, max],
+            _ => apdu.to_vec(),
+        }
+    }
+}
+
+/// A transport that adjusts the APDUs for the quirks of the reader
+pub struct QuirksTransport<T: CardTransport> {
+    card: T,
+    quirks: ReaderQuirks,
+}
+
+impl<T: CardTransport> QuirksTransport<T> {
+    /// Wraps a transport so that its APDUs are adjusted for the quirks of the reader
+    ///
+    /// # Arguments
+    /// - `card` - The transport to the card
+    /// - `quirks` - The quirks of the reader, e.g. from [`ReaderQuirks::for_reader`]
+    pub fn new(card: T, quirks: ReaderQuirks) -> Self {
+        QuirksTransport { card, quirks }
+    }
+}
+
+impl<T: CardTransport> CardTransport for QuirksTransport<T> {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        if self.quirks.get_data_before_select && apdu.starts_with(&[0x00, 0xA4, 0x04]) {
+            let response = self.card.transmit(GET_DATA_UID_COMMAND)?;
+            trace!("GET DATA before SELECT: {}", format_hex(&response));
+        }
+
+        let adjusted = self.quirks.adjust(apdu);
+        if adjusted != apdu {
+            debug!("Adjusted {} to {} for the reader", format_hex(apdu), format_hex(&adjusted));
+        }
+        self.card.transmit(&adjusted)
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        self.card.reconnect()
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        self.card.protocol()
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        // Readers limited to short reads can't be trusted with extended length APDUs either
+        let limits = self.card.limits()?;
+        match self.quirks.max_read_length {
+            Some(max) => Some(ReaderLimits { max_recv: limits.max_recv.min(max as usize + 2), supports_extended: false, ..limits }),
+            None => Some(limits),
+        }
+    }
+}
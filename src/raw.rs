@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+
+use serde::{Serialize, Serializer};
+
+use crate::apdu::{split_status_word, READ_BINARY_COMMAND};
+use crate::error::CardReaderError;
+use crate::hex::format_hex;
+use crate::transport::{CardProtocol, CardTransport, ReaderLimits};
+
+/// A parsed value together with the exact bytes it was parsed from
+///
+/// Keeping the bytes lets them be parsed again later, e.g. with an improved parser, without reading the card again.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Raw<T> {
+    pub parsed: T,
+    /// Contents of the EF the value was parsed from, serialized as space separated hex
+    #[serde(serialize_with = "serialize_hex")]
+    pub bytes: Vec<u8>,
+}
+
+/// A transport that records the data returned by READ BINARY from the most recently selected EF
+pub struct RecordingTransport<T: CardTransport> {
+    card: T,
+    bytes: RefCell<Vec<u8>>,
+}
+
+impl<T: CardTransport> RecordingTransport<T> {
+    /// Starts recording the reads made through a transport
+    ///
+    /// # Arguments
+    /// - `card` - The transport to the card
+    pub fn new(card: T) -> Self {
+        RecordingTransport { card, bytes: RefCell::new(Vec::new()) }
+    }
+
+    /// Returns the bytes read from the most recently selected EF
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes.into_inner()
+    }
+}
+
+impl<T: CardTransport> CardTransport for RecordingTransport<T> {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let response = self.card.transmit(apdu)?;
+        match apdu {
+            // A new EF is selected, so the bytes read so far belong to another EF
+            [0x00, 0xA4, 0x02, ..] => self.bytes.borrow_mut().clear(),
+            _ if apdu.starts_with(READ_BINARY_COMMAND) => {
+                if let Ok((data, _)) = split_status_word(&response) {
+                    self.bytes.borrow_mut().extend_from_slice(data);
+                }
+            }
+            _ => {}
+        }
+        Ok(response)
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        self.card.reconnect()
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        self.card.protocol()
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        self.card.limits()
+    }
+}
+
+/// Runs a reader function and captures the bytes of the EF it parsed
+///
+/// The bytes are the data read from the last EF the function selected, so readers that first read the application
+/// identification to size their EF capture only their own EF.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `read` - The reader function, e.g. `|card| read_events(card, None)`
+///
+/// # Returns
+/// The parsed value and the bytes it was parsed from
+pub fn read_raw<C: CardTransport, T>(card: &C, read: impl FnOnce(&RecordingTransport<&C>) -> Result<T, CardReaderError>) -> Result<Raw<T>, CardReaderError> {
+    let recorder = RecordingTransport::new(card);
+    let parsed = read(&recorder)?;
+    Ok(Raw { parsed, bytes: recorder.into_bytes() })
+}
+
+/// Serializes bytes as space separated hex
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_hex(bytes))
+}
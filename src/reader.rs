@@ -0,0 +1,325 @@
+use std::ffi::{CStr, CString};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use pcsc::{Card, Context, Protocols, ReaderState, Scope, ShareMode, State};
+
+use crate::driver_card::{read_full_card, DriverCard};
+use crate::error::CardReaderError;
+use crate::quirks::{QuirksTransport, ReaderQuirks};
+use crate::transport::CardProtocol;
+
+/// Delay before the first connection retry, doubled for each further retry
+pub const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// Number of connection retries made by default
+pub const DEFAULT_CONNECT_RETRIES: u32 = 3;
+/// How long the state of a reader must stay unchanged before [`monitor`] reports a card inserted or removed
+pub const MONITOR_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Configuration for connecting to a card, built by chaining setters on the default configuration
+///
+/// The defaults are a user scope context, a shared connection with any protocol to the first connected reader and
+/// [`DEFAULT_CONNECT_RETRIES`] retries.
+#[derive(Debug, Clone)]
+pub struct ReaderConfig {
+    scope: Scope,
+    share_mode: ShareMode,
+    protocols: Protocols,
+    reader: Option<String>,
+    retries: u32,
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        ReaderConfig {
+            scope: Scope::User,
+            share_mode: ShareMode::Shared,
+            protocols: Protocols::ANY,
+            reader: None,
+            retries: DEFAULT_CONNECT_RETRIES,
+        }
+    }
+}
+
+impl ReaderConfig {
+    /// Returns the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the scope of the PC/SC context
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Sets the share mode of the connection
+    pub fn share_mode(mut self, share_mode: ShareMode) -> Self {
+        self.share_mode = share_mode;
+        self
+    }
+
+    /// Sets the protocols the reader may negotiate with the card
+    pub fn protocols(mut self, protocols: Protocols) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Sets the name or the index of the reader to connect to, as accepted by [`find_reader`]
+    pub fn reader(mut self, selector: impl Into<String>) -> Self {
+        self.reader = Some(selector.into());
+        self
+    }
+
+    /// Sets how many times to retry connecting when the reader is in use by another application
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Establishes a PC/SC context and connects to the card in the configured reader
+    ///
+    /// # Returns
+    /// The connected card, which keeps the context alive
+    pub fn connect(&self) -> Result<Card, CardReaderError> {
+        let context = Context::establish(self.scope)?;
+        let reader = match &self.reader {
+            Some(selector) => find_reader(&context, selector)?,
+            None => context.list_readers_owned()?.into_iter().next().ok_or(CardReaderError::NoReaders)?,
+        };
+        Ok(connect_in_share_mode(&context, &reader, self.share_mode, self.protocols, self.retries)?)
+    }
+}
+
+/// Lists the names of the readers connected to the system
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+///
+/// # Returns
+/// The names of the connected readers
+pub fn list_reader_names(context: &Context) -> Result<Vec<String>, CardReaderError> {
+    let readers = context.list_readers_owned()?;
+    Ok(readers.iter().map(|reader| reader.to_string_lossy().into_owned()).collect())
+}
+
+/// Finds a connected reader by its name or by its index in the list of connected readers
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `selector` - The name or the index of the reader
+///
+/// # Returns
+/// The name of the reader, or `ReaderNotFound` listing the available readers
+pub fn find_reader(context: &Context, selector: &str) -> Result<CString, CardReaderError> {
+    let readers = context.list_readers_owned()?;
+
+    let by_index = selector.parse::<usize>().ok().and_then(|index| readers.get(index));
+    let by_name = readers.iter().find(|reader| reader.to_string_lossy() == selector);
+    match by_index.or(by_name) {
+        Some(reader) => Ok(reader.clone()),
+        None => Err(CardReaderError::ReaderNotFound {
+            selector: selector.to_string(),
+            available: readers.iter().map(|reader| reader.to_string_lossy().into_owned()).collect(),
+        }),
+    }
+}
+
+/// Connects to the card in the reader, retrying while the reader is in use by another application
+///
+/// Connecting is retried with a growing delay when the reader reports a sharing violation or no card. If all the
+/// attempts in shared mode fail, the same attempts can optionally be made in exclusive mode.
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `reader` - The name of the reader to connect to
+/// - `retries` - How many times to retry connecting in each share mode
+/// - `exclusive_fallback` - Whether to retry in exclusive mode after the shared mode attempts fail
+/// - `protocol` - The protocol to force, or `None` to let the reader negotiate any protocol
+///
+/// # Returns
+/// The connected card and the share mode the connection was made with
+pub fn connect_with_retry(context: &Context, reader: &CStr, retries: u32, exclusive_fallback: bool, protocol: Option<CardProtocol>) -> Result<(Card, ShareMode), CardReaderError> {
+    let protocols = protocol.map_or(Protocols::ANY, CardProtocol::protocols);
+    let share_modes: &[ShareMode] = if exclusive_fallback { &[ShareMode::Shared, ShareMode::Exclusive] } else { &[ShareMode::Shared] };
+    let mut last_error = pcsc::Error::NoSmartcard;
+
+    for &share_mode in share_modes {
+        match connect_in_share_mode(context, reader, share_mode, protocols, retries) {
+            Ok(card) => return Ok((card, share_mode)),
+            Err(e @ (pcsc::Error::SharingViolation | pcsc::Error::NoSmartcard)) => last_error = e,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(last_error.into())
+}
+
+/// Connects to the card in the reader in one share mode, retrying with a growing delay while the reader reports a
+/// sharing violation or no card
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `reader` - The name of the reader to connect to
+/// - `share_mode` - The share mode to connect in
+/// - `protocols` - The protocols the reader may negotiate
+/// - `retries` - How many times to retry connecting
+///
+/// # Returns
+/// The connected card, or the error of the last attempt
+fn connect_in_share_mode(context: &Context, reader: &CStr, share_mode: ShareMode, protocols: Protocols, retries: u32) -> Result<Card, pcsc::Error> {
+    let mut delay = CONNECT_RETRY_DELAY;
+    let mut attempt = 0;
+    loop {
+        match context.connect(reader, share_mode, protocols) {
+            Ok(card) => return Ok(card),
+            Err(e @ (pcsc::Error::SharingViolation | pcsc::Error::NoSmartcard)) if attempt < retries => {
+                warn!("Connecting in {:?} share mode failed: {}, retrying in {:?}", share_mode, e, delay);
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Blocks until a card is present in the reader and connects to it
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `reader` - The name of the reader to wait on
+/// - `timeout` - How long to wait for a card, or `None` to wait indefinitely
+/// - `protocol` - The protocol to force, or `None` to let the reader negotiate any protocol
+///
+/// # Returns
+/// The connected card, or `Timeout` if no card was inserted in time
+pub fn wait_for_card(context: &Context, reader: &CStr, timeout: Option<Duration>, protocol: Option<CardProtocol>) -> Result<Card, CardReaderError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut reader_states = [ReaderState::new(reader, State::UNAWARE)];
+
+    loop {
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        match context.get_status_change(remaining, &mut reader_states) {
+            Ok(()) => {}
+            Err(pcsc::Error::Timeout) => return Err(CardReaderError::Timeout),
+            Err(e) => return Err(e.into()),
+        }
+
+        if reader_states[0].event_state().contains(State::PRESENT) {
+            return Ok(context.connect(reader, ShareMode::Shared, protocol.map_or(Protocols::ANY, CardProtocol::protocols))?);
+        }
+        reader_states[0].sync_current_state();
+    }
+}
+
+/// Monitors a reader, reading every card inserted in it
+///
+/// Each inserted driver card is read with [`read_full_card`] and passed to `on_insert`, and `on_remove` is called when
+/// the card is removed. A card already present when monitoring starts counts as inserted. State changes are only
+/// reported once the state has stayed unchanged for [`MONITOR_DEBOUNCE`], so a card bouncing in the slot is not read
+/// several times. Cards that fail to read are logged and skipped.
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `reader` - The name of the reader to monitor
+/// - `on_insert` - Called with the data of each inserted card
+/// - `on_remove` - Called when a card that was reported inserted is removed
+///
+/// # Returns
+/// Only returns when monitoring fails, e.g. with `ReaderUnavailable` when the reader is unplugged
+pub fn monitor(context: &Context, reader: &CStr, mut on_insert: impl FnMut(DriverCard), mut on_remove: impl FnMut()) -> Result<(), CardReaderError> {
+    let mut reader_states = [ReaderState::new(reader, State::UNAWARE)];
+    let mut present = false;
+
+    loop {
+        context.get_status_change(None, &mut reader_states)?;
+        reader_states[0].sync_current_state();
+        check_reader_available(&reader_states[0])?;
+
+        // Wait for the state to settle, picking up any further changes made meanwhile
+        thread::sleep(MONITOR_DEBOUNCE);
+        match context.get_status_change(Duration::ZERO, &mut reader_states) {
+            Ok(()) => reader_states[0].sync_current_state(),
+            Err(pcsc::Error::Timeout) => {}
+            Err(e) => return Err(e.into()),
+        }
+        check_reader_available(&reader_states[0])?;
+
+        let card_present = reader_states[0].current_state().contains(State::PRESENT);
+        if card_present == present {
+            continue;
+        }
+        present = card_present;
+
+        if present {
+            match read_present_card(context, reader) {
+                Ok(driver_card) => on_insert(driver_card),
+                Err(e) => warn!("Failed to read the card inserted in {:?}: {}", reader, e),
+            }
+        } else {
+            on_remove();
+        }
+    }
+}
+
+/// Fails with `ReaderUnavailable` if the reader has been unplugged
+fn check_reader_available(reader_state: &ReaderState) -> Result<(), CardReaderError> {
+    if reader_state.current_state().intersects(State::UNKNOWN | State::UNAVAILABLE) {
+        return Err(pcsc::Error::ReaderUnavailable.into());
+    }
+    Ok(())
+}
+
+/// The name of a reader with the result of reading the card in it
+pub type ReaderResult = (String, Result<DriverCard, CardReaderError>);
+
+/// Reads the cards in all the readers that have a card present, each reader in its own thread
+///
+/// A card that fails to connect or to read doesn't stop the others from being read. Each card is read with the
+/// quirks of its reader, see [`ReaderQuirks::for_reader`].
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+///
+/// # Returns
+/// The name of each reader that had a card present with the result of reading its card, in the order of the readers
+pub fn read_all_present(context: &Context) -> Result<Vec<ReaderResult>, CardReaderError> {
+    let readers = context.list_readers_owned()?;
+    let mut reader_states = readers.iter().map(|reader| ReaderState::new(reader.as_c_str(), State::UNAWARE)).collect::<Vec<_>>();
+    context.get_status_change(Duration::ZERO, &mut reader_states)?;
+
+    let present = reader_states
+        .iter()
+        .filter(|state| state.event_state().contains(State::PRESENT))
+        .map(|state| state.name().to_owned())
+        .collect::<Vec<_>>();
+
+    let results = thread::scope(|scope| {
+        let handles = present
+            .iter()
+            .map(|reader| scope.spawn(move || read_present_card(context, reader)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(Err(CardReaderError::ReaderThreadPanicked)))
+            .collect::<Vec<_>>()
+    });
+
+    Ok(present.iter().map(|reader| reader.to_string_lossy().into_owned()).zip(results).collect())
+}
+
+/// Connects to the card in a reader and reads it
+///
+/// # Arguments
+/// - `context` - The PC/SC context
+/// - `reader` - The name of the reader
+///
+/// # Returns
+/// The data read from the card
+fn read_present_card(context: &Context, reader: &CStr) -> Result<DriverCard, CardReaderError> {
+    let card = connect_in_share_mode(context, reader, ShareMode::Shared, Protocols::ANY, DEFAULT_CONNECT_RETRIES)?;
+    let quirks = ReaderQuirks::for_reader(&reader.to_string_lossy());
+    read_full_card(&QuirksTransport::new(&card, quirks), None)
+}
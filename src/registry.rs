@@ -0,0 +1,216 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::apdu::{read_ef_fully, read_ef_fully_auto, select_ef_length, transmit_select_ef_with_fcp_apdu};
+use crate::card::{select_tachograph_df, Generation};
+use crate::error::CardReaderError;
+use crate::fcp::Fcp;
+use crate::transport::CardTransport;
+
+/// The registry embedded in the crate, used when no external registry is loaded
+pub const DEFAULT_REGISTRY: &str = include_str!("files.toml");
+
+/// Definition of an EF under the tachograph application
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EfDefinition {
+    pub name: String,
+    /// File identifier, given in hex in the registry
+    #[serde(deserialize_with = "deserialize_ef_id")]
+    pub id: [u8; 2],
+    /// Number of bytes to read from the EF
+    pub length: u16,
+    /// Generation of the application the definition applies to, `None` if it applies to both
+    #[serde(default)]
+    pub generation: Option<Generation>,
+}
+
+/// A registry of EF definitions, looked up by name
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EfRegistry {
+    #[serde(rename = "file", default)]
+    pub files: Vec<EfDefinition>,
+}
+
+/// Result of probing for an EF on the card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EfProbe {
+    pub name: String,
+    pub id: [u8; 2],
+    /// Whether the card selected the EF successfully
+    pub present: bool,
+    /// Length of the EF reported in its FCP, `None` if the EF is missing or the card did not report it
+    pub length: Option<u16>,
+}
+
+/// Entry of the manifest written by [`dump_all_efs`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DumpedEf {
+    pub name: String,
+    /// File identifier in hex
+    pub id: String,
+    /// Name of the file the contents were written to, `None` if the EF could not be read
+    pub file: Option<String>,
+    /// Number of bytes read from the EF
+    pub length: Option<usize>,
+    /// Status word the card answered with, in hex
+    pub status_word: Option<String>,
+    /// Why the EF could not be read
+    pub error: Option<String>,
+}
+
+impl EfRegistry {
+    /// Parses a registry from TOML, with one `[[file]]` table per EF definition
+    ///
+    /// # Arguments
+    /// - `input` - The TOML document
+    ///
+    /// # Returns
+    /// The parsed registry
+    pub fn from_toml(input: &str) -> Result<Self, CardReaderError> {
+        Ok(toml::from_str(input)?)
+    }
+
+    /// Loads a registry from a TOML file
+    ///
+    /// # Arguments
+    /// - `path` - Path of the TOML file
+    ///
+    /// # Returns
+    /// The loaded registry
+    pub fn load(path: &Path) -> Result<Self, CardReaderError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Looks up the definition of an EF
+    ///
+    /// # Arguments
+    /// - `name` - The name of the EF
+    /// - `generation` - The generation of the application the EF is read from
+    ///
+    /// # Returns
+    /// The first definition with the name that applies to the generation
+    pub fn get(&self, name: &str, generation: Generation) -> Option<&EfDefinition> {
+        self.files
+            .iter()
+            .find(|file| file.name == name && file.generation.is_none_or(|file_generation| file_generation == generation))
+    }
+}
+
+impl Default for EfRegistry {
+    /// Returns the registry embedded in the crate
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_REGISTRY).expect("The embedded EF registry is valid")
+    }
+}
+
+/// Deserializes a file identifier given as 4 hex digits
+fn deserialize_ef_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 2], D::Error> {
+    let id = String::deserialize(deserializer)?;
+    match u16::from_str_radix(&id, 16) {
+        Ok(value) if id.len() == 4 => Ok(value.to_be_bytes()),
+        _ => Err(serde::de::Error::custom(format!("{:?} is not a 2-byte hex file identifier", id))),
+    }
+}
+
+/// Reads an EF defined in the registry from the tachograph application of the card
+///
+/// The EF is read up to the file size the card reports in its FCP, or up to the length in the registry if the card
+/// doesn't report one.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `registry` - The registry to look the EF up from
+/// - `name` - The name of the EF
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The contents of the EF, or `UnknownEf` if the registry has no definition for it
+pub fn read_registered_ef(card: &impl CardTransport, registry: &EfRegistry, name: &str, generation: Option<Generation>) -> Result<Vec<u8>, CardReaderError> {
+    let generation = select_tachograph_df(card, generation)?;
+    let file = registry.get(name, generation).ok_or_else(|| CardReaderError::UnknownEf(name.to_string()))?;
+    let length = select_ef_length(card, file.id)?.unwrap_or(file.length);
+    read_ef_fully_auto(card, length)
+}
+
+/// Probes the tachograph application of the card for every EF in the registry
+///
+/// Each EF that applies to the selected generation is selected requesting its FCP. EFs that the card refuses to select
+/// are reported as missing, which makes this useful for exploring card variants without parsing their files.
+///
+/// # Arguments
+/// - `card` - The smart card to probe
+/// - `registry` - The registry listing the EFs to probe
+/// - `generation` - The generation of the application to probe, or `None` to use the newest one on the card
+///
+/// # Returns
+/// One probe result per EF definition, in registry order
+pub fn probe_efs(card: &impl CardTransport, registry: &EfRegistry, generation: Option<Generation>) -> Result<Vec<EfProbe>, CardReaderError> {
+    let generation = select_tachograph_df(card, generation)?;
+    registry
+        .files
+        .iter()
+        .filter(|file| file.generation.is_none_or(|file_generation| file_generation == generation))
+        .map(|file| {
+            let (present, length) = match transmit_select_ef_with_fcp_apdu(card, &file.id) {
+                Ok(response) => (true, Fcp::new(&response).ok().and_then(|fcp| fcp.readable_length())),
+                Err(CardReaderError::UnexpectedStatusWord(_, _)) => (false, None),
+                Err(e) => return Err(e),
+            };
+            Ok(EfProbe { name: file.name.clone(), id: file.id, present, length })
+        })
+        .collect()
+}
+
+/// Reads every EF in the registry and writes their contents to a directory
+///
+/// Each EF is written to `<name>.bin`, and a `manifest.json` lists every EF with its identifier, length and status word.
+/// EFs that can't be selected or read are recorded in the manifest with their error instead of aborting the dump.
+///
+/// # Arguments
+/// - `card` - The smart card to read from
+/// - `registry` - The registry listing the EFs to dump
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `dir` - The directory to write the files to, created if it doesn't exist
+///
+/// # Returns
+/// The entries of the manifest
+pub fn dump_all_efs(card: &impl CardTransport, registry: &EfRegistry, generation: Option<Generation>, dir: &Path) -> Result<Vec<DumpedEf>, CardReaderError> {
+    let generation = select_tachograph_df(card, generation)?;
+    fs::create_dir_all(dir)?;
+
+    let mut manifest = Vec::new();
+    for file in registry.files.iter().filter(|file| file.generation.is_none_or(|file_generation| file_generation == generation)) {
+        let mut entry = DumpedEf {
+            name: file.name.clone(),
+            id: format!("{:02X}{:02X}", file.id[0], file.id[1]),
+            file: None,
+            length: None,
+            status_word: None,
+            error: None,
+        };
+
+        let data = select_ef_length(card, file.id).and_then(|length| read_ef_fully(card, length.unwrap_or(file.length), None));
+        match data {
+            Ok(data) => {
+                let file_name = format!("{}.bin", file.name);
+                fs::write(dir.join(&file_name), &data)?;
+                entry.file = Some(file_name);
+                entry.length = Some(data.len());
+                entry.status_word = Some("9000".to_string());
+            }
+            Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) => {
+                entry.status_word = Some(format!("{:02X}{:02X}", sw1, sw2));
+                entry.error = Some(CardReaderError::UnexpectedStatusWord(sw1, sw2).to_string());
+            }
+            Err(e @ CardReaderError::Truncated) => entry.error = Some(e.to_string()),
+            Err(e) => return Err(e),
+        }
+        manifest.push(entry);
+    }
+
+    let manifest_json = serde_json::to_string_pretty(&manifest).expect("The manifest is serializable");
+    fs::write(dir.join("manifest.json"), manifest_json)?;
+    Ok(manifest)
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+
+use crate::apdu::transmit_read_binary_apdu;
+use crate::application::read_application_identification;
+use crate::card::{
+    select_tachograph_ef, Generation, TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF, TACHOGRAPH_EVENTS_DATA_EF, TACHOGRAPH_FAULTS_DATA_EF, TACHOGRAPH_PLACES_EF,
+    TACHOGRAPH_VEHICLES_USED_EF,
+};
+use crate::error::CardReaderError;
+use crate::events::event_type_count;
+use crate::faults::FAULT_TYPE_COUNT;
+use crate::transport::CardTransport;
+
+/// An EF storing its records in a ring buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RingEf {
+    DriverActivity,
+    Events,
+    Faults,
+    Places,
+    VehiclesUsed,
+}
+
+impl RingEf {
+    /// Returns the file identifier of the EF
+    pub fn id(self) -> &'static [u8] {
+        match self {
+            RingEf::DriverActivity => TACHOGRAPH_DRIVER_ACTIVITY_DATA_EF,
+            RingEf::Events => TACHOGRAPH_EVENTS_DATA_EF,
+            RingEf::Faults => TACHOGRAPH_FAULTS_DATA_EF,
+            RingEf::Places => TACHOGRAPH_PLACES_EF,
+            RingEf::VehiclesUsed => TACHOGRAPH_VEHICLES_USED_EF,
+        }
+    }
+}
+
+/// The raw pointers of a ring buffer EF
+///
+/// The driver activity EF points to the byte offsets of its oldest and newest daily records, the vehicles and places
+/// EFs to the index of their newest record. The events and faults EFs have no pointers, their slots are overwritten
+/// oldest first within each type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RingPointers {
+    pub ef: RingEf,
+    /// Generation of the application the EF was read from
+    pub generation: Generation,
+    /// Byte offset of the oldest daily record, only stored by the driver activity EF
+    pub oldest: Option<u16>,
+    /// Byte offset of the newest daily record, or the index of the newest record
+    pub newest: Option<u16>,
+    /// Length of the driver activity buffer in bytes, or the number of records the EF holds, from the application identification
+    pub capacity: u16,
+}
+
+impl RingPointers {
+    /// Returns whether both pointers are within the ring buffer, i.e. whether the records can be walked from them
+    pub fn is_in_bounds(&self) -> bool {
+        [self.oldest, self.newest].iter().flatten().all(|&pointer| pointer < self.capacity)
+    }
+}
+
+/// Reads the ring buffer pointers of an EF without reading its records
+///
+/// # Arguments
+/// - `card` - The smart card to read the pointers from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+/// - `ef` - The EF to read the pointers of
+///
+/// # Returns
+/// The pointers and the capacity of the ring buffer
+pub fn read_ring_pointers(card: &impl CardTransport, generation: Option<Generation>, ef: RingEf) -> Result<RingPointers, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let generation = select_tachograph_ef(card, Some(app_id.generation), ef.id())?;
+
+    let (oldest, newest, capacity) = match ef {
+        RingEf::DriverActivity => {
+            let pointers = transmit_read_binary_apdu(card, 0, 4)?;
+            let pointers = pointers.get(..4).ok_or(CardReaderError::Truncated)?;
+            (Some(u16::from_be_bytes([pointers[0], pointers[1]])), Some(u16::from_be_bytes([pointers[2], pointers[3]])), app_id.activity_structure_length)
+        }
+        RingEf::Events => (None, None, (event_type_count(generation) * app_id.events_per_type as usize) as u16),
+        RingEf::Faults => (None, None, (FAULT_TYPE_COUNT * app_id.faults_per_type as usize) as u16),
+        // Gen1 stores the newest place record pointer in a single byte, Gen2 in two bytes
+        RingEf::Places => match generation {
+            Generation::Gen1 => {
+                let pointer = transmit_read_binary_apdu(card, 0, 1)?;
+                (None, Some(*pointer.first().ok_or(CardReaderError::Truncated)? as u16), app_id.place_records)
+            }
+            Generation::Gen2 => (None, Some(read_u16_pointer(card)?), app_id.place_records),
+        },
+        RingEf::VehiclesUsed => (None, Some(read_u16_pointer(card)?), app_id.vehicle_records),
+    };
+
+    Ok(RingPointers { ef, generation, oldest, newest, capacity })
+}
+
+/// Reads a two byte pointer from the start of the currently selected EF
+fn read_u16_pointer(card: &impl CardTransport) -> Result<u16, CardReaderError> {
+    let pointer = transmit_read_binary_apdu(card, 0, 2)?;
+    let pointer = pointer.get(..2).ok_or(CardReaderError::Truncated)?;
+    Ok(u16::from_be_bytes([pointer[0], pointer[1]]))
+}
@@ -0,0 +1,292 @@
+use std::cell::Cell;
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256};
+use cmac::{Cmac, Mac};
+
+use crate::apdu::{split_status_word, SW1_WRONG_LE};
+use crate::error::CardReaderError;
+use crate::transport::{CardProtocol, CardTransport};
+use crate::tlv::{find_tlv, parse_tlv, Tlv};
+
+/// Block length of AES, used for padding and as the length of the send sequence counter
+pub const BLOCK_LENGTH: usize = 16;
+/// Length of the truncated CMAC in the `8E` data object
+pub const MAC_LENGTH: usize = 8;
+
+pub const CRYPTOGRAM_TAG: u16 = 0x87;
+pub const EXPECTED_LENGTH_TAG: u16 = 0x97;
+pub const PROCESSING_STATUS_TAG: u16 = 0x99;
+pub const MAC_TAG: u16 = 0x8E;
+
+/// AES session keys established by the Gen2 chip authentication
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionKeys {
+    /// Key used to encrypt the command and response data, 16, 24 or 32 bytes
+    pub encryption_key: Vec<u8>,
+    /// Key used to compute the CMACs, of the same length as the encryption key
+    pub mac_key: Vec<u8>,
+}
+
+/// An AES block cipher with a key of any of the lengths used by Gen2 cards
+enum BlockCipher {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+impl BlockCipher {
+    fn new(key: &[u8]) -> Result<Self, CardReaderError> {
+        match key.len() {
+            16 => Ok(BlockCipher::Aes128(Aes128::new(GenericArray::from_slice(key)))),
+            24 => Ok(BlockCipher::Aes192(Aes192::new(GenericArray::from_slice(key)))),
+            32 => Ok(BlockCipher::Aes256(Aes256::new(GenericArray::from_slice(key)))),
+            _ => Err(CardReaderError::InvalidSessionKey),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            BlockCipher::Aes128(cipher) => cipher.encrypt_block(block),
+            BlockCipher::Aes192(cipher) => cipher.encrypt_block(block),
+            BlockCipher::Aes256(cipher) => cipher.encrypt_block(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            BlockCipher::Aes128(cipher) => cipher.decrypt_block(block),
+            BlockCipher::Aes192(cipher) => cipher.decrypt_block(block),
+            BlockCipher::Aes256(cipher) => cipher.decrypt_block(block),
+        }
+    }
+}
+
+/// A card transport that protects every APDU with Gen2 secure messaging
+///
+/// Command data is encrypted and every command is MACed; responses are verified and decrypted before they are
+/// returned, so the APDU functions work unchanged on top of a secure session. The session ends at the first
+/// response that fails verification, as the send sequence counters of the card and the reader no longer agree.
+pub struct SecureChannel<T: CardTransport> {
+    card: T,
+    cipher: BlockCipher,
+    mac_key: Vec<u8>,
+    send_sequence_counter: Cell<u128>,
+}
+
+impl<T: CardTransport> SecureChannel<T> {
+    /// Starts secure messaging with established session keys
+    ///
+    /// # Arguments
+    /// - `card` - The transport to the card the keys were established with
+    /// - `keys` - The session keys
+    ///
+    /// # Returns
+    /// The secure channel, or `InvalidSessionKey` if the keys are not valid AES keys
+    pub fn new(card: T, keys: &SessionKeys) -> Result<Self, CardReaderError> {
+        if keys.mac_key.len() != keys.encryption_key.len() {
+            return Err(CardReaderError::InvalidSessionKey);
+        }
+
+        Ok(SecureChannel {
+            card,
+            cipher: BlockCipher::new(&keys.encryption_key)?,
+            mac_key: keys.mac_key.clone(),
+            // The send sequence counter starts at zero after the keys are established
+            send_sequence_counter: Cell::new(0),
+        })
+    }
+
+    /// Ends secure messaging and returns the underlying transport
+    pub fn into_inner(self) -> T {
+        self.card
+    }
+
+    /// Increments the send sequence counter and returns its new value
+    fn next_send_sequence_counter(&self) -> [u8; BLOCK_LENGTH] {
+        let counter = self.send_sequence_counter.get().wrapping_add(1);
+        self.send_sequence_counter.set(counter);
+        counter.to_be_bytes()
+    }
+
+    /// Computes the truncated CMAC of data prefixed with the send sequence counter
+    fn mac(&self, send_sequence_counter: &[u8], data: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let mac_input = [send_sequence_counter, &pad(data)].concat();
+        let mac = match self.mac_key.len() {
+            16 => cmac_of::<Cmac<Aes128>>(&self.mac_key, &mac_input),
+            24 => cmac_of::<Cmac<Aes192>>(&self.mac_key, &mac_input),
+            32 => cmac_of::<Cmac<Aes256>>(&self.mac_key, &mac_input),
+            _ => return Err(CardReaderError::InvalidSessionKey),
+        };
+        Ok(mac[..MAC_LENGTH].to_vec())
+    }
+
+    /// Encrypts or decrypts data in CBC mode, with the encrypted send sequence counter as the IV
+    fn cbc(&self, send_sequence_counter: &[u8], data: &[u8], encrypt: bool) -> Vec<u8> {
+        let mut chaining = send_sequence_counter.to_vec();
+        self.cipher.encrypt_block(&mut chaining);
+
+        let mut output = Vec::with_capacity(data.len());
+        for block in data.chunks(BLOCK_LENGTH) {
+            let mut block = block.to_vec();
+            block.resize(BLOCK_LENGTH, 0);
+            if encrypt {
+                block.iter_mut().zip(&chaining).for_each(|(byte, chain)| *byte ^= chain);
+                self.cipher.encrypt_block(&mut block);
+                chaining = block.clone();
+            } else {
+                let ciphertext = block.clone();
+                self.cipher.decrypt_block(&mut block);
+                block.iter_mut().zip(&chaining).for_each(|(byte, chain)| *byte ^= chain);
+                chaining = ciphertext;
+            }
+            output.extend_from_slice(&block);
+        }
+        output
+    }
+
+    /// Protects a command APDU
+    ///
+    /// # Arguments
+    /// - `apdu` - The plain command APDU
+    ///
+    /// # Returns
+    /// The command APDU with the data encrypted and a MAC appended
+    fn wrap(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let Command { header, data, expected_length } = Command::new(apdu)?;
+        let header = [header[0] | 0x0C, header[1], header[2], header[3]];
+        let send_sequence_counter = self.next_send_sequence_counter();
+
+        let mut objects = Vec::new();
+        if !data.is_empty() {
+            let cryptogram = self.cbc(&send_sequence_counter, &pad(data), true);
+            objects.extend(Tlv { tag: CRYPTOGRAM_TAG, value: [&[0x01], cryptogram.as_slice()].concat() }.to_bytes());
+        }
+        if let Some(expected_length) = expected_length {
+            objects.extend(Tlv { tag: EXPECTED_LENGTH_TAG, value: vec![expected_length] }.to_bytes());
+        }
+
+        let mac = self.mac(&send_sequence_counter, &[pad(&header).as_slice(), &objects].concat())?;
+        objects.extend(Tlv { tag: MAC_TAG, value: mac }.to_bytes());
+
+        let mut wrapped = header.to_vec();
+        wrapped.push(objects.len() as u8);
+        wrapped.extend_from_slice(&objects);
+        wrapped.push(0x00);
+        Ok(wrapped)
+    }
+
+    /// Verifies and decrypts a protected response APDU
+    ///
+    /// # Arguments
+    /// - `response` - The protected response APDU
+    ///
+    /// # Returns
+    /// The plain response APDU including the status word
+    fn unwrap(&self, response: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let send_sequence_counter = self.next_send_sequence_counter();
+        let (data, status_word) = split_status_word(response)?;
+        if data.is_empty() {
+            // Only errors, e.g. `69 87` or `69 88` for the secure messaging itself, are reported without protection.
+            // An unprotected success or warning could have had its data objects stripped, so it is rejected.
+            return match status_word {
+                (sw1, _) if is_unprotected_error(sw1) => Ok(response.to_vec()),
+                _ => Err(CardReaderError::SecureMessaging),
+            };
+        }
+
+        let objects = parse_tlv(data)?;
+        let mac = find_tlv(&objects, MAC_TAG).ok_or(CardReaderError::SecureMessaging)?;
+        let protected = objects
+            .iter()
+            .filter(|object| object.tag == CRYPTOGRAM_TAG || object.tag == PROCESSING_STATUS_TAG)
+            .flat_map(Tlv::to_bytes)
+            .collect::<Vec<u8>>();
+        if self.mac(&send_sequence_counter, &protected)? != mac.value {
+            return Err(CardReaderError::SecureMessaging);
+        }
+
+        let mut plain = match find_tlv(&objects, CRYPTOGRAM_TAG) {
+            Some(cryptogram) => unpad(self.cbc(&send_sequence_counter, cryptogram.value.get(1..).unwrap_or_default(), false))?,
+            None => Vec::new(),
+        };
+        match find_tlv(&objects, PROCESSING_STATUS_TAG) {
+            Some(processing_status) => plain.extend_from_slice(&processing_status.value),
+            None => plain.extend_from_slice(&[status_word.0, status_word.1]),
+        }
+        Ok(plain)
+    }
+}
+
+impl<T: CardTransport> CardTransport for SecureChannel<T> {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let response = self.card.transmit(&self.wrap(apdu)?)?;
+        self.unwrap(&response)
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        self.card.protocol()
+    }
+}
+
+/// Returns whether a status word without data objects is an error the card may send without protection
+///
+/// Successes (`90 00`), warnings (`62 XX`, `63 XX`) and the `61 XX` and `6C XX` status words of T=0 are always
+/// protected, everything in the `64 XX` to `6F XX` error range other than `6C XX` may be sent as is.
+fn is_unprotected_error(sw1: u8) -> bool {
+    matches!(sw1, 0x64..=0x6F) && sw1 != SW1_WRONG_LE
+}
+
+/// Computes the CMAC of data with an AES key
+fn cmac_of<C: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <C as KeyInit>::new_from_slice(key).expect("The key length was checked");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The parts of a short command APDU
+struct Command<'a> {
+    header: &'a [u8],
+    data: &'a [u8],
+    expected_length: Option<u8>,
+}
+
+impl<'a> Command<'a> {
+    /// Splits a short command APDU into its header, its data and its expected length
+    fn new(apdu: &'a [u8]) -> Result<Self, CardReaderError> {
+        if apdu.len() < 4 {
+            return Err(CardReaderError::Truncated);
+        }
+        let (header, body) = apdu.split_at(4);
+
+        let (data, expected_length) = match body {
+            [] => (&[][..], None),
+            [expected_length] => (&[][..], Some(*expected_length)),
+            [length, rest @ ..] if rest.len() == *length as usize => (rest, None),
+            [length, rest @ ..] if rest.len() == *length as usize + 1 => (&rest[..*length as usize], rest.last().copied()),
+            _ => return Err(CardReaderError::Truncated),
+        };
+        Ok(Command { header, data, expected_length })
+    }
+}
+
+/// Pads data to a multiple of the block length with `80` followed by `00` bytes
+fn pad(data: &[u8]) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    padded.resize(padded.len().div_ceil(BLOCK_LENGTH) * BLOCK_LENGTH, 0x00);
+    padded
+}
+
+/// Removes the `80 00 ..` padding of decrypted data
+fn unpad(mut data: Vec<u8>) -> Result<Vec<u8>, CardReaderError> {
+    while data.last() == Some(&0x00) {
+        data.pop();
+    }
+    match data.pop() {
+        Some(0x80) => Ok(data),
+        _ => Err(CardReaderError::SecureMessaging),
+    }
+}
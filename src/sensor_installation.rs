@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::{read_card_type, record_ef_length, CardType};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_APPLICATION_IDENTIFICATION_EF, TACHOGRAPH_CALIBRATION_EF, TACHOGRAPH_SENSOR_INSTALLATION_DATA_EF};
+use crate::error::CardReaderError;
+use crate::parse::{check_ring_pointer, parse_bcd_number, Reader};
+use crate::transport::CardTransport;
+
+/// Length of the sensor installation data EF
+pub const SENSOR_INSTALLATION_LENGTH: u16 = 16;
+/// Length of the Gen1 workshop card application identification, which ends with the number of calibration records
+pub const WORKSHOP_APPLICATION_IDENTIFICATION_LENGTH: u16 = 11;
+/// Length of a single Gen1 `WorkshopCardCalibrationRecord`
+pub const CALIBRATION_RECORD_LENGTH: usize = 105;
+/// Length of the header of the calibration EF, i.e. the total number of calibrations and the newest record pointer
+const CALIBRATION_HEADER_LENGTH: usize = 3;
+
+/// The `SensorInstallationSecData` of a Gen1 workshop card
+///
+/// The EF holds the TDES key the vehicle unit uses to pair with a motion sensor, not a record of the pairing. What
+/// was installed is recorded in the calibration records of the card, see [`read_sensor_installations`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SensorInstallationSecData {
+    /// First half of the double length TDES key
+    pub key_a: [u8; 8],
+    /// Second half of the double length TDES key
+    pub key_b: [u8; 8],
+}
+
+impl SensorInstallationSecData {
+    /// Parses the contents of the sensor installation data EF
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the EF (16 bytes)
+    ///
+    /// # Returns
+    /// The parsed key
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        Ok(SensorInstallationSecData { key_a: reader.take_array()?, key_b: reader.take_array()? })
+    }
+}
+
+/// Serial number of a VU or motion sensor (`ExtendedSerialNumber`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ExtendedSerialNumber {
+    /// Serial number, unique for the manufacturer, equipment type and manufacturing month
+    pub serial_number: u32,
+    pub manufacturing_month: u8,
+    pub manufacturing_year: u16,
+    pub equipment_type: u8,
+    pub manufacturer_code: u8,
+}
+
+impl ExtendedSerialNumber {
+    /// Parses an `ExtendedSerialNumber`
+    ///
+    /// # Arguments
+    /// - `data` - The serial number (8 bytes)
+    ///
+    /// # Returns
+    /// The parsed serial number
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let mut reader = Reader::new(data);
+        let serial_number = reader.take_u32_be()?;
+        let [month, year] = reader.take_array()?;
+        let [equipment_type, manufacturer_code] = reader.take_array()?;
+
+        Ok(ExtendedSerialNumber {
+            serial_number,
+            manufacturing_month: parse_bcd_number(&[month])? as u8,
+            manufacturing_year: 2000 + parse_bcd_number(&[year])? as u16,
+            equipment_type,
+            manufacturer_code,
+        })
+    }
+}
+
+/// A motion sensor installation, i.e. the pairing of a sensor with a VU, from a `WorkshopCardCalibrationRecord`
+///
+/// The approval number of the sensor is only recorded by the VU it was paired with, the card doesn't store it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SensorInstallation {
+    /// Purpose of the calibration (`CalibrationPurpose`), e.g. 2 for a first installation
+    pub calibration_purpose: u8,
+    pub vehicle_identification_number: String,
+    pub sensor_serial_number: ExtendedSerialNumber,
+    pub vu_serial_number: ExtendedSerialNumber,
+    pub vu_part_number: String,
+    /// Time the VU clock was set to during the calibration
+    pub pairing_time: DateTime<Utc>,
+}
+
+impl SensorInstallation {
+    /// Parses a single `WorkshopCardCalibrationRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (105 bytes)
+    ///
+    /// # Returns
+    /// The parsed installation, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let mut reader = Reader::new(record);
+        let calibration_purpose = reader.take_u8()?;
+        let vehicle_identification_number = reader.take(17)?;
+        // Vehicle registration, w and k constants, tyre circumference, tyre size, authorised speed and odometer values
+        reader.take(15 + 2 + 2 + 2 + 15 + 1 + 3 + 3)?;
+        // Old time value
+        reader.take(4)?;
+        let pairing_time = reader.take_time_real()?;
+        // Next calibration date
+        reader.take(4)?;
+        let vu_part_number = reader.take(16)?;
+        let vu_serial_number = ExtendedSerialNumber::new(reader.take(8)?)?;
+        let sensor_serial_number = ExtendedSerialNumber::new(reader.take(8)?)?;
+
+        let Some(pairing_time) = pairing_time else {
+            return Ok(None);
+        };
+
+        Ok(Some(SensorInstallation {
+            calibration_purpose,
+            vehicle_identification_number: String::from_utf8(vehicle_identification_number.to_vec())?.trim_end_matches(['\0', ' ']).to_string(),
+            sensor_serial_number,
+            vu_serial_number,
+            vu_part_number: String::from_utf8(vu_part_number.to_vec())?.trim_end_matches(['\0', ' ']).to_string(),
+            pairing_time,
+        }))
+    }
+}
+
+/// Parses the contents of the calibration EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `record_count` - The number of calibration records, from the application identification
+///
+/// # Returns
+/// The sensor installations in chronological order, skipping empty slots
+pub fn parse_sensor_installations(data: &[u8], record_count: usize) -> Result<Vec<SensorInstallation>, CardReaderError> {
+    let mut reader = Reader::new(data);
+    // Total number of calibrations the card was used for
+    reader.take_u16_be()?;
+    let newest = reader.take_u8()? as usize;
+
+    let records = reader.remaining().chunks_exact(CALIBRATION_RECORD_LENGTH).take(record_count).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut installations = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(installation) = SensorInstallation::new(records[(newest + 1 + i) % records.len()])? {
+            installations.push(installation);
+        }
+    }
+    Ok(installations)
+}
+
+/// Checks that the card is a workshop card, the only card type with the sensor installation EFs
+fn require_workshop_card(card: &impl CardTransport) -> Result<(), CardReaderError> {
+    match read_card_type(card, Some(Generation::Gen1))? {
+        CardType::Workshop => Ok(()),
+        card_type => Err(CardReaderError::UnsupportedCardType(card_type)),
+    }
+}
+
+/// Reads the sensor installation data from the Gen1 tachograph application of a workshop card
+///
+/// Only workshop cards have the EF, other card types fail with `CardReaderError::UnsupportedCardType` without
+/// selecting it.
+///
+/// # Arguments
+/// - `card` - The smart card to read the sensor installation data from
+///
+/// # Returns
+/// The sensor installation data
+pub fn read_sensor_installation(card: &impl CardTransport) -> Result<SensorInstallationSecData, CardReaderError> {
+    require_workshop_card(card)?;
+    select_tachograph_ef(card, Some(Generation::Gen1), TACHOGRAPH_SENSOR_INSTALLATION_DATA_EF)?;
+    let data = read_ef_fully(card, SENSOR_INSTALLATION_LENGTH, None)?;
+    SensorInstallationSecData::new(&data)
+}
+
+/// Reads the sensor installations from the calibration records of the Gen1 tachograph application of a workshop card
+///
+/// Only workshop cards have the EF, other card types fail with `CardReaderError::UnsupportedCardType` without
+/// selecting it. The number of records comes from the application identification of the card.
+///
+/// # Arguments
+/// - `card` - The smart card to read the sensor installations from
+///
+/// # Returns
+/// The sensor installations stored on the card in chronological order
+pub fn read_sensor_installations(card: &impl CardTransport) -> Result<Vec<SensorInstallation>, CardReaderError> {
+    require_workshop_card(card)?;
+    select_tachograph_ef(card, Some(Generation::Gen1), TACHOGRAPH_APPLICATION_IDENTIFICATION_EF)?;
+    let app_id = read_ef_fully(card, WORKSHOP_APPLICATION_IDENTIFICATION_LENGTH, None)?;
+    let record_count = *app_id.get(WORKSHOP_APPLICATION_IDENTIFICATION_LENGTH as usize - 1).ok_or(CardReaderError::Truncated)? as usize;
+
+    select_tachograph_ef(card, Some(Generation::Gen1), TACHOGRAPH_CALIBRATION_EF)?;
+    let data = read_ef_fully(card, record_ef_length(CALIBRATION_HEADER_LENGTH, record_count, CALIBRATION_RECORD_LENGTH)?, None)?;
+    parse_sensor_installations(&data, record_count)
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::{read_ef_fully, STATUS_WORD_FILE_NOT_FOUND};
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_SPECIFIC_CONDITIONS_EF};
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, take_array};
+use crate::transport::CardTransport;
+
+/// Length of a single `SpecificConditionRecord`
+pub const SPECIFIC_CONDITION_RECORD_LENGTH: usize = 5;
+/// Number of specific condition records stored on the card
+pub const NO_OF_SPECIFIC_CONDITION_RECORDS: usize = 56;
+
+/// Type of a specific condition (`SpecificConditionType`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConditionType {
+    OutOfScopeBegin,
+    OutOfScopeEnd,
+    FerryTrainCrossingBegin,
+    /// End of a ferry/train crossing, only recorded by Gen2 VUs
+    FerryTrainCrossingEnd,
+    Unknown(u8),
+}
+
+impl From<u8> for ConditionType {
+    fn from(code: u8) -> Self {
+        match code {
+            0x01 => ConditionType::OutOfScopeBegin,
+            0x02 => ConditionType::OutOfScopeEnd,
+            0x03 => ConditionType::FerryTrainCrossingBegin,
+            0x04 => ConditionType::FerryTrainCrossingEnd,
+            _ => ConditionType::Unknown(code),
+        }
+    }
+}
+
+/// A specific condition entered by the driver, e.g. the beginning of a ferry/train crossing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpecificCondition {
+    pub entry_time: DateTime<Utc>,
+    pub condition_type: ConditionType,
+}
+
+impl SpecificCondition {
+    /// Parses a single `SpecificConditionRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (5 bytes)
+    ///
+    /// # Returns
+    /// The parsed specific condition, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (entry_time, remaining) = take_array(record)?;
+        let ([condition_type], _) = take_array(remaining)?;
+
+        let Some(entry_time) = parse_time_real(&entry_time) else {
+            return Ok(None);
+        };
+
+        Ok(Some(SpecificCondition { entry_time, condition_type: ConditionType::from(condition_type) }))
+    }
+}
+
+/// Parses the contents of the specific conditions EF
+///
+/// Gen1 cards store the records without a newest record pointer, so the records are sorted by their entry time.
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `generation` - The generation of the application the EF was read from
+///
+/// # Returns
+/// The specific conditions in chronological order, skipping empty slots
+pub fn parse_specific_conditions(data: &[u8], generation: Generation) -> Result<Vec<SpecificCondition>, CardReaderError> {
+    let records = match generation {
+        Generation::Gen1 => data,
+        Generation::Gen2 => take_array::<2>(data)?.1,
+    };
+
+    let mut conditions = Vec::new();
+    for record in records.chunks_exact(SPECIFIC_CONDITION_RECORD_LENGTH) {
+        if let Some(condition) = SpecificCondition::new(record)? {
+            conditions.push(condition);
+        }
+    }
+    conditions.sort_by_key(|condition| condition.entry_time);
+    Ok(conditions)
+}
+
+/// Reads the specific conditions from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the specific conditions from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The specific conditions in chronological order, empty for early Gen1 cards that don't have the EF
+pub fn read_specific_conditions(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<SpecificCondition>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let generation = match select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_SPECIFIC_CONDITIONS_EF) {
+        Ok(generation) => generation,
+        Err(CardReaderError::UnexpectedStatusWord(sw1, sw2)) if (sw1, sw2) == STATUS_WORD_FILE_NOT_FOUND => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    // Gen1 has no count for the records in the application identification
+    let length = match app_id.specific_condition_records {
+        None => record_ef_length(0, NO_OF_SPECIFIC_CONDITION_RECORDS, SPECIFIC_CONDITION_RECORD_LENGTH)?,
+        Some(record_count) => record_ef_length(2, record_count as usize, SPECIFIC_CONDITION_RECORD_LENGTH)?,
+    };
+    let data = read_ef_fully(card, length, None)?;
+    parse_specific_conditions(&data, generation)
+}
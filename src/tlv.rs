@@ -0,0 +1,108 @@
+use crate::error::CardReaderError;
+use crate::parse::take_n;
+
+/// A BER-TLV data object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlv {
+    /// The tag, with 1-byte tags stored in the low byte
+    pub tag: u16,
+    pub value: Vec<u8>,
+}
+
+impl Tlv {
+    /// Returns whether the data object is constructed, i.e. its value consists of further data objects
+    pub fn is_constructed(&self) -> bool {
+        let first_byte = if self.tag > 0xFF { (self.tag >> 8) as u8 } else { self.tag as u8 };
+        first_byte & 0x20 != 0
+    }
+
+    /// Parses the value of a constructed data object into its nested data objects
+    pub fn children(&self) -> Result<Vec<Tlv>, CardReaderError> {
+        parse_tlv(&self.value)
+    }
+
+    /// Encodes the data object, using the shortest length encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = if self.tag > 0xFF { self.tag.to_be_bytes().to_vec() } else { vec![self.tag as u8] };
+
+        let length = self.value.len();
+        match length {
+            0..=0x7F => bytes.push(length as u8),
+            0x80..=0xFF => bytes.extend_from_slice(&[0x81, length as u8]),
+            0x100..=0xFFFF => bytes.extend_from_slice(&[0x82, (length >> 8) as u8, length as u8]),
+            _ => bytes.extend_from_slice(&[0x83, (length >> 16) as u8, (length >> 8) as u8, length as u8]),
+        }
+
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+}
+
+/// Finds the first data object with the given tag
+///
+/// # Arguments
+/// - `objects` - The data objects to search
+/// - `tag` - The tag to look for
+pub fn find_tlv(objects: &[Tlv], tag: u16) -> Option<&Tlv> {
+    objects.iter().find(|object| object.tag == tag)
+}
+
+/// Parses a sequence of BER-TLV data objects
+///
+/// Supports 1 and 2 byte tags and lengths in the short form or the long form with up to 3 length bytes.
+/// `00` and `FF` padding bytes between data objects are skipped.
+///
+/// # Arguments
+/// - `input` - The encoded data objects
+///
+/// # Returns
+/// The parsed data objects
+pub fn parse_tlv(input: &[u8]) -> Result<Vec<Tlv>, CardReaderError> {
+    let mut objects = Vec::new();
+    let mut remaining = input;
+
+    while let Some(&first_byte) = remaining.first() {
+        if first_byte == 0x00 || first_byte == 0xFF {
+            remaining = &remaining[1..];
+            continue;
+        }
+
+        let (tag, rest) = parse_tag(remaining)?;
+        let (length, rest) = parse_length(rest)?;
+        let (value, rest) = take_n(length, rest)?;
+        objects.push(Tlv { tag, value: value.to_vec() });
+        remaining = rest;
+    }
+
+    Ok(objects)
+}
+
+/// Parses a BER-TLV tag of 1 or 2 bytes
+fn parse_tag(input: &[u8]) -> Result<(u16, &[u8]), CardReaderError> {
+    let (first_byte, rest) = take_n(1, input)?;
+    if first_byte[0] & 0x1F != 0x1F {
+        return Ok((first_byte[0] as u16, rest));
+    }
+
+    let (second_byte, rest) = take_n(1, rest)?;
+    // A set high bit would announce a third tag byte, which is not used by tachograph cards
+    if second_byte[0] & 0x80 != 0 {
+        return Err(CardReaderError::InvalidTlv);
+    }
+    Ok((u16::from_be_bytes([first_byte[0], second_byte[0]]), rest))
+}
+
+/// Parses a BER-TLV length in the short form or the long form with up to 3 length bytes
+fn parse_length(input: &[u8]) -> Result<(usize, &[u8]), CardReaderError> {
+    let (first_byte, rest) = take_n(1, input)?;
+    if first_byte[0] < 0x80 {
+        return Ok((first_byte[0] as usize, rest));
+    }
+
+    let length_bytes = (first_byte[0] & 0x7F) as usize;
+    if length_bytes == 0 || length_bytes > 3 {
+        return Err(CardReaderError::InvalidTlv);
+    }
+    let (length, rest) = take_n(length_bytes, rest)?;
+    Ok((length.iter().fold(0, |length, &byte| (length << 8) | byte as usize), rest))
+}
@@ -0,0 +1,361 @@
+#[cfg(feature = "pcsc")]
+use std::cell::RefCell;
+use std::panic;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+#[cfg(feature = "pcsc")]
+use pcsc::{Attribute, Disposition, Protocol, Protocols, ShareMode};
+use serde::Serialize;
+
+use crate::error::CardReaderError;
+
+/// Default time to wait for the card to answer an APDU when transmitting with a timeout
+pub const DEFAULT_TRANSMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Transmission protocol negotiated with a card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CardProtocol {
+    /// Character oriented T=0, where long responses are fetched with GET RESPONSE
+    T0,
+    /// Block oriented T=1
+    T1,
+    /// Raw protocol of the reader
+    Raw,
+}
+
+#[cfg(feature = "pcsc")]
+impl CardProtocol {
+    /// Returns the PC/SC protocol mask that allows only this protocol
+    pub fn protocols(self) -> Protocols {
+        match self {
+            CardProtocol::T0 => Protocols::T0,
+            CardProtocol::T1 => Protocols::T1,
+            CardProtocol::Raw => Protocols::RAW,
+        }
+    }
+}
+
+#[cfg(feature = "pcsc")]
+impl From<Protocol> for CardProtocol {
+    fn from(protocol: Protocol) -> Self {
+        match protocol {
+            Protocol::T0 => CardProtocol::T0,
+            Protocol::T1 => CardProtocol::T1,
+            Protocol::RAW => CardProtocol::Raw,
+        }
+    }
+}
+
+/// Longest short length command APDU: header, Lc, 255 bytes of data and Le
+pub const SHORT_APDU_MAX_SEND: usize = 261;
+/// Longest short length response APDU: 256 bytes of data and the status word
+pub const SHORT_APDU_MAX_RECV: usize = 258;
+
+/// How many bytes a reader can exchange with the card in one APDU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReaderLimits {
+    /// Longest command APDU the reader accepts
+    pub max_send: usize,
+    /// Longest response APDU, including the status word, the reader can return
+    pub max_recv: usize,
+    /// Whether the reader has room for extended length APDUs
+    pub supports_extended: bool,
+}
+
+impl ReaderLimits {
+    /// Limits assumed for readers that don't report theirs, which only allow short length APDUs
+    pub const CONSERVATIVE: ReaderLimits = ReaderLimits { max_send: SHORT_APDU_MAX_SEND, max_recv: SHORT_APDU_MAX_RECV, supports_extended: false };
+
+    /// Derives the limits from the size of the APDU buffer of a reader
+    ///
+    /// # Arguments
+    /// - `buffer_size` - The largest APDU the reader can exchange in either direction
+    pub fn from_buffer_size(buffer_size: usize) -> Self {
+        ReaderLimits { max_send: buffer_size, max_recv: buffer_size, supports_extended: buffer_size > SHORT_APDU_MAX_SEND }
+    }
+}
+
+/// A channel for exchanging APDUs with a card
+///
+/// Implemented for [`pcsc::Card`] and for a [`RefCell`] holding one, which can also reconnect; other implementations allow reading from something else than a physical card,
+/// e.g. a saved download with [`FileCard`](crate::ddd::FileCard) or canned responses in tests.
+pub trait CardTransport {
+    /// Transmits an APDU to the card
+    ///
+    /// # Arguments
+    /// - `apdu` - The APDU to transmit
+    ///
+    /// # Returns
+    /// The full response from the card including the trailing status word
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError>;
+
+    /// Reconnects to the card after it has been reset or removed and reinserted
+    ///
+    /// The card is reset when reconnecting, so the MF is selected afterwards. Transports that can't reconnect
+    /// return `ReconnectUnsupported`.
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        Err(CardReaderError::ReconnectUnsupported)
+    }
+
+    /// Returns the transmission protocol negotiated with the card
+    ///
+    /// Transports that don't know the protocol return `None`.
+    fn protocol(&self) -> Option<CardProtocol> {
+        None
+    }
+
+    /// Returns how many bytes the reader can exchange with the card in one APDU
+    ///
+    /// Transports that don't know the limits return `None`, see [`reader_limits`].
+    fn limits(&self) -> Option<ReaderLimits> {
+        None
+    }
+}
+
+impl<T: CardTransport + ?Sized> CardTransport for &T {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        (**self).transmit(apdu)
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        (**self).reconnect()
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        (**self).protocol()
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        (**self).limits()
+    }
+}
+
+#[cfg(feature = "pcsc")]
+impl CardTransport for pcsc::Card {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let mut rapdu_buf = vec![0; response_buffer_size(apdu)];
+        match pcsc::Card::transmit(self, apdu, &mut rapdu_buf) {
+            Ok(response) => return Ok(response.to_vec()),
+            Err(pcsc::Error::InsufficientBuffer) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // The card returned more than the APDU asked for, retry with room for the longest possible response
+        warn!("Response did not fit in {} bytes, retrying with {} bytes", rapdu_buf.len(), pcsc::MAX_BUFFER_SIZE_EXTENDED);
+        rapdu_buf.resize(pcsc::MAX_BUFFER_SIZE_EXTENDED, 0);
+        let response = pcsc::Card::transmit(self, apdu, &mut rapdu_buf)?;
+        Ok(response.to_vec())
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        self.status2_owned().ok()?.protocol2().map(CardProtocol::from)
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        // PC/SC reports a single buffer size for both directions, as a DWORD in native byte order
+        let buffer_size = self.get_attribute_owned(Attribute::Maxinput).ok()?;
+        let buffer_size = u32::from_ne_bytes(buffer_size.get(..4)?.try_into().ok()?);
+        (buffer_size > 0).then(|| ReaderLimits::from_buffer_size(buffer_size as usize))
+    }
+}
+
+#[cfg(feature = "pcsc")]
+impl CardTransport for RefCell<pcsc::Card> {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        CardTransport::transmit(&*self.borrow(), apdu)
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        reconnect(&mut self.borrow_mut())
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        CardTransport::protocol(&*self.borrow())
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        CardTransport::limits(&*self.borrow())
+    }
+}
+
+/// A transport that gives up on APDUs the card doesn't answer in time
+///
+/// Each APDU is transmitted on a worker thread. If the card doesn't answer before the timeout, `TransmitTimeout` is
+/// returned and the worker thread is left to finish the hung transmit in the background.
+pub struct TimeoutTransport<T> {
+    card: Arc<T>,
+    timeout: Duration,
+}
+
+impl<T> TimeoutTransport<T> {
+    /// Wraps a transport with a transmit timeout
+    ///
+    /// # Arguments
+    /// - `card` - The transport to transmit the APDUs with
+    /// - `timeout` - How long to wait for the card to answer each APDU
+    pub fn new(card: Arc<T>, timeout: Duration) -> Self {
+        TimeoutTransport { card, timeout }
+    }
+}
+
+impl<T: CardTransport + Send + Sync + 'static> TimeoutTransport<T> {
+    /// Runs an operation on the card on a worker thread, waiting at most the timeout for it to finish
+    fn with_timeout<R, F>(&self, operation: F) -> Result<R, CardReaderError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&T) -> Result<R, CardReaderError> + Send + 'static,
+    {
+        let card = Arc::clone(&self.card);
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            // The receiver is gone if the operation timed out, in which case the result is no longer needed
+            let _ = sender.send(operation(&card));
+        });
+
+        match receiver.recv_timeout(self.timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(CardReaderError::TransmitTimeout(self.timeout)),
+            Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+                Err(payload) => panic::resume_unwind(payload),
+                Ok(()) => unreachable!("The worker thread always sends a result before finishing"),
+            },
+        }
+    }
+}
+
+impl<T: CardTransport + Send + Sync + 'static> CardTransport for TimeoutTransport<T> {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let apdu = apdu.to_vec();
+        self.with_timeout(move |card| card.transmit(&apdu))
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        self.with_timeout(|card| card.reconnect())
+    }
+
+    fn protocol(&self) -> Option<CardProtocol> {
+        self.card.protocol()
+    }
+
+    fn limits(&self) -> Option<ReaderLimits> {
+        self.card.limits()
+    }
+}
+
+/// Returns how many bytes the reader of a card can exchange in one APDU
+///
+/// # Arguments
+/// - `card` - The transport to the card
+///
+/// # Returns
+/// The limits reported by the reader, or [`ReaderLimits::CONSERVATIVE`] if it doesn't report them
+pub fn reader_limits(card: &impl CardTransport) -> ReaderLimits {
+    card.limits().unwrap_or(ReaderLimits::CONSERVATIVE)
+}
+
+/// Returns the size of the buffer needed for the response to an APDU
+///
+/// The size is derived from the expected length (Le) of the short or extended length APDU, plus room for the status
+/// word. It is never smaller than the buffer of a short APDU response.
+///
+/// # Arguments
+/// - `apdu` - The command APDU
+#[cfg(feature = "pcsc")]
+fn response_buffer_size(apdu: &[u8]) -> usize {
+    let body = apdu.get(4..).unwrap_or_default();
+    let expected_length = match body {
+        [] => 0,
+        [le] => short_le(*le),
+        [0x00, le_hi, le_lo] => extended_le(*le_hi, *le_lo),
+        [0x00, lc_hi, lc_lo, rest @ ..] if rest.len() >= u16::from_be_bytes([*lc_hi, *lc_lo]) as usize => {
+            match rest[u16::from_be_bytes([*lc_hi, *lc_lo]) as usize..] {
+                [le_hi, le_lo] => extended_le(le_hi, le_lo),
+                _ => 0,
+            }
+        }
+        [lc, rest @ ..] => match rest.get(*lc as usize..) {
+            Some(&[le]) => short_le(le),
+            _ => 0,
+        },
+    };
+
+    (expected_length + 2).max(pcsc::MAX_BUFFER_SIZE)
+}
+
+/// Decodes a short Le, where `00` means 256 bytes
+#[cfg(feature = "pcsc")]
+fn short_le(le: u8) -> usize {
+    if le == 0 { 256 } else { le as usize }
+}
+
+/// Decodes an extended Le, where `00 00` means 65536 bytes
+#[cfg(feature = "pcsc")]
+fn extended_le(le_hi: u8, le_lo: u8) -> usize {
+    match u16::from_be_bytes([le_hi, le_lo]) {
+        0 => 65536,
+        le => le as usize,
+    }
+}
+
+/// Runs a read, restarting it once if the card was reset during it
+///
+/// A reset card has the MF selected, so the read is restarted from the beginning, including the selection of its DF
+/// and EF. The transport must reconnect to the card itself, like a [`RefCell`] holding a [`pcsc::Card`]; a plain card
+/// has to be reconnected with [`reconnect`] before the read is restarted.
+///
+/// # Arguments
+/// - `card` - The transport to the card
+/// - `read` - The read to run
+///
+/// # Returns
+/// The result of the read, or of the restarted read if the card was reset
+pub fn retry_after_reset<C: CardTransport, R>(card: &C, read: impl Fn(&C) -> Result<R, CardReaderError>) -> Result<R, CardReaderError> {
+    match read(card) {
+        Err(CardReaderError::CardReset) => {
+            warn!("The card was reset during the read, restarting it");
+            read(card)
+        }
+        result => result,
+    }
+}
+
+/// Reconnects to a card, resetting it
+///
+/// The protocol negotiated for the previous connection is kept, so a protocol forced when connecting stays in use.
+///
+/// # Arguments
+/// - `card` - The card to reconnect to
+#[cfg(feature = "pcsc")]
+pub fn reconnect(card: &mut pcsc::Card) -> Result<(), CardReaderError> {
+    let protocols = CardTransport::protocol(&*card).map_or(Protocols::ANY, CardProtocol::protocols);
+    card.reconnect(ShareMode::Shared, protocols, Disposition::ResetCard)?;
+    Ok(())
+}
+
+/// Runs a sequence of card operations in a PC/SC transaction
+///
+/// The transaction locks the card for the duration of the closure, so APDUs of other processes sharing the card
+/// can't interleave with a multi-step read. The transaction is ended on both success and error; an error ending it
+/// is only returned if the closure succeeded.
+///
+/// # Arguments
+/// - `card` - The card to lock
+/// - `f` - The operations to run, given the card to transmit to while it is locked
+///
+/// # Returns
+/// The result of the closure
+#[cfg(feature = "pcsc")]
+pub fn with_transaction<R>(card: &mut pcsc::Card, f: impl FnOnce(&pcsc::Card) -> Result<R, CardReaderError>) -> Result<R, CardReaderError> {
+    let transaction = card.transaction()?;
+    let result = f(&transaction);
+    match transaction.end(Disposition::LeaveCard) {
+        Ok(()) => result,
+        Err((_, e)) => {
+            warn!("Failed to end the transaction: {}", e);
+            result.and(Err(e.into()))
+        }
+    }
+}
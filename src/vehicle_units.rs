@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_VEHICLE_UNITS_USED_EF};
+use crate::error::CardReaderError;
+use crate::parse::{check_ring_pointer, parse_time_real, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single `CardVehicleUnitRecord`
+pub const VEHICLE_UNIT_RECORD_LENGTH: usize = 10;
+pub const NO_OF_CARD_VEHICLE_UNIT_RECORDS: usize = 200;
+
+/// A vehicle unit the card was inserted in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VehicleUnitUsed {
+    /// Beginning of the insertion of the card in the VU
+    pub time_stamp: DateTime<Utc>,
+    pub manufacturer_code: u8,
+    /// Type of the VU, as set by its manufacturer
+    pub device_id: u8,
+    pub vu_software_version: String,
+}
+
+impl VehicleUnitUsed {
+    /// Parses a single `CardVehicleUnitRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (10 bytes)
+    ///
+    /// # Returns
+    /// The parsed vehicle unit, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (time_stamp, remaining) = take_array(record)?;
+        let ([manufacturer_code, device_id], remaining) = take_array(remaining)?;
+        let (vu_software_version, _) = take_n(4, remaining)?;
+
+        let Some(time_stamp) = parse_time_real(&time_stamp) else {
+            return Ok(None);
+        };
+
+        Ok(Some(VehicleUnitUsed {
+            time_stamp,
+            manufacturer_code,
+            device_id,
+            vu_software_version: String::from_utf8(vu_software_version.to_vec())?.trim_end_matches(['\0', ' ']).to_string(),
+        }))
+    }
+}
+
+/// Parses the contents of the vehicle units used EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+///
+/// # Returns
+/// The vehicle units used in chronological order, skipping empty slots
+pub fn parse_vehicle_units_used(data: &[u8]) -> Result<Vec<VehicleUnitUsed>, CardReaderError> {
+    let (newest, records) = take_array(data)?;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let records = records.chunks_exact(VEHICLE_UNIT_RECORD_LENGTH).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut vehicle_units = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(vehicle_unit) = VehicleUnitUsed::new(records[(newest + 1 + i) % records.len()])? {
+            vehicle_units.push(vehicle_unit);
+        }
+    }
+    Ok(vehicle_units)
+}
+
+/// Reads the vehicle units used from the Gen2 tachograph application of the card
+///
+/// The EF only exists under the Gen2 application.
+///
+/// # Arguments
+/// - `card` - The smart card to read the vehicle units used from
+///
+/// # Returns
+/// The vehicle units used stored on the card in chronological order
+pub fn read_vehicle_units_used(card: &impl CardTransport) -> Result<Vec<VehicleUnitUsed>, CardReaderError> {
+    let app_id = read_application_identification(card, Some(Generation::Gen2))?;
+    let record_count = app_id.vehicle_unit_records.map_or(NO_OF_CARD_VEHICLE_UNIT_RECORDS, usize::from);
+    select_tachograph_ef(card, Some(Generation::Gen2), TACHOGRAPH_VEHICLE_UNITS_USED_EF)?;
+    let data = read_ef_fully(card, record_ef_length(2, record_count, VEHICLE_UNIT_RECORD_LENGTH)?, None)?;
+    parse_vehicle_units_used(&data)
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::apdu::read_ef_fully;
+use crate::application::{read_application_identification, record_ef_length};
+use crate::card::{select_tachograph_ef, Generation, TACHOGRAPH_VEHICLES_USED_EF};
+use crate::error::CardReaderError;
+use crate::nation::{nation_alpha, nation_name};
+use crate::parse::{check_ring_pointer, parse_bcd_number, parse_odometer, parse_time_real, parse_vehicle_registration, take_array, take_n};
+use crate::transport::CardTransport;
+
+/// Length of a single Gen1 `CardVehicleRecord`
+pub const VEHICLE_RECORD_LENGTH: usize = 31;
+/// Length of a single Gen2 `CardVehicleRecord`, which additionally holds the VIN
+pub const GEN2_VEHICLE_RECORD_LENGTH: usize = 48;
+/// Maximum number of vehicle records stored on a Gen1 card, the card reports its own count in the application identification
+pub const NO_OF_CARD_VEHICLE_RECORDS: usize = 200;
+
+/// A vehicle the card has been used in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VehicleUsed {
+    /// Odometer of the vehicle at the first insertion of the card, in kilometres
+    pub odometer_begin: u32,
+    /// Odometer of the vehicle at the last withdrawal of the card, in kilometres
+    pub odometer_end: u32,
+    pub first_use: DateTime<Utc>,
+    /// Last withdrawal of the card, `None` while the card is still inserted
+    pub last_use: Option<DateTime<Utc>>,
+    pub registration_nation: u8,
+    pub registration_number: String,
+    /// Value of the VU data block counter at the last extraction of the vehicle use
+    pub vu_data_block_counter: u32,
+    /// Vehicle identification number, only present on Gen2 cards
+    pub vin: Option<String>,
+}
+
+impl VehicleUsed {
+    /// Parses a single `CardVehicleRecord`
+    ///
+    /// # Arguments
+    /// - `record` - The record (31 bytes, or 48 bytes for Gen2 records)
+    ///
+    /// # Returns
+    /// The parsed vehicle use, or `None` if the record is an empty slot
+    pub fn new(record: &[u8]) -> Result<Option<Self>, CardReaderError> {
+        let (odometer_begin, remaining) = take_array(record)?;
+        let (odometer_end, remaining) = take_array(remaining)?;
+        let (first_use, remaining) = take_array(remaining)?;
+        let (last_use, remaining) = take_array(remaining)?;
+        let (registration, remaining) = take_n(15, remaining)?;
+        let (vu_data_block_counter, remaining) = take_n(2, remaining)?;
+
+        let Some(first_use) = parse_time_real(&first_use) else {
+            return Ok(None);
+        };
+
+        let (registration_nation, registration_number) = parse_vehicle_registration(registration)?;
+        let vin = match take_n(17, remaining) {
+            Ok((vin, _)) => Some(String::from_utf8(vin.to_vec())?.trim_end_matches(['\0', ' ']).to_string()),
+            Err(_) => None,
+        };
+
+        Ok(Some(VehicleUsed {
+            odometer_begin: parse_odometer(&odometer_begin),
+            odometer_end: parse_odometer(&odometer_end),
+            first_use,
+            last_use: parse_time_real(&last_use),
+            registration_nation,
+            registration_number,
+            vu_data_block_counter: parse_bcd_number(vu_data_block_counter)?,
+            vin,
+        }))
+    }
+
+    /// Returns the name of the nation the vehicle is registered in
+    pub fn registration_nation_name(&self) -> Option<&'static str> {
+        nation_name(self.registration_nation)
+    }
+
+    /// Returns the distinguishing sign of the nation the vehicle is registered in
+    pub fn registration_nation_alpha(&self) -> Option<&'static str> {
+        nation_alpha(self.registration_nation)
+    }
+}
+
+/// Parses the contents of the vehicles used EF
+///
+/// # Arguments
+/// - `data` - The contents of the EF
+/// - `record_length` - The length of a single vehicle record
+/// - `record_count` - The number of vehicle records, from the application identification
+///
+/// # Returns
+/// The vehicles used in chronological order, skipping empty slots
+pub fn parse_vehicles_used(data: &[u8], record_length: usize, record_count: usize) -> Result<Vec<VehicleUsed>, CardReaderError> {
+    let (newest, records) = take_array(data)?;
+    let newest = u16::from_be_bytes(newest) as usize;
+
+    let records = records.chunks_exact(record_length).take(record_count).collect::<Vec<&[u8]>>();
+    check_ring_pointer(newest, records.len())?;
+    let mut vehicles = Vec::new();
+    // The records form a cyclic buffer, so the oldest record is the one following the newest record
+    for i in 0..records.len() {
+        if let Some(vehicle) = VehicleUsed::new(records[(newest + 1 + i) % records.len()])? {
+            vehicles.push(vehicle);
+        }
+    }
+    Ok(vehicles)
+}
+
+/// Reads the vehicles used from the tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the vehicles used from
+/// - `generation` - The generation of the application to read from, or `None` to use the newest one on the card
+///
+/// # Returns
+/// The vehicles used stored on the card in chronological order
+pub fn read_vehicles_used(card: &impl CardTransport, generation: Option<Generation>) -> Result<Vec<VehicleUsed>, CardReaderError> {
+    let app_id = read_application_identification(card, generation)?;
+    let record_count = app_id.vehicle_records as usize;
+    let record_length = match select_tachograph_ef(card, Some(app_id.generation), TACHOGRAPH_VEHICLES_USED_EF)? {
+        Generation::Gen1 => VEHICLE_RECORD_LENGTH,
+        Generation::Gen2 => GEN2_VEHICLE_RECORD_LENGTH,
+    };
+    let data = read_ef_fully(card, record_ef_length(2, record_count, record_length)?, None)?;
+    parse_vehicles_used(&data, record_length, record_count)
+}
@@ -0,0 +1,377 @@
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+
+use crate::apdu::{read_ef, read_ef_fully};
+use crate::card::{
+    select_tachograph_df, select_tachograph_ef, Generation, TACHOGRAPH_CARD_CERTIFICATE_EF, TACHOGRAPH_CARD_MA_CERTIFICATE_EF, TACHOGRAPH_CARD_SIGN_CERTIFICATE_EF,
+    TACHOGRAPH_CA_CERTIFICATE_EF,
+};
+use crate::ddd::CERTIFICATE_LENGTH;
+use crate::error::CardReaderError;
+use crate::parse::{parse_time_real, take_array, take_n};
+use crate::tlv::{find_tlv, parse_tlv, Tlv};
+use crate::transport::CardTransport;
+
+/// Length of a Gen1 RSA modulus and of the signatures made with it
+pub const RSA_MODULUS_LENGTH: usize = 128;
+/// Length of a Gen1 RSA public exponent
+pub const RSA_EXPONENT_LENGTH: usize = 8;
+/// Length of a public key file, e.g. the European root key, holding the key identifier, the modulus and the exponent
+pub const PUBLIC_KEY_LENGTH: usize = 144;
+
+pub const CV_CERTIFICATE_TAG: u16 = 0x7F21;
+pub const CERTIFICATE_BODY_TAG: u16 = 0x7F4E;
+pub const SIGNATURE_TAG: u16 = 0x5F37;
+pub const CERTIFICATE_PROFILE_IDENTIFIER_TAG: u16 = 0x5F29;
+pub const CERTIFICATION_AUTHORITY_REFERENCE_TAG: u16 = 0x42;
+pub const CERTIFICATE_HOLDER_AUTHORISATION_TAG: u16 = 0x5F4C;
+pub const PUBLIC_KEY_TAG: u16 = 0x7F49;
+pub const DOMAIN_PARAMETERS_TAG: u16 = 0x06;
+pub const PUBLIC_POINT_TAG: u16 = 0x86;
+pub const CERTIFICATE_HOLDER_REFERENCE_TAG: u16 = 0x5F20;
+pub const CERTIFICATE_EFFECTIVE_DATE_TAG: u16 = 0x5F25;
+pub const CERTIFICATE_EXPIRATION_DATE_TAG: u16 = 0x5F24;
+
+/// `DigestInfo` prefix of a SHA-1 hash in a PKCS #1 v1.5 signature
+const SHA1_DIGEST_INFO: &[u8] = &[0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2B, 0x0E, 0x03, 0x02, 0x1A, 0x05, 0x00, 0x04, 0x14];
+
+/// A Gen1 RSA public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RsaPublicKey {
+    /// Identifier of the key, i.e. the CHR of its certificate
+    pub key_identifier: [u8; 8],
+    pub modulus: Vec<u8>,
+    pub exponent: Vec<u8>,
+}
+
+impl RsaPublicKey {
+    /// Parses a public key file, e.g. the European root public key published by the ERCA
+    ///
+    /// # Arguments
+    /// - `data` - The key identifier (8 bytes), the modulus (128 bytes) and the exponent (8 bytes)
+    ///
+    /// # Returns
+    /// The parsed public key
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let (key_identifier, remaining) = take_array(data)?;
+        let (modulus, remaining) = take_n(RSA_MODULUS_LENGTH, remaining)?;
+        let (exponent, _) = take_n(RSA_EXPONENT_LENGTH, remaining)?;
+
+        Ok(RsaPublicKey {
+            key_identifier,
+            modulus: modulus.to_vec(),
+            exponent: exponent.to_vec(),
+        })
+    }
+
+    /// Applies the public key to a signature, i.e. computes `signature ^ e mod n`
+    ///
+    /// # Arguments
+    /// - `signature` - The signature
+    ///
+    /// # Returns
+    /// The recovered message, left padded to the length of the modulus
+    pub fn apply(&self, signature: &[u8]) -> Vec<u8> {
+        rsa_apply(&self.modulus, &self.exponent, signature)
+    }
+}
+
+/// Computes `input ^ exponent mod modulus`
+///
+/// # Arguments
+/// - `modulus` - The RSA modulus
+/// - `exponent` - The public or private exponent
+/// - `input` - The message or signature
+///
+/// # Returns
+/// The result, left padded to the length of the modulus
+pub(crate) fn rsa_apply(modulus: &[u8], exponent: &[u8], input: &[u8]) -> Vec<u8> {
+    let result = BigUint::from_bytes_be(input).modpow(&BigUint::from_bytes_be(exponent), &BigUint::from_bytes_be(modulus));
+    left_pad(&result.to_bytes_be(), modulus.len())
+}
+
+/// Left pads a big endian number with zero bytes
+pub(crate) fn left_pad(number: &[u8], length: usize) -> Vec<u8> {
+    let mut padded = vec![0; length.saturating_sub(number.len())];
+    padded.extend_from_slice(number);
+    padded
+}
+
+/// A Gen1 certificate whose content has been recovered and verified with the public key of its issuer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CardCertificate {
+    /// `CertificateProfileIdentifier`
+    pub profile_identifier: u8,
+    /// Certification authority reference, identifying the key of the issuer
+    pub authority_reference: [u8; 8],
+    /// Certificate holder authorisation
+    pub holder_authorisation: [u8; 7],
+    /// End of validity of the certificate, `None` if it doesn't expire
+    pub end_of_validity: Option<DateTime<Utc>>,
+    /// The certified public key, whose identifier is the certificate holder reference
+    pub public_key: RsaPublicKey,
+}
+
+impl CardCertificate {
+    /// Recovers the content of a Gen1 certificate and verifies it with the public key of the issuer
+    ///
+    /// # Arguments
+    /// - `certificate` - The certificate (194 bytes)
+    /// - `issuer` - Public key of the certification authority that issued the certificate
+    ///
+    /// # Returns
+    /// The verified certificate, or `InvalidCertificate` if it was not signed with the issuer key
+    pub fn new(certificate: &[u8], issuer: &RsaPublicKey) -> Result<Self, CardReaderError> {
+        let (signature, remaining) = take_n(RSA_MODULUS_LENGTH, certificate)?;
+        let (non_recoverable, remaining) = take_n(58, remaining)?;
+        let (authority_reference, _) = take_array::<8>(remaining)?;
+        if authority_reference != issuer.key_identifier {
+            return Err(CardReaderError::InvalidCertificate);
+        }
+
+        // The recovered message is `6A || Cr' || H' || BC`, where H' is the SHA-1 hash of the whole content
+        let recovered = issuer.apply(signature);
+        let ([header], recoverable) = take_array(&recovered)?;
+        let (recoverable, remaining) = take_n(106, recoverable)?;
+        let (hash, remaining) = take_n(20, remaining)?;
+        if header != 0x6A || remaining != [0xBC] {
+            return Err(CardReaderError::InvalidCertificate);
+        }
+
+        let content = [recoverable, non_recoverable].concat();
+        if Sha1::digest(&content).as_slice() != hash {
+            return Err(CardReaderError::InvalidCertificate);
+        }
+
+        let ([profile_identifier], remaining) = take_array(&content)?;
+        let (authority_reference, remaining) = take_array(remaining)?;
+        let (holder_authorisation, remaining) = take_array(remaining)?;
+        let (end_of_validity, remaining) = take_array(remaining)?;
+        let (key_identifier, remaining) = take_array(remaining)?;
+        let (modulus, remaining) = take_n(RSA_MODULUS_LENGTH, remaining)?;
+        let (exponent, _) = take_n(RSA_EXPONENT_LENGTH, remaining)?;
+
+        Ok(CardCertificate {
+            profile_identifier,
+            authority_reference,
+            holder_authorisation,
+            end_of_validity: if end_of_validity == [0xFF; 4] { None } else { parse_time_real(&end_of_validity) },
+            public_key: RsaPublicKey {
+                key_identifier,
+                modulus: modulus.to_vec(),
+                exponent: exponent.to_vec(),
+            },
+        })
+    }
+
+    /// Returns whether the certificate has not expired
+    ///
+    /// # Arguments
+    /// - `now` - The time to check the validity at
+    pub fn is_certificate_valid(&self, now: DateTime<Utc>) -> bool {
+        self.end_of_validity.is_none_or(|end_of_validity| now <= end_of_validity)
+    }
+}
+
+/// Verifies the signature of a data block, e.g. an EF signed by the card during a download
+///
+/// # Arguments
+/// - `data` - The signed data
+/// - `signature` - The PKCS #1 v1.5 SHA-1 signature of the data (128 bytes)
+/// - `cert` - The verified certificate of the card that signed the data
+///
+/// # Returns
+/// Whether the signature is valid
+pub fn verify_block(data: &[u8], signature: &[u8], cert: &CardCertificate) -> bool {
+    if signature.len() != RSA_MODULUS_LENGTH {
+        return false;
+    }
+
+    let recovered = cert.public_key.apply(signature);
+    let digest_info = [SHA1_DIGEST_INFO, Sha1::digest(data).as_slice()].concat();
+    let padding_length = RSA_MODULUS_LENGTH - digest_info.len() - 3;
+
+    let mut expected = vec![0x00, 0x01];
+    expected.extend(std::iter::repeat_n(0xFF, padding_length));
+    expected.push(0x00);
+    expected.extend_from_slice(&digest_info);
+    recovered == expected
+}
+
+/// Reads the certificates of the Gen1 tachograph application and verifies the chain up to the root key
+///
+/// The CA certificate is verified with the root key, and the card certificate with the member state key certified
+/// by the CA certificate.
+///
+/// # Arguments
+/// - `card` - The smart card to read the certificates from
+/// - `root_key` - The European root public key
+///
+/// # Returns
+/// The verified card certificate
+pub fn read_card_certificate(card: &impl CardTransport, root_key: &RsaPublicKey) -> Result<CardCertificate, CardReaderError> {
+    select_tachograph_ef(card, Some(Generation::Gen1), TACHOGRAPH_CA_CERTIFICATE_EF)?;
+    let ca_certificate = read_ef_fully(card, CERTIFICATE_LENGTH, None)?;
+    let member_state_certificate = CardCertificate::new(&ca_certificate, root_key)?;
+
+    select_tachograph_ef(card, Some(Generation::Gen1), TACHOGRAPH_CARD_CERTIFICATE_EF)?;
+    let card_certificate = read_ef_fully(card, CERTIFICATE_LENGTH, None)?;
+    CardCertificate::new(&card_certificate, &member_state_certificate.public_key)
+}
+
+/// Elliptic curve of a Gen2 public key, identified by the OID of its domain parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum EccCurve {
+    NistP256,
+    NistP384,
+    NistP521,
+    BrainpoolP256r1,
+    BrainpoolP384r1,
+    BrainpoolP512r1,
+    /// A curve not allowed by the specification, with the encoded OID
+    Unknown(Vec<u8>),
+}
+
+impl From<&[u8]> for EccCurve {
+    fn from(oid: &[u8]) -> Self {
+        match oid {
+            [0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07] => EccCurve::NistP256,
+            [0x2B, 0x81, 0x04, 0x00, 0x22] => EccCurve::NistP384,
+            [0x2B, 0x81, 0x04, 0x00, 0x23] => EccCurve::NistP521,
+            [0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07] => EccCurve::BrainpoolP256r1,
+            [0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0B] => EccCurve::BrainpoolP384r1,
+            [0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0D] => EccCurve::BrainpoolP512r1,
+            other => EccCurve::Unknown(other.to_vec()),
+        }
+    }
+}
+
+/// A Gen2 ECC public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EccPublicKey {
+    pub curve: EccCurve,
+    /// The public point, uncompressed (`04 || x || y`)
+    pub public_point: Vec<u8>,
+}
+
+/// A Gen2 card verifiable certificate, e.g. the `CardMA_Certificate` or the `CardSignCertificate`
+///
+/// The certificate is only parsed, its signature is not verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EccCertificate {
+    /// `CertificateProfileIdentifier`
+    pub profile_identifier: u8,
+    /// Certification authority reference, identifying the key of the issuer
+    pub authority_reference: [u8; 8],
+    /// Certificate holder authorisation
+    pub holder_authorisation: [u8; 7],
+    pub public_key: EccPublicKey,
+    /// Certificate holder reference, identifying the certified key
+    pub holder_reference: [u8; 8],
+    pub effective_date: Option<DateTime<Utc>>,
+    pub expiration_date: Option<DateTime<Utc>>,
+    /// The encoded certificate body, i.e. the data the signature was computed over
+    pub body: Vec<u8>,
+    /// The ECDSA signature of the body, `r || s`
+    pub signature: Vec<u8>,
+}
+
+impl EccCertificate {
+    /// Parses a Gen2 certificate
+    ///
+    /// # Arguments
+    /// - `data` - The contents of the certificate EF
+    ///
+    /// # Returns
+    /// The parsed certificate, or `InvalidCertificate` if a mandatory data object is missing or malformed
+    pub fn new(data: &[u8]) -> Result<Self, CardReaderError> {
+        let objects = parse_tlv(data)?;
+        let certificate = find_tlv(&objects, CV_CERTIFICATE_TAG).ok_or(CardReaderError::InvalidCertificate)?.children()?;
+        let body = find_tlv(&certificate, CERTIFICATE_BODY_TAG).ok_or(CardReaderError::InvalidCertificate)?;
+        let signature = find_tlv(&certificate, SIGNATURE_TAG).ok_or(CardReaderError::InvalidCertificate)?;
+
+        let elements = body.children()?;
+        let public_key = find_element(&elements, PUBLIC_KEY_TAG)?.children()?;
+        let curve = EccCurve::from(find_element(&public_key, DOMAIN_PARAMETERS_TAG)?.value.as_slice());
+        let public_point = find_element(&public_key, PUBLIC_POINT_TAG)?.value.clone();
+
+        Ok(EccCertificate {
+            profile_identifier: fixed_element::<1>(&elements, CERTIFICATE_PROFILE_IDENTIFIER_TAG)?[0],
+            authority_reference: fixed_element(&elements, CERTIFICATION_AUTHORITY_REFERENCE_TAG)?,
+            holder_authorisation: fixed_element(&elements, CERTIFICATE_HOLDER_AUTHORISATION_TAG)?,
+            public_key: EccPublicKey { curve, public_point },
+            holder_reference: fixed_element(&elements, CERTIFICATE_HOLDER_REFERENCE_TAG)?,
+            effective_date: parse_time_real(&fixed_element(&elements, CERTIFICATE_EFFECTIVE_DATE_TAG)?),
+            expiration_date: parse_time_real(&fixed_element(&elements, CERTIFICATE_EXPIRATION_DATE_TAG)?),
+            body: body.to_bytes(),
+            signature: signature.value.clone(),
+        })
+    }
+
+    /// Returns whether the certificate is in effect and has not expired
+    ///
+    /// # Arguments
+    /// - `now` - The time to check the validity at
+    pub fn is_certificate_valid(&self, now: DateTime<Utc>) -> bool {
+        self.effective_date.is_none_or(|effective_date| effective_date <= now) && self.expiration_date.is_none_or(|expiration_date| now <= expiration_date)
+    }
+}
+
+/// Finds a mandatory data object of a certificate
+fn find_element(elements: &[Tlv], tag: u16) -> Result<&Tlv, CardReaderError> {
+    find_tlv(elements, tag).ok_or(CardReaderError::InvalidCertificate)
+}
+
+/// Finds a mandatory fixed length data object of a certificate and returns its value
+fn fixed_element<const N: usize>(elements: &[Tlv], tag: u16) -> Result<[u8; N], CardReaderError> {
+    find_element(elements, tag)?.value.as_slice().try_into().map_err(|_| CardReaderError::InvalidCertificate)
+}
+
+/// Reads and parses a certificate from the Gen2 tachograph application of the card
+///
+/// # Arguments
+/// - `card` - The smart card to read the certificate from
+/// - `ef` - The file identifier of the certificate EF
+///
+/// # Returns
+/// The parsed certificate
+fn read_ecc_certificate(card: &impl CardTransport, ef: &[u8]) -> Result<EccCertificate, CardReaderError> {
+    select_tachograph_df(card, Some(Generation::Gen2))?;
+    let data = read_ef(card, [ef[0], ef[1]])?;
+    EccCertificate::new(&data)
+}
+
+/// Reads the `CardMA_Certificate`, certifying the key used for mutual authentication
+///
+/// # Arguments
+/// - `card` - The smart card to read the certificate from
+///
+/// # Returns
+/// The parsed certificate
+pub fn read_card_ma_certificate(card: &impl CardTransport) -> Result<EccCertificate, CardReaderError> {
+    read_ecc_certificate(card, TACHOGRAPH_CARD_MA_CERTIFICATE_EF)
+}
+
+/// Reads the `CardSignCertificate`, certifying the key used to sign downloaded data
+///
+/// # Arguments
+/// - `card` - The smart card to read the certificate from
+///
+/// # Returns
+/// The parsed certificate
+pub fn read_card_sign_certificate(card: &impl CardTransport) -> Result<EccCertificate, CardReaderError> {
+    read_ecc_certificate(card, TACHOGRAPH_CARD_SIGN_CERTIFICATE_EF)
+}
+
+/// Reads the Gen2 `CA_Certificate`, certifying the member state key that signed the card certificates
+///
+/// # Arguments
+/// - `card` - The smart card to read the certificate from
+///
+/// # Returns
+/// The parsed certificate
+pub fn read_gen2_ca_certificate(card: &impl CardTransport) -> Result<EccCertificate, CardReaderError> {
+    read_ecc_certificate(card, TACHOGRAPH_CA_CERTIFICATE_EF)
+}
@@ -0,0 +1,58 @@
+use driver_card_reader::activity::{decode_activity_change, Activity, ActivityChange, Slot};
+
+#[test]
+fn decodes_break_at_midnight() {
+    let change = decode_activity_change(0x0000);
+    assert_eq!(
+        change,
+        ActivityChange { slot: Slot::Driver, crew: false, card_inserted: true, activity: Activity::Break, minute_of_day: 0 }
+    );
+}
+
+#[test]
+fn decodes_driving_single_in_driver_slot() {
+    // 0 0 0 11 00111100000: driving at 08:00
+    let change = decode_activity_change(0x19E0);
+    assert_eq!(
+        change,
+        ActivityChange { slot: Slot::Driver, crew: false, card_inserted: true, activity: Activity::Driving, minute_of_day: 480 }
+    );
+}
+
+#[test]
+fn decodes_work_crewed_in_co_driver_slot() {
+    // 1 1 0 10 10110011111: work at 23:59
+    let change = decode_activity_change(0xD59F);
+    assert_eq!(
+        change,
+        ActivityChange { slot: Slot::CoDriver, crew: true, card_inserted: true, activity: Activity::Work, minute_of_day: 1439 }
+    );
+}
+
+#[test]
+fn decodes_card_not_inserted() {
+    // 0 0 1 01 00001111000: available at 02:00 with no card in the slot
+    let change = decode_activity_change(0x2878);
+    assert_eq!(
+        change,
+        ActivityChange { slot: Slot::Driver, crew: false, card_inserted: false, activity: Activity::Available, minute_of_day: 120 }
+    );
+}
+
+#[test]
+fn decodes_every_activity() {
+    let activities = [0x0000, 0x0800, 0x1000, 0x1800].map(|raw| decode_activity_change(raw).activity);
+    assert_eq!(activities, [Activity::Break, Activity::Available, Activity::Work, Activity::Driving]);
+}
+
+#[test]
+fn minutes_use_the_low_eleven_bits() {
+    // Bit 10 is the most significant bit of the minutes, not part of the activity
+    let change = decode_activity_change(0x0400);
+    assert_eq!(change.activity, Activity::Break);
+    assert_eq!(change.minute_of_day, 1024);
+
+    let change = decode_activity_change(0x07FF);
+    assert_eq!(change.activity, Activity::Break);
+    assert_eq!(change.minute_of_day, 0x07FF);
+}
@@ -0,0 +1,52 @@
+use chrono::NaiveDate;
+use driver_card_reader::parse::{decode_bcd, encode_bcd, parse_bcd_number, parse_datef};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn decoding_inverts_encoding() {
+    let mut rng = StdRng::seed_from_u64(0x7AC0);
+    for _ in 0..1000 {
+        let length = rng.gen_range(0..16) * 2;
+        let digits = (0..length).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect::<String>();
+        assert_eq!(decode_bcd(&encode_bcd(&digits).unwrap()).unwrap(), digits);
+    }
+}
+
+#[test]
+fn encodes_every_byte_value() {
+    for number in 0..100 {
+        let digits = format!("{:02}", number);
+        let encoded = encode_bcd(&digits).unwrap();
+        assert_eq!(encoded, [((number / 10) << 4) | (number % 10)]);
+        assert_eq!(parse_bcd_number(&encoded).unwrap(), number as u32);
+    }
+}
+
+#[test]
+fn pads_odd_length_values() {
+    assert_eq!(encode_bcd("123").unwrap(), [0x01, 0x23]);
+    assert!(encode_bcd("").unwrap().is_empty());
+}
+
+#[test]
+fn rejects_non_digits() {
+    assert!(encode_bcd("12A4").is_err());
+    assert!(decode_bcd(&[0x1A]).is_err());
+}
+
+#[test]
+fn encodes_birth_date_as_year_month_day() {
+    let datef = encode_bcd("19850423").unwrap();
+    assert_eq!(datef, [0x19, 0x85, 0x04, 0x23]);
+    assert_eq!(parse_datef(&datef.try_into().unwrap()).unwrap(), NaiveDate::from_ymd_opt(1985, 4, 23));
+}
+
+#[test]
+fn parses_birth_date_layout() {
+    assert_eq!(parse_datef(&[0x20, 0x00, 0x02, 0x29]).unwrap(), NaiveDate::from_ymd_opt(2000, 2, 29));
+    assert_eq!(parse_datef(&[0x00, 0x00, 0x00, 0x00]).unwrap(), None);
+    assert_eq!(parse_datef(&[0xFF, 0xFF, 0xFF, 0xFF]).unwrap(), None);
+    assert!(parse_datef(&[0x19, 0x85, 0x13, 0x01]).is_err());
+    assert!(parse_datef(&[0x19, 0x85, 0x02, 0x30]).is_err());
+}
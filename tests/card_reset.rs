@@ -0,0 +1,50 @@
+#![cfg(feature = "pcsc")]
+
+mod common;
+
+use common::{hex, MockCard};
+use driver_card_reader::apdu::{read_ef_fully, transmit_select_ef_under_df_apdu};
+use driver_card_reader::card::{select_tachograph_df, Generation};
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::transport::retry_after_reset;
+
+const EF_LENGTH: u16 = 600;
+
+/// A Gen1 card with a driver activity EF that takes three READ BINARY commands to read
+///
+/// The APDUs of [`read_activity`] are the SELECT of the DF (0), the SELECT of the EF (1) and the READ BINARY commands
+/// (2 to 4).
+fn card() -> MockCard {
+    MockCard::default().respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[]).ef([0x05, 0x04], &contents())
+}
+
+fn contents() -> Vec<u8> {
+    (0..EF_LENGTH).map(|i| i as u8).collect()
+}
+
+fn read_activity(card: &MockCard) -> Result<Vec<u8>, CardReaderError> {
+    select_tachograph_df(card, Some(Generation::Gen1))?;
+    transmit_select_ef_under_df_apdu(card, &[0x05, 0x04])?;
+    read_ef_fully(card, EF_LENGTH, None)
+}
+
+#[test]
+fn fails_a_read_interrupted_by_a_reset() {
+    let card = card().reset_before(3);
+    assert!(matches!(read_activity(&card), Err(CardReaderError::CardReset)));
+    assert_eq!(card.reconnects(), 1);
+}
+
+#[test]
+fn restarts_a_read_interrupted_by_a_reset() {
+    let card = card().reset_before(3);
+    assert_eq!(retry_after_reset(&card, read_activity).unwrap(), contents());
+    assert_eq!(card.reconnects(), 1);
+}
+
+#[test]
+fn retries_selecting_the_df_after_a_reset() {
+    let card = card().reset_before(0);
+    assert_eq!(read_activity(&card).unwrap(), contents());
+    assert_eq!(card.reconnects(), 1);
+}
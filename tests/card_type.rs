@@ -0,0 +1,27 @@
+mod common;
+
+use common::{hex, MockCard};
+use driver_card_reader::application::{read_card_type, CardType};
+use driver_card_reader::card::Generation;
+use driver_card_reader::driver_card::read_full_card;
+use driver_card_reader::error::CardReaderError;
+
+/// A Gen1 card with the given application identification
+fn card(application_identification: &str) -> MockCard {
+    MockCard::default().respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[]).ef([0x05, 0x01], &hex(application_identification))
+}
+
+#[test]
+fn reads_the_type_of_company_and_control_cards() {
+    // Type, structure version and the 2-byte record count of a company or control card
+    for (application_identification, card_type) in [("04 00 01 00 FF", CardType::Company), ("03 00 01 00 FF", CardType::Control)] {
+        let card = card(application_identification);
+        assert_eq!(read_card_type(&card, Some(Generation::Gen1)).unwrap(), card_type);
+    }
+}
+
+#[test]
+fn refuses_to_read_a_company_card_in_full() {
+    let card = card("04 00 01 00 FF");
+    assert!(matches!(read_full_card(&card, Some(Generation::Gen1)), Err(CardReaderError::UnsupportedCardType(CardType::Company))));
+}
@@ -0,0 +1,49 @@
+use driver_card_reader::card::Generation;
+use driver_card_reader::parse::{decode_codepage_string, CODE_PAGE_UTF16_BE, CODE_PAGE_UTF8};
+
+/// Builds a 36 byte `Name` field from a code page byte and the encoded characters, padded with a byte
+fn name_field(code_page: u8, encoded: &[u8], padding: u8) -> Vec<u8> {
+    let mut field = vec![code_page];
+    field.extend_from_slice(encoded);
+    field.resize(36, padding);
+    field
+}
+
+#[test]
+fn decodes_utf8_names() {
+    let field = name_field(CODE_PAGE_UTF8, "Müller-Šťastný".as_bytes(), 0x20);
+    assert_eq!(decode_codepage_string(&field, Generation::Gen2), "Müller-Šťastný");
+}
+
+#[test]
+fn decodes_utf8_names_padded_with_zeros() {
+    let field = name_field(CODE_PAGE_UTF8, "Ωμέγα".as_bytes(), 0x00);
+    assert_eq!(decode_codepage_string(&field, Generation::Gen2), "Ωμέγα");
+}
+
+#[test]
+fn decodes_utf16be_names_without_a_trailing_replacement_character() {
+    // Padded with U+0000 and with U+0020, leaving a single padding byte at the end of the field
+    for (padding, byte) in [('\0', 0x00), (' ', 0x20)] {
+        let padded = format!("{:\0<17}", "Ærøskøbing").replace('\0', &padding.to_string());
+        let encoded = padded.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>();
+        let field = name_field(CODE_PAGE_UTF16_BE, &encoded, byte);
+        let decoded = decode_codepage_string(&field, Generation::Gen2);
+        assert_eq!(decoded, "Ærøskøbing");
+        assert!(!decoded.contains('\u{FFFD}'));
+    }
+}
+
+#[test]
+fn decodes_utf16be_names_filling_the_field() {
+    // 17 characters take 34 of the 35 bytes after the code page byte
+    let encoded = "Александр Пушкин!".encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>();
+    let field = name_field(CODE_PAGE_UTF16_BE, &encoded, 0x00);
+    assert_eq!(decode_codepage_string(&field, Generation::Gen2), "Александр Пушкин!");
+}
+
+#[test]
+fn decodes_gen1_names_with_their_code_page() {
+    let field = name_field(1, b"M\xFCller", 0x20);
+    assert_eq!(decode_codepage_string(&field, Generation::Gen1), "Müller");
+}
@@ -0,0 +1,111 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::transport::CardTransport;
+
+/// A card transport answering APDUs from a map of canned responses
+///
+/// APDUs without a canned response select one of the EFs added with [`MockCard::ef`] and read from the selected one.
+/// Other APDUs are answered with `6A 82` (file not found).
+#[derive(Default)]
+pub struct MockCard {
+    responses: HashMap<Vec<u8>, Vec<u8>>,
+    efs: HashMap<[u8; 2], Vec<u8>>,
+    selected_ef: RefCell<Option<[u8; 2]>>,
+    reset_before: Cell<Option<usize>>,
+    transmitted: Cell<usize>,
+    reconnects: Cell<usize>,
+}
+
+impl MockCard {
+    /// Adds a successful response, appending the `90 00` status word to the data
+    #[allow(dead_code)]
+    pub fn respond(mut self, apdu: &[u8], data: &[u8]) -> Self {
+        let mut response = data.to_vec();
+        response.extend_from_slice(&[0x90, 0x00]);
+        self.responses.insert(apdu.to_vec(), response);
+        self
+    }
+
+    /// Adds an EF that can be selected with `00 A4 02 0C` and read with READ BINARY
+    #[allow(dead_code)]
+    pub fn ef(mut self, id: [u8; 2], contents: &[u8]) -> Self {
+        self.efs.insert(id, contents.to_vec());
+        self
+    }
+
+    /// Simulates a reset of the card before the nth APDU (counting from 0), which fails with `SCARD_W_RESET_CARD`
+    ///
+    /// The card can then be reconnected, which leaves it with no EF selected.
+    #[allow(dead_code)]
+    pub fn reset_before(self, apdu_index: usize) -> Self {
+        self.reset_before.set(Some(apdu_index));
+        self
+    }
+
+    /// Returns how many times the card was reconnected
+    #[allow(dead_code)]
+    pub fn reconnects(&self) -> usize {
+        self.reconnects.get()
+    }
+
+    /// Adds a response consisting of only the given status word
+    #[allow(dead_code)]
+    pub fn respond_status(mut self, apdu: &[u8], sw1: u8, sw2: u8) -> Self {
+        self.responses.insert(apdu.to_vec(), vec![sw1, sw2]);
+        self
+    }
+}
+
+impl CardTransport for MockCard {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        let index = self.transmitted.replace(self.transmitted.get() + 1);
+        #[cfg(feature = "pcsc")]
+        if self.reset_before.get() == Some(index) {
+            self.reset_before.set(None);
+            *self.selected_ef.borrow_mut() = None;
+            return Err(CardReaderError::Pcsc(pcsc::Error::ResetCard));
+        }
+        #[cfg(not(feature = "pcsc"))]
+        let _ = index;
+
+        if let Some(response) = self.responses.get(apdu) {
+            return Ok(response.clone());
+        }
+
+        match apdu {
+            [0x00, 0xA4, 0x02, 0x0C, 0x02, id @ ..] if self.efs.contains_key(id) => {
+                *self.selected_ef.borrow_mut() = Some([id[0], id[1]]);
+                Ok(vec![0x90, 0x00])
+            }
+            [0x00, 0xB0, p1, p2, le] => {
+                let Some(contents) = self.selected_ef.borrow().and_then(|id| self.efs.get(&id).cloned()) else {
+                    return Ok(vec![0x69, 0x86]);
+                };
+                let offset = u16::from_be_bytes([*p1, *p2]) as usize;
+                if offset >= contents.len() {
+                    return Ok(vec![0x6B, 0x00]);
+                }
+                let mut response = contents[offset..contents.len().min(offset + *le as usize)].to_vec();
+                response.extend_from_slice(&[0x90, 0x00]);
+                Ok(response)
+            }
+            _ => Ok(vec![0x6A, 0x82]),
+        }
+    }
+
+    fn reconnect(&self) -> Result<(), CardReaderError> {
+        self.reconnects.set(self.reconnects.get() + 1);
+        Ok(())
+    }
+}
+
+/// Decodes a hex string, ignoring whitespace
+pub fn hex(data: &str) -> Vec<u8> {
+    let digits = data.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>();
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).unwrap())
+        .collect()
+}
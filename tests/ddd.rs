@@ -0,0 +1,98 @@
+use driver_card_reader::ddd::{write_ddd, FileCard, DDD_APPENDIX_DATA, DDD_APPENDIX_GEN2_DATA, DDD_APPENDIX_GEN2_SIGNATURE, DDD_APPENDIX_SIGNATURE};
+
+/// `ApplicationIdentification` of a Gen1 application holding one record of each kind and 56 bytes of activity
+const GEN1_APPLICATION_IDENTIFICATION: &[u8] = &[0x01, 0x00, 0x01, 0x01, 0x01, 0x00, 0x38, 0x00, 0x01, 0x01];
+
+/// `ApplicationIdentification` of a Gen2 application holding one record of each kind and 56 bytes of activity
+const GEN2_APPLICATION_IDENTIFICATION: &[u8] = &[0x01, 0x01, 0x00, 0x01, 0x01, 0x00, 0x38, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01];
+
+/// Appends a block of a download
+fn block(ddd: &mut Vec<u8>, id: [u8; 2], appendix: u8, data: &[u8]) {
+    ddd.extend_from_slice(&id);
+    ddd.push(appendix);
+    ddd.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    ddd.extend_from_slice(data);
+}
+
+/// Appends an EF of the given length filled with its id, and the signature of the EF if it is signed
+fn ef(ddd: &mut Vec<u8>, id: [u8; 2], length: usize, appendices: (u8, Option<u8>)) {
+    block(ddd, id, appendices.0, &vec![id[1]; length]);
+    if let Some(signature_appendix) = appendices.1 {
+        block(ddd, id, signature_appendix, &vec![0x5A; if appendices.0 == DDD_APPENDIX_DATA { 128 } else { 64 }]);
+    }
+}
+
+/// Builds a download of the MF and Gen1 application EFs in the order `write_ddd` writes them
+fn gen1_download() -> Vec<u8> {
+    let signed = (DDD_APPENDIX_DATA, Some(DDD_APPENDIX_SIGNATURE));
+    let unsigned = (DDD_APPENDIX_DATA, None);
+
+    let mut ddd = Vec::new();
+    ef(&mut ddd, [0x00, 0x02], 25, unsigned);
+    ef(&mut ddd, [0x00, 0x05], 8, unsigned);
+    block(&mut ddd, [0x05, 0x01], DDD_APPENDIX_DATA, GEN1_APPLICATION_IDENTIFICATION);
+    block(&mut ddd, [0x05, 0x01], DDD_APPENDIX_SIGNATURE, &[0x5A; 128]);
+    ef(&mut ddd, [0xC1, 0x00], 194, unsigned);
+    ef(&mut ddd, [0xC1, 0x08], 194, unsigned);
+    for (id, length) in [
+        ([0x05, 0x20], 143),
+        ([0x05, 0x0E], 4),
+        ([0x05, 0x21], 53),
+        ([0x05, 0x02], 6 * 24),
+        ([0x05, 0x03], 2 * 24),
+        ([0x05, 0x04], 4 + 56),
+        ([0x05, 0x05], 2 + 31),
+        ([0x05, 0x06], 1 + 10),
+        ([0x05, 0x07], 19),
+        ([0x05, 0x08], 46),
+    ] {
+        ef(&mut ddd, id, length, signed);
+    }
+    ddd
+}
+
+#[test]
+fn downloads_a_gen1_card() {
+    let ddd = gen1_download();
+    let card = FileCard::new(&ddd).unwrap();
+
+    let mut written = Vec::new();
+    write_ddd(&card, &mut written).unwrap();
+    assert_eq!(written, ddd);
+}
+
+#[test]
+fn downloads_the_gen2_application_of_a_gen2_card() {
+    let signed = (DDD_APPENDIX_GEN2_DATA, Some(DDD_APPENDIX_GEN2_SIGNATURE));
+    let unsigned = (DDD_APPENDIX_GEN2_DATA, None);
+
+    let mut ddd = gen1_download();
+    block(&mut ddd, [0x05, 0x01], DDD_APPENDIX_GEN2_DATA, GEN2_APPLICATION_IDENTIFICATION);
+    block(&mut ddd, [0x05, 0x01], DDD_APPENDIX_GEN2_SIGNATURE, &[0x5A; 64]);
+    // ECC certificates and an application without the optional link certificate and most of the Gen2v2 EFs
+    ef(&mut ddd, [0xC1, 0x01], 204, unsigned);
+    ef(&mut ddd, [0xC1, 0x08], 205, unsigned);
+    for (id, length) in [
+        ([0x05, 0x20], 143),
+        ([0x05, 0x0E], 4),
+        ([0x05, 0x21], 53),
+        ([0x05, 0x02], 11 * 24),
+        ([0x05, 0x03], 2 * 24),
+        ([0x05, 0x04], 4 + 56),
+        ([0x05, 0x05], 2 + 48),
+        ([0x05, 0x06], 2 + 21),
+        ([0x05, 0x07], 19),
+        ([0x05, 0x08], 46),
+        ([0x05, 0x22], 2 + 5),
+        ([0x05, 0x23], 2 + 10),
+        ([0x05, 0x24], 2 + 18),
+        ([0x05, 0x26], 2 + 5),
+    ] {
+        ef(&mut ddd, id, length, signed);
+    }
+    let card = FileCard::new(&ddd).unwrap();
+
+    let mut written = Vec::new();
+    write_ddd(&card, &mut written).unwrap();
+    assert_eq!(written, ddd);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::{hex, MockCard};
+use driver_card_reader::activity::{Activity, Slot};
+use driver_card_reader::card::Generation;
+use driver_card_reader::driver_card::read_full_card;
+
+/// `ApplicationIdentification` of a Gen1 driver card holding one record of each kind and 56 bytes of activity
+const APPLICATION_IDENTIFICATION: &str = "01 00 01 01 01 00 38 00 01 01";
+
+/// `CardIdentification` followed by `DriverCardHolderIdentification` of a Finnish driver card
+const IDENTIFICATION: &str = "
+    12 46 49 30 30 30 30 30 30 31 32 33 34 35 36 30 30 01 54 72 61 66 69 63 6F 6D 20 20 20 20 20 20
+    20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 5E 3C 9C 00 5E 3C 9C 00 67 F8 5E
+    00 01 56 49 52 54 41 4E 45 4E 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20
+    20 20 20 20 20 01 4D 41 54 54 49 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20
+    20 20 20 20 20 20 20 20 20 19 85 04 23 66 69
+";
+
+/// `LastCardDownload` at 2024-03-05 08:00 UTC
+const CARD_DOWNLOAD: &str = "65 E6 D1 00";
+
+/// `CardDrivingLicenceInformation` of a licence issued by Traficom
+const DRIVING_LICENCE_INFO: &str = "
+    01 54 52 41 46 49 43 4F 4D 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20
+    20 20 20 20 12 46 49 31 32 33 34 35 36 37 20 20 20 20 20 20 20
+";
+
+/// `CardEventData` with a single card insertion while driving
+const EVENTS: &str = "
+    05 65 E5 62 E8 65 E5 63 60 12 01 41 42 43 2D 31 32 33 20 20 20 20 20 20 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+";
+
+/// `CardFaultData` with a single sensor fault
+const FAULTS: &str = "
+    35 65 E5 74 F4 65 E5 76 20 12 01 41 42 43 2D 31 32 33 20 20 20 20 20 20 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+";
+
+/// `CardDriverActivity` with one day: break, driving from 06:00, work from 10:00 and break from 10:30
+const DRIVER_ACTIVITY: &str = "
+    00 00 00 00 00 00 00 14 65 E5 0F 00 00 12 01 2C 00 00 19 68 12 58 02 76 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+";
+
+/// `CardVehiclesUsed` with one vehicle driven 300 km
+const VEHICLES_USED: &str = "
+    00 00 01 E2 40 01 E3 6C 65 E5 63 60 65 E5 A2 A8 12 01 41 42 43 2D 31 32 33 20 20 20 20 20 20 00
+    07
+";
+
+/// `CardPlaceDailyWorkPeriod` with the beginning of a daily work period in Finland
+const PLACES: &str = "00 65 E5 63 60 00 12 00 01 E2 40";
+
+/// `CardCurrentUse` of a session opened at 2024-03-04 06:00 UTC
+const CURRENT_USAGE: &str = "65 E5 63 60 12 01 41 42 43 2D 31 32 33 20 20 20 20 20 20";
+
+/// `CardControlActivityDataRecord` of a card that has not been controlled
+const CONTROL_ACTIVITY: &str = "
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    00 00 00 00 00 00 00 00 00 00 00 00 00 00
+";
+
+/// A Gen1 driver card preloaded with the EFs above, without the specific conditions EF of early cards
+fn gen1_card() -> MockCard {
+    MockCard::default()
+        .respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[])
+        .ef([0x05, 0x01], &hex(APPLICATION_IDENTIFICATION))
+        .ef([0x05, 0x20], &hex(IDENTIFICATION))
+        .ef([0x05, 0x0E], &hex(CARD_DOWNLOAD))
+        .ef([0x05, 0x21], &hex(DRIVING_LICENCE_INFO))
+        .ef([0x05, 0x02], &hex(EVENTS))
+        .ef([0x05, 0x03], &hex(FAULTS))
+        .ef([0x05, 0x04], &hex(DRIVER_ACTIVITY))
+        .ef([0x05, 0x05], &hex(VEHICLES_USED))
+        .ef([0x05, 0x06], &hex(PLACES))
+        .ef([0x05, 0x07], &hex(CURRENT_USAGE))
+        .ef([0x05, 0x08], &hex(CONTROL_ACTIVITY))
+}
+
+#[test]
+fn reads_full_gen1_card() {
+    let card = read_full_card(&gen1_card(), None).unwrap();
+
+    assert_eq!(card.generation, Generation::Gen1);
+    assert_eq!(card.identification.card_number.raw, "FI00000012345600");
+    assert_eq!(card.identification.last_name, "VIRTANEN");
+    assert_eq!(card.identification.first_name, "MATTI");
+    assert_eq!(card.identification.birth_date, NaiveDate::from_ymd_opt(1985, 4, 23));
+    assert_eq!(card.certificate_expiry, None);
+    assert_eq!(card.last_download, Some(Utc.with_ymd_and_hms(2024, 3, 5, 8, 0, 0).unwrap()));
+
+    assert_eq!(card.driving_licence_info.issuing_authority, "TRAFICOM");
+    assert_eq!(card.driving_licence_info.issuing_nation_alpha(), Some("FIN"));
+    assert_eq!(card.driving_licence_info.licence_number, "FI1234567");
+
+    assert_eq!(card.events.len(), 1);
+    assert_eq!(card.events[0].description, "Card insertion while driving");
+    assert_eq!(card.events[0].begin, Utc.with_ymd_and_hms(2024, 3, 4, 5, 58, 0).unwrap());
+    assert_eq!(card.events[0].end, Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap());
+    assert_eq!(card.events[0].vehicle_registration, "ABC-123");
+
+    assert_eq!(card.faults.len(), 1);
+    assert_eq!(card.faults[0].description, "Sensor fault");
+    assert_eq!(card.faults[0].begin, Utc.with_ymd_and_hms(2024, 3, 4, 7, 15, 0).unwrap());
+
+    assert_eq!(card.activity.len(), 1);
+    let day = &card.activity[0];
+    assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+    assert_eq!(day.presence_counter, 12);
+    assert_eq!(day.day_distance, 300);
+    let changes = day.changes.iter().map(|change| (change.minute_of_day, change.activity)).collect::<Vec<_>>();
+    assert_eq!(changes, [(0, Activity::Break), (360, Activity::Driving), (600, Activity::Work), (630, Activity::Break)]);
+    assert!(day.changes.iter().all(|change| change.slot == Slot::Driver && change.card_inserted));
+
+    assert_eq!(card.vehicles_used.len(), 1);
+    let vehicle = &card.vehicles_used[0];
+    assert_eq!(vehicle.odometer_begin, 123456);
+    assert_eq!(vehicle.odometer_end, 123756);
+    assert_eq!(vehicle.first_use, Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap());
+    assert_eq!(vehicle.last_use, Some(Utc.with_ymd_and_hms(2024, 3, 4, 10, 30, 0).unwrap()));
+    assert_eq!(vehicle.registration_number, "ABC-123");
+    assert_eq!(vehicle.vu_data_block_counter, 7);
+    assert_eq!(vehicle.vin, None);
+
+    assert_eq!(card.places.len(), 1);
+    assert_eq!(card.places[0].entry_time, Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap());
+    assert_eq!(card.places[0].country, 0x12);
+    assert_eq!(card.places[0].odometer, 123456);
+
+    assert_eq!(card.current_usage.session_open_time, Some(Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap()));
+    assert_eq!(card.current_usage.vehicle_registration, "ABC-123");
+    assert_eq!(card.control_activity, None);
+    assert!(card.specific_conditions.is_empty());
+
+    assert_eq!(card.vehicle_units_used, None);
+    assert_eq!(card.places_authentication, None);
+    assert_eq!(card.border_crossings, None);
+    assert_eq!(card.load_unload_operations, None);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{hex, MockCard};
+use driver_card_reader::card::read_card_identification;
+
+/// `CardIdentification` of a Finnish driver card
+const CARD_IDENTIFICATION: &str = "
+    12 46 49 30 30 30 30 30 30 31 32 33 34 35 36 30 30 01 54 72 61 66 69 63 6F 6D 20 20 20 20 20 20
+    20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 5E 3C 9C 00 5E 3C 9C 00 67 F8 5E
+    00
+";
+
+/// `DriverCardHolderIdentification` of a Finnish driver card
+const DRIVER_CARD_HOLDER_IDENTIFICATION: &str = "
+    01 56 49 52 54 41 4E 45 4E 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20
+    20 20 20 20 01 4D 41 54 54 49 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20 20
+    20 20 20 20 20 20 20 20 19 85 04 23 66 69
+";
+
+#[test]
+fn reads_canned_identification() {
+    let card = MockCard::default()
+        .respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[])
+        .respond(&hex("00 A4 02 0C 02 05 20"), &[])
+        .respond(&hex("00 B0 00 00 41"), &hex(CARD_IDENTIFICATION))
+        .respond(&hex("00 B0 00 41 4E"), &hex(DRIVER_CARD_HOLDER_IDENTIFICATION));
+
+    let identification = read_card_identification(&card, None).unwrap();
+
+    assert_eq!(identification.issuing_member_state, 0x12);
+    assert_eq!(identification.issuing_member_state_alpha(), Some("FIN"));
+    assert_eq!(identification.card_number.raw, "FI00000012345600");
+    assert_eq!(identification.card_number.driver_identification, "FI000000123456");
+    assert!(!identification.card_number.is_replacement());
+    assert_eq!(identification.last_name, "VIRTANEN");
+    assert_eq!(identification.first_name, "MATTI");
+    assert_eq!(identification.birth_date, NaiveDate::from_ymd_opt(1985, 4, 23));
+    assert_eq!(identification.preferred_language, "fi");
+}
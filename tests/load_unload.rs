@@ -0,0 +1,30 @@
+mod common;
+
+use common::{hex, MockCard};
+use driver_card_reader::load_unload::read_load_unload_operations;
+
+#[test]
+fn returns_no_operations_without_load_unload_ef() {
+    // A Gen2v1 card has the Gen2 application but no load/unload operations EF
+    let card = MockCard::default().respond(&hex("00 A4 04 0C 06 FF 53 4D 52 44 54"), &[]);
+
+    assert_eq!(read_load_unload_operations(&card).unwrap(), Vec::new());
+}
+
+#[test]
+fn reads_only_the_records_of_the_card_capacity() {
+    // The application identification V2 gives room for one record, so whatever follows it is not read
+    let record = |time_stamp: &str| hex(&format!("{} 01 00 00 00 00 00 00 00 00 00 00 00 00 00 01 2C", time_stamp));
+    let mut operations = hex("00 00");
+    operations.extend_from_slice(&record("65 E5 63 60"));
+    operations.extend_from_slice(&record("65 E5 A2 A8"));
+    let card = MockCard::default()
+        .respond(&hex("00 A4 04 0C 06 FF 53 4D 52 44 54"), &[])
+        .ef([0x05, 0x25], &hex("00 08 00 00 00 01 00 00 00 00"))
+        .ef([0x05, 0x29], &operations);
+
+    let operations = read_load_unload_operations(&card).unwrap();
+
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0].odometer, 300);
+}
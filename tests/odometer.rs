@@ -0,0 +1,22 @@
+use driver_card_reader::parse::parse_odometer;
+use driver_card_reader::vehicles::VehicleUsed;
+
+#[test]
+fn parses_odometer_short() {
+    assert_eq!(parse_odometer(&[0x01, 0x86, 0xA0]), 100000);
+    assert_eq!(parse_odometer(&[0x00, 0x00, 0x00]), 0);
+    assert_eq!(parse_odometer(&[0xFF, 0xFF, 0xFF]), 16777215);
+}
+
+#[test]
+fn parses_vehicle_record_odometers() {
+    let mut record = vec![0x01, 0x86, 0xA0, 0x01, 0x87, 0x0B];
+    record.extend_from_slice(&[0x5E, 0x3C, 0x9C, 0x00, 0x5E, 0x3D, 0x00, 0x00]);
+    record.push(0x12);
+    record.extend_from_slice(b"\x01ABC-123      ");
+    record.extend_from_slice(&[0x00, 0x42]);
+
+    let vehicle = VehicleUsed::new(&record).unwrap().unwrap();
+    assert_eq!(vehicle.odometer_begin, 100000);
+    assert_eq!(vehicle.odometer_end, 100107);
+}
@@ -0,0 +1,45 @@
+use std::cell::RefCell;
+
+use driver_card_reader::apdu::read_ef_fully_with_min_chunk;
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::transport::CardTransport;
+
+/// A card rejecting every READ BINARY with the given status word, recording the requested lengths
+struct RejectingCard {
+    status_word: [u8; 2],
+    lengths: RefCell<Vec<u8>>,
+}
+
+impl CardTransport for RejectingCard {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        self.lengths.borrow_mut().push(apdu[4]);
+        Ok(self.status_word.to_vec())
+    }
+}
+
+#[test]
+fn stops_halving_the_chunk_size_at_one_byte() {
+    let card = RejectingCard { status_word: [0x67, 0x00], lengths: RefCell::default() };
+
+    let result = read_ef_fully_with_min_chunk(&card, 600, 0, None);
+
+    assert!(matches!(result, Err(CardReaderError::UnexpectedStatusWord(0x67, 0x00))));
+    assert_eq!(*card.lengths.borrow(), vec![255, 127, 63, 31, 15, 7, 3, 1]);
+}
+
+#[test]
+fn stops_retrying_a_card_that_keeps_asking_for_another_length() {
+    // Every read is also retried once with the length the card asks for
+    let card = RejectingCard { status_word: [0x6C, 0x10], lengths: RefCell::default() };
+
+    assert!(read_ef_fully_with_min_chunk(&card, 600, 0, None).is_err());
+    assert_eq!(*card.lengths.borrow(), vec![255, 16, 127, 16, 63, 16, 31, 16, 15, 16, 7, 16, 3, 16, 1, 16]);
+}
+
+#[test]
+fn fails_once_the_chunk_size_would_drop_below_the_minimum() {
+    let card = RejectingCard { status_word: [0x67, 0x00], lengths: RefCell::default() };
+
+    assert!(read_ef_fully_with_min_chunk(&card, 600, 16, None).is_err());
+    assert_eq!(*card.lengths.borrow(), vec![255, 127, 63, 31]);
+}
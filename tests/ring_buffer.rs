@@ -0,0 +1,42 @@
+mod common;
+
+use common::hex;
+use driver_card_reader::activity::parse_driver_activity;
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::vehicles::{parse_vehicles_used, VEHICLE_RECORD_LENGTH};
+
+/// `CardDriverActivity` with a 24 byte buffer holding one daily record, its newest record pointer damaged to `01 00`
+const CORRUPT_DRIVER_ACTIVITY: &str = "
+    00 00 01 00 00 00 00 14 65 E5 0F 00 00 12 01 2C 00 00 19 68 12 58 02 76 00 00 00 00
+";
+
+/// `CardVehiclesUsed` holding a single vehicle record, its newest record pointer damaged to `00 05`
+const CORRUPT_VEHICLES_USED: &str = "
+    00 05 01 E2 40 01 E3 6C 65 E5 63 60 65 E5 A2 A8 12 01 41 42 43 2D 31 32 33 20 20 20 20 20 20 00
+    07
+";
+
+#[test]
+fn rejects_activity_pointer_beyond_buffer() {
+    let result = parse_driver_activity(&hex(CORRUPT_DRIVER_ACTIVITY));
+
+    assert!(matches!(result, Err(CardReaderError::CorruptRingBuffer { pointer: 0x100, file_len: 24 })));
+}
+
+#[test]
+fn rejects_vehicle_pointer_beyond_records() {
+    let result = parse_vehicles_used(&hex(CORRUPT_VEHICLES_USED), VEHICLE_RECORD_LENGTH, 1);
+
+    assert!(matches!(result, Err(CardReaderError::CorruptRingBuffer { pointer: 5, file_len: 1 })));
+}
+
+#[test]
+fn walks_activity_with_valid_pointers() {
+    let mut data = hex(CORRUPT_DRIVER_ACTIVITY);
+    data[2] = 0x00;
+
+    let days = parse_driver_activity(&data).unwrap();
+
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].changes.len(), 4);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use common::hex;
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::secure_messaging::{SecureChannel, SessionKeys};
+use driver_card_reader::transport::CardTransport;
+
+// The expected APDUs were computed independently with AES-CBC and AES-CMAC for these keys
+
+/// A card answering with queued responses and recording the APDUs it was sent
+#[derive(Default)]
+struct ScriptedCard {
+    responses: RefCell<VecDeque<Vec<u8>>>,
+    sent: RefCell<Vec<Vec<u8>>>,
+}
+
+impl ScriptedCard {
+    fn new(responses: &[&str]) -> Self {
+        ScriptedCard { responses: RefCell::new(responses.iter().map(|response| hex(response)).collect()), sent: RefCell::default() }
+    }
+}
+
+impl CardTransport for ScriptedCard {
+    fn transmit(&self, apdu: &[u8]) -> Result<Vec<u8>, CardReaderError> {
+        self.sent.borrow_mut().push(apdu.to_vec());
+        Ok(self.responses.borrow_mut().pop_front().expect("No response left"))
+    }
+}
+
+fn keys() -> SessionKeys {
+    SessionKeys { encryption_key: hex("000102030405060708090a0b0c0d0e0f"), mac_key: hex("101112131415161718191a1b1c1d1e1f") }
+}
+
+const READ_RESPONSE: &str = "872101201a3b5742d54f01015abde79c988bed22b629185770817305754d688c288b68990290008e0828ab269891fef4de9000";
+
+#[test]
+fn wraps_and_unwraps_read_binary() {
+    let card = ScriptedCard::new(&[READ_RESPONSE]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+
+    let response = channel.transmit(&hex("00B0000010")).unwrap();
+    assert_eq!(card.sent.borrow()[0], hex("0cb000000d9701108e0850cfb2f9927acc8f00"));
+
+    let mut expected = (0x20..0x30).collect::<Vec<u8>>();
+    expected.extend_from_slice(&[0x90, 0x00]);
+    assert_eq!(response, expected);
+}
+
+#[test]
+fn increments_the_send_sequence_counter_for_every_apdu() {
+    let card = ScriptedCard::new(&[READ_RESPONSE, "990290008e086f3fe0257c588de39000"]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+
+    channel.transmit(&hex("00B0000010")).unwrap();
+    // The command is MACed with counter 3, after 1 for the READ BINARY and 2 for its response
+    let response = channel.transmit(&hex("00A4020C020501")).unwrap();
+    assert_eq!(card.sent.borrow()[1], hex("0ca4020c1d8711016afc38af08c0af2960360eb36f079afc8e08d3e144546b6c05a700"));
+    assert_eq!(response, vec![0x90, 0x00]);
+}
+
+#[test]
+fn rejects_a_response_macked_with_a_stale_counter() {
+    let card = ScriptedCard::new(&["872101ab4c7edee4fe15496ba518c8c65d72a488f05ed35b7da3ea41be8532bcf80728990290008e081fbb79d9f106af499000"]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+
+    assert!(matches!(channel.transmit(&hex("00B0000010")), Err(CardReaderError::SecureMessaging)));
+}
+
+#[test]
+fn pads_command_data_to_the_next_block() {
+    // 15 bytes of data fit in one padded block, 16 bytes need a second block for the padding
+    let card = ScriptedCard::new(&["6987"]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+    channel.transmit(&[&hex("002000000F")[..], &(0..15).collect::<Vec<u8>>()].concat()).unwrap();
+    assert_eq!(card.sent.borrow()[0], hex("0c2000001d87110114ca343f5b8987a3241e98937e84685b8e08fc4c971616f234cf00"));
+
+    let card = ScriptedCard::new(&["6987"]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+    channel.transmit(&[&hex("0020000010")[..], &(0..16).collect::<Vec<u8>>()].concat()).unwrap();
+    assert_eq!(
+        card.sent.borrow()[0],
+        hex("0c2000002d872101efe25b6c15aaeeeb7332d3466f3d32b11bacb73053febd51abb228445d9703058e08e8fa62afe978c79200")
+    );
+}
+
+#[test]
+fn rejects_a_mac_mismatch() {
+    let mut response = hex(READ_RESPONSE);
+    let mac_end = response.len() - 3;
+    response[mac_end] ^= 0x01;
+
+    let card = ScriptedCard { responses: RefCell::new(VecDeque::from([response])), sent: RefCell::default() };
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+    assert!(matches!(channel.transmit(&hex("00B0000010")), Err(CardReaderError::SecureMessaging)));
+}
+
+#[test]
+fn rejects_invalid_padding_of_response_data() {
+    let card = ScriptedCard::new(&["872101201a3b5742d54f01015abde79c988bed7dd32daebfa3d047930ed4dc6c0a8de1990290008e08ff8b6198c20c18f99000"]);
+    let channel = SecureChannel::new(&card, &keys()).unwrap();
+    assert!(matches!(channel.transmit(&hex("00B0000010")), Err(CardReaderError::SecureMessaging)));
+}
+
+#[test]
+fn rejects_unprotected_success_status_words() {
+    for status_word in ["9000", "6110", "6C10", "6282"] {
+        let card = ScriptedCard::new(&[status_word]);
+        let channel = SecureChannel::new(&card, &keys()).unwrap();
+        assert!(matches!(channel.transmit(&hex("00B0000010")), Err(CardReaderError::SecureMessaging)), "{}", status_word);
+    }
+}
+
+#[test]
+fn passes_unprotected_errors_through() {
+    for status_word in ["6987", "6988", "6A82"] {
+        let card = ScriptedCard::new(&[status_word]);
+        let channel = SecureChannel::new(&card, &keys()).unwrap();
+        let response = channel.transmit(&hex("00B0000010"));
+        assert_eq!(response.unwrap(), hex(status_word));
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{hex, MockCard};
+use driver_card_reader::apdu::{select_df, DfSelectionMethod};
+use driver_card_reader::card::{TACHOGRAPH_DF, TACHOGRAPH_GEN2_DF};
+use driver_card_reader::error::CardReaderError;
+
+/// The SELECT variants of each DF, with the card accepting only the given one
+const SELECT_VARIANTS: &[(&[u8], &str, DfSelectionMethod)] = &[
+    (TACHOGRAPH_DF, "00 A4 04 0C 06 FF 54 41 43 48 4F", DfSelectionMethod::FullAid),
+    (TACHOGRAPH_DF, "00 A4 04 00 06 FF 54 41 43 48 4F 00", DfSelectionMethod::FullAidWithFci),
+    (TACHOGRAPH_DF, "00 A4 04 00 05 FF 54 41 43 48 00", DfSelectionMethod::PartialAid),
+    (TACHOGRAPH_GEN2_DF, "00 A4 04 0C 06 FF 53 4D 52 44 54", DfSelectionMethod::FullAid),
+    (TACHOGRAPH_GEN2_DF, "00 A4 04 00 06 FF 53 4D 52 44 54 00", DfSelectionMethod::FullAidWithFci),
+    (TACHOGRAPH_GEN2_DF, "00 A4 04 00 05 FF 53 4D 52 44 00", DfSelectionMethod::PartialAid),
+];
+
+#[test]
+fn builds_select_variants() {
+    for (df, apdu, method) in SELECT_VARIANTS {
+        assert_eq!(method.apdu(df), hex(apdu), "{:?}", method);
+    }
+}
+
+#[test]
+fn falls_back_to_the_accepted_select_variant() {
+    for (df, apdu, method) in SELECT_VARIANTS {
+        let card = MockCard::default().respond(&hex(apdu), &[]);
+
+        let (selected_with, _) = select_df(&card, df).unwrap();
+
+        assert_eq!(selected_with, *method, "{}", apdu);
+    }
+}
+
+#[test]
+fn fails_when_no_select_variant_is_accepted() {
+    let card = MockCard::default();
+
+    let result = select_df(&card, TACHOGRAPH_DF);
+
+    assert!(matches!(result, Err(CardReaderError::UnexpectedStatusWord(0x6A, 0x82))));
+}
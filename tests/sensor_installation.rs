@@ -0,0 +1,59 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{hex, MockCard};
+use driver_card_reader::application::CardType;
+use driver_card_reader::error::CardReaderError;
+use driver_card_reader::sensor_installation::{read_sensor_installations, ExtendedSerialNumber};
+
+/// `WorkshopCardApplicationIdentification` of a Gen1 workshop card with two calibration records
+const APPLICATION_IDENTIFICATION: &str = "02 00 01 01 01 00 38 00 01 01 02";
+
+/// `WorkshopCardCalibrationData` with the newest record in the second slot and the first slot empty
+fn calibration_data() -> Vec<u8> {
+    let mut record = vec![0x02];
+    record.extend_from_slice(b"WDB9634031L123456");
+    record.extend_from_slice(&[0x00; 43]);
+    // Old time value and the new time value of 2024-03-04 06:00 UTC
+    record.extend_from_slice(&hex("65 E5 62 E8 65 E5 63 60"));
+    record.extend_from_slice(&hex("67 A9 5F 00"));
+    record.extend_from_slice(b"A2C12345678     ");
+    record.extend_from_slice(&hex("00 01 E2 40 03 24 01 0A"));
+    record.extend_from_slice(&hex("00 00 30 39 11 23 06 0B"));
+
+    let mut data = hex("00 07 01");
+    data.extend_from_slice(&[0x00; 105]);
+    data.extend_from_slice(&record);
+    data
+}
+
+#[test]
+fn reads_sensor_installations_from_the_calibration_records() {
+    let card = MockCard::default()
+        .respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[])
+        .ef([0x05, 0x01], &hex(APPLICATION_IDENTIFICATION))
+        .ef([0x05, 0x0A], &calibration_data());
+
+    let installations = read_sensor_installations(&card).unwrap();
+
+    assert_eq!(installations.len(), 1);
+    let installation = &installations[0];
+    assert_eq!(installation.calibration_purpose, 2);
+    assert_eq!(installation.vehicle_identification_number, "WDB9634031L123456");
+    assert_eq!(installation.vu_part_number, "A2C12345678");
+    assert_eq!(installation.pairing_time, Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap());
+    assert_eq!(
+        installation.vu_serial_number,
+        ExtendedSerialNumber { serial_number: 123456, manufacturing_month: 3, manufacturing_year: 2024, equipment_type: 1, manufacturer_code: 0x0A }
+    );
+    assert_eq!(
+        installation.sensor_serial_number,
+        ExtendedSerialNumber { serial_number: 12345, manufacturing_month: 11, manufacturing_year: 2023, equipment_type: 6, manufacturer_code: 0x0B }
+    );
+}
+
+#[test]
+fn refuses_to_read_sensor_installations_of_a_driver_card() {
+    let card = MockCard::default().respond(&hex("00 A4 04 0C 06 FF 54 41 43 48 4F"), &[]).ef([0x05, 0x01], &hex("01 00 01 01 01 00 38 00 01 01"));
+    assert!(matches!(read_sensor_installations(&card), Err(CardReaderError::UnsupportedCardType(CardType::Driver))));
+}